@@ -0,0 +1,77 @@
+use std::collections::{BTreeSet, HashMap};
+
+use serde::Serialize;
+
+/// A change to the default application of one MIME type
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DefaultChange {
+    pub mime: String,
+    /// Previous default, `None` if there was none
+    pub old: Option<String>,
+    /// New default, `None` if it was removed
+    pub new: Option<String>,
+}
+
+/// Compare two MIME type → default application maps, returning the changes
+/// sorted by MIME type
+pub fn diff_defaults(
+    old: &HashMap<String, String>,
+    new: &HashMap<String, String>,
+) -> Vec<DefaultChange> {
+    let mimes: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+
+    mimes
+        .into_iter()
+        .filter_map(|mime| {
+            let before = old.get(mime);
+            let after = new.get(mime);
+            (before != after).then(|| DefaultChange {
+                mime: mime.clone(),
+                old: before.cloned(),
+                new: after.cloned(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_defaults() {
+        let old = map(&[
+            ("text/plain", "gedit.desktop"),
+            ("image/png", "eog.desktop"),
+        ]);
+        let new = map(&[
+            ("text/plain", "code.desktop"),
+            ("text/html", "firefox.desktop"),
+            ("image/png", "eog.desktop"),
+        ]);
+
+        assert_eq!(
+            diff_defaults(&old, &new),
+            vec![
+                DefaultChange {
+                    mime: "text/html".into(),
+                    old: None,
+                    new: Some("firefox.desktop".into()),
+                },
+                DefaultChange {
+                    mime: "text/plain".into(),
+                    old: Some("gedit.desktop".into()),
+                    new: Some("code.desktop".into()),
+                },
+            ]
+        );
+        assert!(diff_defaults(&new, &new).is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use super::document::{
+    LineKind, MimeAppsDocument, ADDED_ASSOCIATIONS, DEFAULT_APPLICATIONS, REMOVED_ASSOCIATIONS,
+};
+use super::MimeAppsConfig;
+use crate::desktop::discovery::find_desktop_file;
+
+/// How serious a problem in a mimeapps.list file is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The line is ignored or can't work as intended
+    Error,
+    /// The line works but probably not as the author meant
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        })
+    }
+}
+
+/// A problem found on a specific line of a mimeapps.list file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    /// Line number, starting at 1
+    pub line: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.path.display(),
+            self.line,
+            self.severity,
+            self.message
+        )
+    }
+}
+
+/// Check a parsed mimeapps.list for malformed lines, unknown sections,
+/// duplicate keys and invalid MIME types or application IDs. With
+/// `check_installed`, also warn about applications that aren't installed.
+pub fn check_document(
+    doc: &MimeAppsDocument,
+    path: &Path,
+    check_installed: bool,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut section: Option<&str> = None;
+    let mut seen_keys: HashSet<(&str, &str)> = HashSet::new();
+
+    let mut push = |line: usize, severity: Severity, message: String| {
+        diagnostics.push(Diagnostic {
+            path: path.to_path_buf(),
+            line,
+            severity,
+            message,
+        });
+    };
+
+    for (index, kind) in doc.lines().enumerate() {
+        let line = index + 1;
+
+        match kind {
+            LineKind::Blank => {}
+            LineKind::Invalid(raw) => push(
+                line,
+                Severity::Error,
+                format!(
+                    "expected a [Section] header or key=value pair, found '{}'",
+                    raw.trim()
+                ),
+            ),
+            LineKind::Section(name) => {
+                if ![
+                    DEFAULT_APPLICATIONS,
+                    ADDED_ASSOCIATIONS,
+                    REMOVED_ASSOCIATIONS,
+                ]
+                .contains(&name)
+                {
+                    push(
+                        line,
+                        Severity::Warning,
+                        format!("unknown section [{}]", name),
+                    );
+                }
+                section = Some(name);
+            }
+            LineKind::Entry { key, values } => {
+                let Some(current) = section else {
+                    push(
+                        line,
+                        Severity::Error,
+                        format!("'{}' appears before any section", key),
+                    );
+                    continue;
+                };
+
+                if !seen_keys.insert((current, key)) {
+                    push(
+                        line,
+                        Severity::Warning,
+                        format!(
+                            "duplicate key '{}' in [{}], only the last one is used",
+                            key, current
+                        ),
+                    );
+                }
+
+                if let Err(e) = MimeAppsConfig::validate_mime_type(key) {
+                    push(line, Severity::Error, e.to_string());
+                }
+
+                if values.is_empty() {
+                    push(
+                        line,
+                        Severity::Warning,
+                        format!("no applications listed for '{}'", key),
+                    );
+                }
+
+                for app_id in values {
+                    if let Err(e) = MimeAppsConfig::validate_app_id(app_id) {
+                        push(line, Severity::Error, e.to_string());
+                    } else if check_installed
+                        && current != REMOVED_ASSOCIATIONS
+                        && find_desktop_file(app_id).is_none()
+                    {
+                        push(
+                            line,
+                            Severity::Warning,
+                            format!("application '{}' is not installed", app_id),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_document() {
+        let doc = MimeAppsDocument::parse(
+            "text/plain=gedit.desktop\n\
+             [Default Applications]\n\
+             text/plain=gedit.desktop;\n\
+             not a valid line\n\
+             badmime=gedit.desktop\n\
+             image/png=eog\n\
+             text/plain=code.desktop\n\
+             [Custom]\n",
+        );
+        let path = Path::new("mimeapps.list");
+
+        let diagnostics = check_document(&doc, path, false);
+        let found: Vec<(usize, Severity)> =
+            diagnostics.iter().map(|d| (d.line, d.severity)).collect();
+
+        assert_eq!(
+            found,
+            vec![
+                (1, Severity::Error),
+                (4, Severity::Error),
+                (5, Severity::Error),
+                (6, Severity::Error),
+                (7, Severity::Warning),
+                (8, Severity::Warning),
+            ]
+        );
+        assert_eq!(
+            diagnostics[5].to_string(),
+            "mimeapps.list:8: warning: unknown section [Custom]"
+        );
+    }
+}
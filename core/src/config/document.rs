@@ -0,0 +1,454 @@
+use std::fmt;
+
+/// Section holding the preferred application for each MIME type
+pub const DEFAULT_APPLICATIONS: &str = "Default Applications";
+/// Section holding extra "Open With" associations
+pub const ADDED_ASSOCIATIONS: &str = "Added Associations";
+/// Section holding associations the user has blocked
+pub const REMOVED_ASSOCIATIONS: &str = "Removed Associations";
+
+/// Read-only view of a document line, for validation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind<'a> {
+    /// Empty line or comment
+    Blank,
+    /// Section header with its name
+    Section(&'a str),
+    /// `key=value` pair with its parsed values
+    Entry { key: &'a str, values: &'a [String] },
+    /// Line that is neither of the above
+    Invalid(&'a str),
+}
+
+/// A single line of a mimeapps.list file
+#[derive(Debug, Clone)]
+enum Line {
+    /// Empty line, comment or anything we don't understand, kept verbatim
+    Verbatim(String),
+    /// Section header such as `[Default Applications]`
+    Section { raw: String, name: String },
+    /// `key=value;value;` pair
+    Entry(Entry),
+}
+
+/// A key=value line, keeping the original text until it is edited
+#[derive(Debug, Clone)]
+struct Entry {
+    /// Original text of the line, `None` once the entry has been modified
+    raw: Option<String>,
+    key: String,
+    values: Vec<String>,
+    /// Whether the value list ends with a `;`
+    trailing_semicolon: bool,
+}
+
+impl Entry {
+    fn render(&self) -> String {
+        let mut line = format!("{}={}", self.key, self.values.join(";"));
+        if self.trailing_semicolon && !self.values.is_empty() {
+            line.push(';');
+        }
+        line
+    }
+}
+
+/// Line-preserving representation of a single mimeapps.list file.
+///
+/// Unlike the merged [`MimeAppsConfig`](super::MimeAppsConfig) view, this keeps
+/// comments, blank lines, unknown sections and the original key order, so that
+/// serializing an unmodified document reproduces the input byte for byte and
+/// edits only touch the lines they change.
+#[derive(Debug, Clone)]
+pub struct MimeAppsDocument {
+    lines: Vec<Line>,
+    /// Whether the file ends with a newline
+    trailing_newline: bool,
+}
+
+impl Default for MimeAppsDocument {
+    fn default() -> Self {
+        Self {
+            lines: Vec::new(),
+            trailing_newline: true,
+        }
+    }
+}
+
+impl MimeAppsDocument {
+    /// Parse a document from file content
+    pub fn parse(content: &str) -> Self {
+        if content.is_empty() {
+            return Self::default();
+        }
+
+        let (body, trailing_newline) = match content.strip_suffix('\n') {
+            Some(body) => (body, true),
+            None => (content, false),
+        };
+
+        let lines = body.split('\n').map(Self::parse_line).collect();
+
+        Self {
+            lines,
+            trailing_newline,
+        }
+    }
+
+    fn parse_line(raw: &str) -> Line {
+        let trimmed = raw.trim();
+
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            return Line::Verbatim(raw.to_string());
+        }
+
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            return Line::Section {
+                raw: raw.to_string(),
+                name: trimmed[1..trimmed.len() - 1].to_string(),
+            };
+        }
+
+        if let Some((key, value)) = trimmed.split_once('=') {
+            let value = value.trim();
+            let values = value
+                .split(';')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+                .collect();
+
+            return Line::Entry(Entry {
+                raw: Some(raw.to_string()),
+                key: key.trim().to_string(),
+                values,
+                trailing_semicolon: value.ends_with(';'),
+            });
+        }
+
+        Line::Verbatim(raw.to_string())
+    }
+
+    /// Iterate over all lines in file order (line numbers are index + 1)
+    pub fn lines(&self) -> impl Iterator<Item = LineKind<'_>> {
+        self.lines.iter().map(|line| match line {
+            Line::Verbatim(raw) => {
+                let trimmed = raw.trim();
+                if trimmed.is_empty() || trimmed.starts_with('#') {
+                    LineKind::Blank
+                } else {
+                    LineKind::Invalid(raw)
+                }
+            }
+            Line::Section { name, .. } => LineKind::Section(name),
+            Line::Entry(entry) => LineKind::Entry {
+                key: &entry.key,
+                values: &entry.values,
+            },
+        })
+    }
+
+    /// Iterate over the entries of a section in file order
+    pub fn entries<'a>(
+        &'a self,
+        section: &'a str,
+    ) -> impl Iterator<Item = (&'a str, &'a [String])> + 'a {
+        let mut current: Option<&str> = None;
+        self.lines.iter().filter_map(move |line| match line {
+            Line::Section { name, .. } => {
+                current = Some(name.as_str());
+                None
+            }
+            Line::Entry(entry) if current == Some(section) => {
+                Some((entry.key.as_str(), entry.values.as_slice()))
+            }
+            _ => None,
+        })
+    }
+
+    /// Get the values of a key within a section (last occurrence wins)
+    pub fn get(&self, section: &str, key: &str) -> Option<&[String]> {
+        match &self.lines[self.find_entry(section, key)?] {
+            Line::Entry(entry) => Some(&entry.values),
+            _ => None,
+        }
+    }
+
+    /// Set the values of a key, editing the existing line in place when there
+    /// is one and appending to the section (creating it if needed) otherwise.
+    ///
+    /// Setting the values a key already has leaves the line untouched.
+    pub fn set(&mut self, section: &str, key: &str, values: Vec<String>) {
+        if let Some(index) = self.find_entry(section, key) {
+            if let Line::Entry(entry) = &mut self.lines[index] {
+                if entry.values != values {
+                    entry.values = values;
+                    entry.raw = None;
+                }
+            }
+            return;
+        }
+
+        let entry = Entry {
+            raw: None,
+            key: key.to_string(),
+            values,
+            trailing_semicolon: self.section_style(section),
+        };
+
+        match self.section_insert_position(section) {
+            Some(index) => self.lines.insert(index, Line::Entry(entry)),
+            None => {
+                // Separate the new section from existing content with a blank line
+                if self.lines.last().is_some_and(
+                    |line| !matches!(line, Line::Verbatim(raw) if raw.trim().is_empty()),
+                ) {
+                    self.lines.push(Line::Verbatim(String::new()));
+                }
+                self.lines.push(Line::Section {
+                    raw: format!("[{}]", section),
+                    name: section.to_string(),
+                });
+                self.lines.push(Line::Entry(entry));
+            }
+        }
+    }
+
+    /// Remove every occurrence of a key from a section.
+    /// Returns `true` if anything was removed.
+    pub fn remove(&mut self, section: &str, key: &str) -> bool {
+        let mut current: Option<String> = None;
+        let before = self.lines.len();
+
+        self.lines.retain(|line| match line {
+            Line::Section { name, .. } => {
+                current = Some(name.clone());
+                true
+            }
+            Line::Entry(entry) => !(current.as_deref() == Some(section) && entry.key == key),
+            Line::Verbatim(_) => true,
+        });
+
+        self.lines.len() != before
+    }
+
+    /// Index of the last line holding `key` in `section`
+    fn find_entry(&self, section: &str, key: &str) -> Option<usize> {
+        let mut current: Option<&str> = None;
+        let mut found = None;
+
+        for (index, line) in self.lines.iter().enumerate() {
+            match line {
+                Line::Section { name, .. } => current = Some(name.as_str()),
+                Line::Entry(entry) if current == Some(section) && entry.key == key => {
+                    found = Some(index);
+                }
+                _ => {}
+            }
+        }
+
+        found
+    }
+
+    /// Position right after the last entry of the first matching section,
+    /// or `None` if the section does not exist yet
+    fn section_insert_position(&self, section: &str) -> Option<usize> {
+        let start = self
+            .lines
+            .iter()
+            .position(|line| matches!(line, Line::Section { name, .. } if name == section))?;
+
+        let mut position = start + 1;
+        for (index, line) in self.lines.iter().enumerate().skip(start + 1) {
+            match line {
+                Line::Section { .. } => break,
+                Line::Entry(_) => position = index + 1,
+                Line::Verbatim(_) => {}
+            }
+        }
+
+        Some(position)
+    }
+
+    /// Trailing semicolon style for new entries, following the existing
+    /// entries of the section and defaulting to the spec's `;`-terminated form
+    fn section_style(&self, section: &str) -> bool {
+        let mut current: Option<&str> = None;
+
+        for line in &self.lines {
+            match line {
+                Line::Section { name, .. } => current = Some(name.as_str()),
+                Line::Entry(entry) if current == Some(section) => {
+                    return entry.trailing_semicolon;
+                }
+                _ => {}
+            }
+        }
+
+        true
+    }
+}
+
+impl fmt::Display for MimeAppsDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, line) in self.lines.iter().enumerate() {
+            if index > 0 {
+                f.write_str("\n")?;
+            }
+
+            match line {
+                Line::Verbatim(raw) | Line::Section { raw, .. } => f.write_str(raw)?,
+                Line::Entry(entry) => match &entry.raw {
+                    Some(raw) => f.write_str(raw)?,
+                    None => f.write_str(&entry.render())?,
+                },
+            }
+        }
+
+        if self.trailing_newline && !self.lines.is_empty() {
+            f.write_str("\n")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\
+# Managed by hand, keep tidy
+[Default Applications]
+x-scheme-handler/https=firefox.desktop
+text/plain=org.gnome.TextEditor.desktop;
+inode/directory=org.gnome.Nautilus.desktop
+
+[Added Associations]
+text/plain=org.gnome.TextEditor.desktop;code.desktop;
+image/png=org.gnome.Loupe.desktop;gimp.desktop;
+
+# Blocked handlers
+[Removed Associations]
+text/html=libreoffice-writer.desktop;
+";
+
+    #[test]
+    fn test_roundtrip_is_byte_identical() {
+        let inputs = [
+            SAMPLE,
+            "",
+            "[Default Applications]\ntext/plain=gedit.desktop",
+            "[Default Applications]\r\ntext/plain=gedit.desktop;\r\n",
+            "\n\n[Added Associations]\n  image/png = eog.desktop ; \n\n",
+            "[X-Custom]\nfoo=bar\nnot a key value line\n",
+        ];
+
+        for input in inputs {
+            assert_eq!(MimeAppsDocument::parse(input).to_string(), input);
+        }
+    }
+
+    #[test]
+    fn test_set_existing_edits_in_place() {
+        let mut doc = MimeAppsDocument::parse(SAMPLE);
+        doc.set(
+            DEFAULT_APPLICATIONS,
+            "text/plain",
+            vec!["code.desktop".into()],
+        );
+
+        let expected = SAMPLE.replace(
+            "text/plain=org.gnome.TextEditor.desktop;\ninode",
+            "text/plain=code.desktop;\ninode",
+        );
+        assert_eq!(doc.to_string(), expected);
+    }
+
+    #[test]
+    fn test_set_same_values_is_noop() {
+        let mut doc = MimeAppsDocument::parse(SAMPLE);
+        doc.set(
+            ADDED_ASSOCIATIONS,
+            "image/png",
+            vec!["org.gnome.Loupe.desktop".into(), "gimp.desktop".into()],
+        );
+        assert_eq!(doc.to_string(), SAMPLE);
+    }
+
+    #[test]
+    fn test_set_new_key_appends_to_section() {
+        let mut doc = MimeAppsDocument::parse(SAMPLE);
+        doc.set(
+            DEFAULT_APPLICATIONS,
+            "image/png",
+            vec!["org.gnome.Loupe.desktop".into()],
+        );
+
+        let expected = SAMPLE.replace(
+            "inode/directory=org.gnome.Nautilus.desktop\n",
+            "inode/directory=org.gnome.Nautilus.desktop\nimage/png=org.gnome.Loupe.desktop\n",
+        );
+        assert_eq!(doc.to_string(), expected);
+    }
+
+    #[test]
+    fn test_set_creates_missing_section() {
+        let mut doc =
+            MimeAppsDocument::parse("[Default Applications]\ntext/plain=gedit.desktop;\n");
+        doc.set(
+            REMOVED_ASSOCIATIONS,
+            "text/html",
+            vec!["gedit.desktop".into()],
+        );
+
+        assert_eq!(
+            doc.to_string(),
+            "[Default Applications]\ntext/plain=gedit.desktop;\n\n[Removed Associations]\ntext/html=gedit.desktop;\n"
+        );
+
+        let mut empty = MimeAppsDocument::default();
+        empty.set(
+            DEFAULT_APPLICATIONS,
+            "text/plain",
+            vec!["gedit.desktop".into()],
+        );
+        assert_eq!(
+            empty.to_string(),
+            "[Default Applications]\ntext/plain=gedit.desktop;\n"
+        );
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut doc = MimeAppsDocument::parse(SAMPLE);
+        assert!(doc.remove(DEFAULT_APPLICATIONS, "text/plain"));
+        assert!(!doc.remove(DEFAULT_APPLICATIONS, "text/plain"));
+        assert!(doc.get(DEFAULT_APPLICATIONS, "text/plain").is_none());
+        // The same key in another section is untouched
+        assert!(doc.get(ADDED_ASSOCIATIONS, "text/plain").is_some());
+        assert_eq!(
+            doc.to_string(),
+            SAMPLE.replace("text/plain=org.gnome.TextEditor.desktop;\ninode", "inode")
+        );
+    }
+
+    #[test]
+    fn test_get_and_entries() {
+        let doc = MimeAppsDocument::parse(SAMPLE);
+        assert_eq!(
+            doc.get(ADDED_ASSOCIATIONS, "text/plain"),
+            Some(
+                &[
+                    "org.gnome.TextEditor.desktop".to_string(),
+                    "code.desktop".to_string()
+                ][..]
+            )
+        );
+
+        let keys: Vec<&str> = doc.entries(DEFAULT_APPLICATIONS).map(|(k, _)| k).collect();
+        assert_eq!(
+            keys,
+            vec!["x-scheme-handler/https", "text/plain", "inode/directory"]
+        );
+    }
+}
@@ -0,0 +1,1385 @@
+use std::collections::{BTreeSet, HashMap};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::changes::DefaultChange;
+use super::diagnostics::{check_document, Diagnostic};
+use super::document::{
+    MimeAppsDocument, ADDED_ASSOCIATIONS, DEFAULT_APPLICATIONS, REMOVED_ASSOCIATIONS,
+};
+use super::settings::Settings;
+use crate::utils::atomic::write_atomic;
+use crate::utils::exec::run_post_save_hook;
+
+/// Manages MIME type application associations via mimeapps.list.
+///
+/// Serializing gives the merged associations only; the files, settings and
+/// diagnostics they came from are left out.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MimeAppsConfig {
+    /// Default applications for each MIME type
+    pub default_apps: HashMap<String, Vec<String>>,
+    /// Additional associations (shown in "Open With" menus)
+    pub added_associations: HashMap<String, Vec<String>>,
+    /// Explicitly removed associations
+    pub removed_associations: HashMap<String, Vec<String>>,
+    /// Path to the user's config file (where we write changes)
+    #[serde(skip)]
+    path: PathBuf,
+    /// The user's config file, edited in place on changes
+    #[serde(skip)]
+    user: MimeAppsDocument,
+    /// The user's config file as last read or written, to tell what changed
+    #[serde(skip)]
+    saved: MimeAppsDocument,
+    /// xdg-chooser's own preferences
+    #[serde(skip)]
+    pub settings: Settings,
+    /// Demo mode: changes are kept in memory and never written
+    #[serde(skip)]
+    in_memory: bool,
+    /// Problems found in the files that were merged
+    #[serde(skip)]
+    diagnostics: Vec<Diagnostic>,
+    /// File each default application was read from (or will be saved to)
+    #[serde(skip)]
+    default_sources: HashMap<String, PathBuf>,
+    /// Defaults set by files that take priority over the user's own
+    #[serde(skip)]
+    overrides: HashMap<String, Override>,
+}
+
+/// Desktops from `XDG_CURRENT_DESKTOP`, lowercase, most specific first
+fn current_desktops() -> Vec<String> {
+    parse_desktops(&env::var("XDG_CURRENT_DESKTOP").unwrap_or_default())
+}
+
+/// Split a colon-separated desktop list such as "ubuntu:GNOME", which the
+/// spec looks up one desktop-specific file for each entry of
+fn parse_desktops(value: &str) -> Vec<String> {
+    let mut desktops: Vec<String> = Vec::new();
+    for desktop in value.split(':').filter(|desktop| !desktop.is_empty()) {
+        let desktop = desktop.to_lowercase();
+        if !desktops.contains(&desktop) {
+            desktops.push(desktop);
+        }
+    }
+    desktops
+}
+
+/// Name of a desktop's mimeapps.list, e.g. `gnome-mimeapps.list`
+fn desktop_file_name(desktop: &str) -> String {
+    format!("{}-mimeapps.list", desktop)
+}
+
+/// Directory of drop-in fragments, under the user's and each system config
+/// directory
+const DROP_IN_DIR: &str = "xdg-chooser/mimeapps.d";
+
+/// The `*.list` fragments in a drop-in directory, highest priority first.
+/// As in other `.d` directories, names that sort later win, so
+/// `90-local.list` can override `10-site.list`.
+fn drop_in_fragments(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut fragments: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "list") && path.is_file())
+        .collect();
+    fragments.sort();
+    fragments.reverse();
+    fragments
+}
+
+/// The files one save writes, taken from the configuration by
+/// [`MimeAppsConfig::prepare_save`] so they can be written on another
+/// thread; fsync on a slow home directory can take seconds
+#[derive(Debug)]
+pub struct SaveJob {
+    path: PathBuf,
+    /// The user's file as it is written
+    user: MimeAppsDocument,
+    /// MIME types whose entries differ from the file on disk
+    changed: Vec<String>,
+    /// Desktop-specific file to copy the changed entries to
+    desktop_path: Option<PathBuf>,
+    /// Legacy location to mirror the user's file to
+    legacy_path: Option<PathBuf>,
+    post_save_hook: Option<String>,
+}
+
+impl SaveJob {
+    /// Write the user's file and the copies the settings ask for, then run
+    /// the post-save hook. Only a failure to write the user's own file is
+    /// an error. Returns the desktop-specific file if it was brought in
+    /// line, for [`MimeAppsConfig::finish_save`].
+    pub fn run(&self) -> Result<Option<PathBuf>> {
+        let content = self.user.to_string();
+        write_atomic(&self.path, &content)?;
+
+        let mut desktop_synced = None;
+        if let Some(desktop_path) = &self.desktop_path {
+            if !self.changed.is_empty() {
+                match sync_desktop_file(&self.user, desktop_path, &self.changed) {
+                    Ok(()) => desktop_synced = Some(desktop_path.clone()),
+                    Err(e) => {
+                        tracing::warn!("Failed to update {}: {:#}", desktop_path.display(), e)
+                    }
+                }
+            }
+        }
+
+        if let Some(legacy) = &self.legacy_path {
+            if let Err(e) = write_atomic(legacy, &content) {
+                tracing::warn!("Failed to mirror to the legacy location: {:#}", e);
+            }
+        }
+
+        if let Some(hook) = &self.post_save_hook {
+            if !self.changed.is_empty() {
+                if let Err(e) = run_post_save_hook(hook, &self.path, &self.changed) {
+                    tracing::warn!("{:#}", e);
+                }
+            }
+        }
+
+        Ok(desktop_synced)
+    }
+}
+
+/// Copy the entries of the `changed` MIME types from the user's file into
+/// a desktop-specific one, leaving its other entries alone
+fn sync_desktop_file(user: &MimeAppsDocument, path: &Path, changed: &[String]) -> Result<()> {
+    let original = if path.exists() {
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?
+    } else {
+        String::new()
+    };
+
+    let mut doc = MimeAppsDocument::parse(&original);
+    for section in [
+        DEFAULT_APPLICATIONS,
+        ADDED_ASSOCIATIONS,
+        REMOVED_ASSOCIATIONS,
+    ] {
+        for mime in changed {
+            match user.get(section, mime) {
+                Some(apps) => doc.set(section, mime, apps.to_vec()),
+                None => {
+                    doc.remove(section, mime);
+                }
+            }
+        }
+    }
+
+    let content = doc.to_string();
+    if content != original {
+        write_atomic(path, &content)?;
+    }
+    Ok(())
+}
+
+/// A default from a file that takes priority over the user's mimeapps.list,
+/// such as `~/.config/gnome-mimeapps.list`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Override {
+    pub path: PathBuf,
+    pub apps: Vec<String>,
+}
+
+/// Parsed content from a single mimeapps.list file
+#[derive(Debug, Default)]
+struct ParsedMimeApps {
+    default_apps: HashMap<String, Vec<String>>,
+    added_associations: HashMap<String, Vec<String>>,
+    removed_associations: HashMap<String, Vec<String>>,
+}
+
+impl ParsedMimeApps {
+    fn from_document(doc: &MimeAppsDocument) -> Self {
+        let mut parsed = Self::default();
+
+        for (section, map) in [
+            (DEFAULT_APPLICATIONS, &mut parsed.default_apps),
+            (ADDED_ASSOCIATIONS, &mut parsed.added_associations),
+            (REMOVED_ASSOCIATIONS, &mut parsed.removed_associations),
+        ] {
+            for (mime, apps) in doc.entries(section) {
+                if !apps.is_empty() {
+                    map.insert(mime.to_string(), apps.to_vec());
+                }
+            }
+        }
+
+        parsed
+    }
+}
+
+impl MimeAppsConfig {
+    /// Load the user's mimeapps.list configuration, merging from all XDG locations
+    /// per the MIME Applications Associations specification.
+    ///
+    /// Priority order (highest to lowest):
+    /// 1. ~/.config/<desktop>-mimeapps.list (desktop-specific user config,
+    ///    one per desktop in `XDG_CURRENT_DESKTOP`, in its order)
+    /// 2. ~/.config/mimeapps.list (user config)
+    /// 3. ~/.config/xdg-chooser/mimeapps.d/*.list (user drop-in fragments)
+    /// 4. /etc/xdg/<desktop>-mimeapps.list (desktop-specific system config)
+    /// 5. /etc/xdg/mimeapps.list (system config)
+    /// 6. /etc/xdg/xdg-chooser/mimeapps.d/*.list (system drop-in fragments)
+    /// 7. ~/.local/share/applications/mimeapps.list (user data)
+    /// 8. /usr/share/applications/mimeapps.list (system data)
+    ///
+    /// Drop-in fragments are only ever read; changes go to the user config.
+    pub fn load() -> Result<Self> {
+        let user_config = Self::user_config_path()?;
+
+        // Only files that exist take part in the merge
+        let layer_paths = Self::layer_paths()?;
+        // Layers whose defaults win over the user's file
+        let above_user: Vec<PathBuf> = layer_paths
+            .iter()
+            .take_while(|path| **path != user_config)
+            .cloned()
+            .collect();
+        let config_paths: Vec<PathBuf> = layer_paths
+            .into_iter()
+            .filter(|path| path.exists())
+            .collect();
+
+        // Keep the user's own file verbatim so saving only touches edited lines
+        let user = if user_config.exists() {
+            let content = fs::read_to_string(&user_config)
+                .with_context(|| format!("Failed to read {}", user_config.display()))?;
+            MimeAppsDocument::parse(&content)
+        } else {
+            MimeAppsDocument::default()
+        };
+
+        let settings = Settings::load().unwrap_or_else(|e| {
+            tracing::warn!("Failed to load settings: {:#}, using defaults", e);
+            Settings::default()
+        });
+
+        // Merge all configs (lower priority files are loaded first, higher priority overwrites)
+        let mut merged = MimeAppsConfig {
+            path: user_config, // We always write to ~/.config/mimeapps.list
+            saved: user.clone(),
+            user,
+            settings,
+            ..Default::default()
+        };
+
+        // Load in reverse order so higher priority files overwrite lower priority
+        for path in config_paths.into_iter().rev() {
+            let doc = match Self::read_document(&path) {
+                Ok(doc) => doc,
+                Err(e) => {
+                    tracing::warn!("{:#}", e);
+                    continue;
+                }
+            };
+            for diagnostic in check_document(&doc, &path, false) {
+                tracing::warn!("{}", diagnostic);
+                merged.diagnostics.push(diagnostic);
+            }
+            let parsed = ParsedMimeApps::from_document(&doc);
+            for (mime, apps) in &parsed.default_apps {
+                merged.default_sources.insert(mime.clone(), path.clone());
+                if above_user.contains(&path) {
+                    merged.overrides.insert(
+                        mime.clone(),
+                        Override {
+                            path: path.clone(),
+                            apps: apps.clone(),
+                        },
+                    );
+                }
+            }
+            merged.merge_from(parsed);
+        }
+
+        let mut overridden: Vec<&String> = merged.overrides.keys().collect();
+        overridden.sort();
+        for mime in overridden {
+            if let Some(found) = merged.overriding_default(mime) {
+                tracing::warn!(
+                    "{} sets the default for {} to {}, overriding {}; changes to it won't take \
+                     effect until that entry is removed",
+                    found.path.display(),
+                    mime,
+                    found.apps.join(";"),
+                    merged.path.display()
+                );
+            }
+        }
+
+        Ok(merged)
+    }
+
+    /// Synthetic configuration for demo mode, without touching the filesystem
+    pub fn demo() -> Self {
+        let user = MimeAppsDocument::parse(crate::desktop::demo::DEMO_MIMEAPPS);
+        let mut config = MimeAppsConfig {
+            saved: user.clone(),
+            in_memory: true,
+            ..Default::default()
+        };
+        config.merge_from(ParsedMimeApps::from_document(&user));
+        config.user = user;
+        config
+    }
+
+    /// Path of the user's mimeapps.list, where all changes are written
+    pub fn user_config_path() -> Result<PathBuf> {
+        let xdg_dirs = xdg::BaseDirectories::new()
+            .context("Failed to determine XDG directories")?;
+
+        Ok(xdg_dirs.get_config_home().join("mimeapps.list"))
+    }
+
+    /// The current desktop's user mimeapps.list, which takes priority over
+    /// the generic one: the first desktop's when `XDG_CURRENT_DESKTOP` lists
+    /// several (e.g. "ubuntu:GNOME"). `None` when it is unset.
+    pub fn desktop_config_path() -> Result<Option<PathBuf>> {
+        let Some(desktop) = current_desktops().into_iter().next() else {
+            return Ok(None);
+        };
+
+        let xdg_dirs = xdg::BaseDirectories::new()
+            .context("Failed to determine XDG directories")?;
+
+        Ok(Some(
+            xdg_dirs.get_config_home().join(desktop_file_name(&desktop)),
+        ))
+    }
+
+    /// Deprecated per-user location under the data directory, which some
+    /// older applications read instead of the config one
+    pub fn legacy_data_path() -> Result<PathBuf> {
+        let xdg_dirs = xdg::BaseDirectories::new()
+            .context("Failed to determine XDG directories")?;
+
+        Ok(xdg_dirs.get_data_home().join("applications/mimeapps.list"))
+    }
+
+    /// All mimeapps.list locations in priority order (highest first), including
+    /// ones that don't exist yet. See [`load`](Self::load) for the order.
+    pub fn layer_paths() -> Result<Vec<PathBuf>> {
+        let xdg_dirs = xdg::BaseDirectories::new()
+            .context("Failed to determine XDG directories")?;
+
+        let desktops = current_desktops();
+
+        let mut config_paths: Vec<PathBuf> = Vec::new();
+
+        // User config directory (~/.config)
+        let config_home = xdg_dirs.get_config_home();
+
+        // Desktop-specific user config (highest priority)
+        for desktop in &desktops {
+            config_paths.push(config_home.join(desktop_file_name(desktop)));
+        }
+
+        // User config
+        config_paths.push(config_home.join("mimeapps.list"));
+        config_paths.extend(drop_in_fragments(&config_home.join(DROP_IN_DIR)));
+
+        // System config directories (/etc/xdg)
+        let config_dirs = env::var("XDG_CONFIG_DIRS")
+            .unwrap_or_else(|_| "/etc/xdg".to_string());
+
+        for dir in config_dirs.split(':') {
+            if dir.is_empty() {
+                continue;
+            }
+            let dir_path = PathBuf::from(dir);
+
+            // Desktop-specific system config
+            for desktop in &desktops {
+                config_paths.push(dir_path.join(desktop_file_name(desktop)));
+            }
+
+            // System config
+            config_paths.push(dir_path.join("mimeapps.list"));
+            config_paths.extend(drop_in_fragments(&dir_path.join(DROP_IN_DIR)));
+        }
+
+        // Data directories for associations
+        // User data directory (~/.local/share/applications)
+        config_paths.push(Self::legacy_data_path()?);
+
+        // System data directories (/usr/share/applications, etc.)
+        let data_dirs = env::var("XDG_DATA_DIRS")
+            .unwrap_or_else(|_| "/usr/local/share:/usr/share".to_string());
+
+        for dir in data_dirs.split(':') {
+            if dir.is_empty() {
+                continue;
+            }
+            config_paths.push(PathBuf::from(dir).join("applications/mimeapps.list"));
+        }
+
+        Ok(config_paths)
+    }
+
+    /// Whether a layer is a drop-in fragment, which xdg-chooser reads but
+    /// never writes
+    pub fn is_drop_in(path: &Path) -> bool {
+        path.parent().is_some_and(|dir| dir.ends_with(DROP_IN_DIR))
+    }
+
+    /// Read the default application of every MIME type in a single file,
+    /// without merging other layers. Missing or unreadable files yield nothing.
+    pub fn read_file_defaults(path: &Path) -> HashMap<String, String> {
+        Self::parse_file(path)
+            .map(|parsed| {
+                parsed
+                    .default_apps
+                    .into_iter()
+                    .filter_map(|(mime, apps)| Some((mime, apps.into_iter().next()?)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Merge another parsed config into this one (other takes priority)
+    fn merge_from(&mut self, other: ParsedMimeApps) {
+        // For default apps, later entries override earlier ones
+        for (mime, apps) in other.default_apps {
+            self.default_apps.insert(mime, apps);
+        }
+
+        // For added associations, merge the lists
+        for (mime, apps) in other.added_associations {
+            let entry = self.added_associations.entry(mime).or_default();
+            for app in apps {
+                if !entry.contains(&app) {
+                    entry.push(app);
+                }
+            }
+        }
+
+        // For removed associations, merge the lists
+        for (mime, apps) in other.removed_associations {
+            let entry = self.removed_associations.entry(mime).or_default();
+            for app in apps {
+                if !entry.contains(&app) {
+                    entry.push(app);
+                }
+            }
+        }
+    }
+
+    fn parse_file(path: &Path) -> Result<ParsedMimeApps> {
+        Ok(ParsedMimeApps::from_document(&Self::read_document(path)?))
+    }
+
+    /// Read one mimeapps.list file, empty if it doesn't exist
+    pub fn read_document(path: &Path) -> Result<MimeAppsDocument> {
+        if !path.exists() {
+            return Ok(MimeAppsDocument::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+
+        Ok(MimeAppsDocument::parse(&content))
+    }
+
+    /// Validate a MIME type format (must be type/subtype)
+    pub fn validate_mime_type(mime: &str) -> Result<()> {
+        if !mime.contains('/') {
+            bail!("Invalid MIME type format '{}': must be type/subtype", mime);
+        }
+
+        let parts: Vec<&str> = mime.split('/').collect();
+        if parts.len() != 2 {
+            bail!(
+                "Invalid MIME type format '{}': must have exactly one '/'",
+                mime
+            );
+        }
+
+        let (type_part, subtype) = (parts[0], parts[1]);
+
+        if type_part.is_empty() {
+            bail!("Invalid MIME type '{}': type cannot be empty", mime);
+        }
+
+        if subtype.is_empty() {
+            bail!("Invalid MIME type '{}': subtype cannot be empty", mime);
+        }
+
+        // Check for valid characters (alphanumeric, dash, dot, plus, x- prefix for extensions)
+        for part in [type_part, subtype] {
+            if !part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.' || c == '+')
+            {
+                bail!(
+                    "Invalid MIME type '{}': contains invalid characters",
+                    mime
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate a desktop file ID format
+    pub fn validate_app_id(app_id: &str) -> Result<()> {
+        if !app_id.ends_with(".desktop") {
+            bail!(
+                "Invalid application ID '{}': must end with .desktop",
+                app_id
+            );
+        }
+
+        if app_id.len() <= 8 {
+            // Just ".desktop"
+            bail!("Invalid application ID '{}': name cannot be empty", app_id);
+        }
+
+        Ok(())
+    }
+
+    /// Malformed lines, unknown sections and invalid values found while
+    /// loading, highest priority file last
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// The user's own mimeapps.list, with unsaved changes
+    pub fn user_document(&self) -> &MimeAppsDocument {
+        &self.user
+    }
+
+    /// The mimeapps.list file the default for a MIME type comes from.
+    /// `None` without a default and in demo mode.
+    pub fn default_source(&self, mime: &str) -> Option<&Path> {
+        self.default_sources.get(mime).map(PathBuf::as_path)
+    }
+
+    /// A default for a MIME type that the user's file sets differently but
+    /// a higher priority file overrides, so the user's choice has no effect
+    pub fn overriding_default(&self, mime: &str) -> Option<&Override> {
+        let own = self.user.get(DEFAULT_APPLICATIONS, mime)?;
+        self.overrides
+            .get(mime)
+            .filter(|found| found.apps.as_slice() != own)
+    }
+
+    /// Remove the default for a MIME type from the file that overrides the
+    /// user's, so the user's default applies
+    pub fn remove_override(&mut self, mime: &str) -> Result<()> {
+        if self.settings.locked {
+            bail!("Default applications are locked and can't be changed");
+        }
+        let Some(found) = self.overrides.remove(mime) else {
+            return Ok(());
+        };
+
+        if !self.in_memory {
+            let mut doc = Self::read_document(&found.path)?;
+            if doc.remove(DEFAULT_APPLICATIONS, mime) {
+                write_atomic(&found.path, &doc.to_string())?;
+            }
+        }
+
+        if let Some(apps) = self.user.get(DEFAULT_APPLICATIONS, mime) {
+            self.default_apps.insert(mime.to_string(), apps.to_vec());
+            self.default_sources
+                .insert(mime.to_string(), self.path.clone());
+        }
+        Ok(())
+    }
+
+    /// Get the default application for a MIME type
+    pub fn get_default(&self, mime: &str) -> Option<&str> {
+        self.default_apps
+            .get(mime)
+            .and_then(|apps| apps.first())
+            .map(|s| s.as_str())
+    }
+
+    /// Every MIME type's default application after merging all layers
+    pub fn effective_defaults(&self) -> HashMap<String, String> {
+        self.default_apps
+            .iter()
+            .filter_map(|(mime, apps)| Some((mime.clone(), apps.first()?.clone())))
+            .collect()
+    }
+
+    /// Whether this is demo mode's in-memory configuration
+    pub fn is_in_memory(&self) -> bool {
+        self.in_memory
+    }
+
+    /// Count how many of the given MIME types have `app_id` as their default
+    pub fn default_count(&self, mimes: &[impl AsRef<str>], app_id: &str) -> usize {
+        mimes
+            .iter()
+            .filter(|mime| self.get_default(mime.as_ref()) == Some(app_id))
+            .count()
+    }
+
+    /// Set the default application for a MIME type
+    pub fn set_default(&mut self, mime: &str, app_id: &str) -> Result<()> {
+        // Validate inputs
+        Self::validate_mime_type(mime)?;
+        Self::validate_app_id(app_id)?;
+
+        // Set as the default
+        self.default_apps
+            .insert(mime.to_string(), vec![app_id.to_string()]);
+        if !self.in_memory {
+            self.default_sources
+                .insert(mime.to_string(), self.path.clone());
+        }
+
+        // Also add to associations if not present
+        let associations = self
+            .added_associations
+            .entry(mime.to_string())
+            .or_default();
+
+        // Remove if already present (to avoid duplicates)
+        associations.retain(|a| a != app_id);
+        // Insert at the beginning
+        associations.insert(0, app_id.to_string());
+
+        // Mirror the change into the user's file, leaving the association
+        // line alone when the app already leads it
+        self.user
+            .set(DEFAULT_APPLICATIONS, mime, vec![app_id.to_string()]);
+
+        self.edit_user_list(ADDED_ASSOCIATIONS, mime, |apps| {
+            if apps.first().map(String::as_str) != Some(app_id) {
+                apps.retain(|a| a != app_id);
+                apps.insert(0, app_id.to_string());
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Set the default application for multiple MIME types
+    pub fn set_default_for_mimes(&mut self, mimes: &[&str], app_id: &str) -> Result<()> {
+        for mime in mimes {
+            self.set_default(mime, app_id)?;
+        }
+        Ok(())
+    }
+
+    /// Remove the default application for a MIME type.
+    /// Returns `true` if the user's config file had a default to remove.
+    pub fn remove_default(&mut self, mime: &str) -> bool {
+        self.default_apps.remove(mime);
+        self.default_sources.remove(mime);
+        self.user.remove(DEFAULT_APPLICATIONS, mime)
+    }
+
+    /// Add an application to the "Open With" associations for a MIME type,
+    /// lifting any block on it. Returns `true` if the user's file changed.
+    pub fn add_association(&mut self, mime: &str, app_id: &str) -> Result<bool> {
+        Self::validate_mime_type(mime)?;
+        Self::validate_app_id(app_id)?;
+
+        Self::edit_list(&mut self.added_associations, mime, |apps| {
+            if !apps.iter().any(|a| a == app_id) {
+                apps.push(app_id.to_string());
+            }
+        });
+        Self::edit_list(&mut self.removed_associations, mime, |apps| {
+            apps.retain(|a| a != app_id);
+        });
+
+        let added = self.edit_user_list(ADDED_ASSOCIATIONS, mime, |apps| {
+            if !apps.iter().any(|a| a == app_id) {
+                apps.push(app_id.to_string());
+            }
+        });
+        let unblocked = self.edit_user_list(REMOVED_ASSOCIATIONS, mime, |apps| {
+            apps.retain(|a| a != app_id);
+        });
+
+        Ok(added || unblocked)
+    }
+
+    /// Block an application from being offered for a MIME type, dropping it
+    /// from the added associations. Returns `true` if the user's file changed.
+    pub fn remove_association(&mut self, mime: &str, app_id: &str) -> Result<bool> {
+        Self::validate_mime_type(mime)?;
+        Self::validate_app_id(app_id)?;
+
+        Self::edit_list(&mut self.removed_associations, mime, |apps| {
+            if !apps.iter().any(|a| a == app_id) {
+                apps.push(app_id.to_string());
+            }
+        });
+        Self::edit_list(&mut self.added_associations, mime, |apps| {
+            apps.retain(|a| a != app_id);
+        });
+
+        let blocked = self.edit_user_list(REMOVED_ASSOCIATIONS, mime, |apps| {
+            if !apps.iter().any(|a| a == app_id) {
+                apps.push(app_id.to_string());
+            }
+        });
+        let unadded = self.edit_user_list(ADDED_ASSOCIATIONS, mime, |apps| {
+            apps.retain(|a| a != app_id);
+        });
+
+        Ok(blocked || unadded)
+    }
+
+    /// Drop an application from the added associations of a MIME type
+    /// without blocking it, so it is only offered if it declares the type.
+    /// Returns `true` if the user's file changed.
+    pub fn unassociate(&mut self, mime: &str, app_id: &str) -> bool {
+        Self::edit_list(&mut self.added_associations, mime, |apps| {
+            apps.retain(|a| a != app_id);
+        });
+        self.edit_user_list(ADDED_ASSOCIATIONS, mime, |apps| {
+            apps.retain(|a| a != app_id);
+        })
+    }
+
+    /// Point every entry naming `from` at `to` instead, such as a native
+    /// package's desktop file ID at its Flatpak's. Defaults from any file
+    /// are overridden in the user's file; the user's other entries are
+    /// rewritten. Returns the MIME types that changed.
+    pub fn replace_app(&mut self, from: &str, to: &str) -> Result<Vec<String>> {
+        Self::validate_app_id(to)?;
+        let mut changed = BTreeSet::new();
+
+        let defaults: Vec<String> = self
+            .default_apps
+            .iter()
+            .filter(|(_, apps)| apps.first().is_some_and(|app| app == from))
+            .map(|(mime, _)| mime.clone())
+            .collect();
+        for mime in defaults {
+            self.set_default(&mime, to)?;
+            changed.insert(mime);
+        }
+
+        for section in [
+            DEFAULT_APPLICATIONS,
+            ADDED_ASSOCIATIONS,
+            REMOVED_ASSOCIATIONS,
+        ] {
+            let mimes: Vec<String> = self
+                .user
+                .entries(section)
+                .filter(|(_, apps)| apps.iter().any(|app| app == from))
+                .map(|(mime, _)| mime.to_string())
+                .collect();
+            for mime in mimes {
+                if self.edit_user_list(section, &mime, |apps| replace_in(apps, from, to)) {
+                    changed.insert(mime);
+                }
+            }
+        }
+
+        for map in [
+            &mut self.default_apps,
+            &mut self.added_associations,
+            &mut self.removed_associations,
+        ] {
+            for apps in map.values_mut() {
+                replace_in(apps, from, to);
+            }
+        }
+
+        Ok(changed.into_iter().collect())
+    }
+
+    /// Edit one list of the merged view, dropping the key once it is empty
+    fn edit_list(
+        map: &mut HashMap<String, Vec<String>>,
+        mime: &str,
+        edit: impl FnOnce(&mut Vec<String>),
+    ) {
+        let apps = map.entry(mime.to_string()).or_default();
+        edit(apps);
+        if apps.is_empty() {
+            map.remove(mime);
+        }
+    }
+
+    /// Edit one list in the user's file, dropping the key once it is empty.
+    /// Returns `true` if the list changed.
+    fn edit_user_list(
+        &mut self,
+        section: &str,
+        mime: &str,
+        edit: impl FnOnce(&mut Vec<String>),
+    ) -> bool {
+        let original = self
+            .user
+            .get(section, mime)
+            .map(<[String]>::to_vec)
+            .unwrap_or_default();
+
+        let mut apps = original.clone();
+        edit(&mut apps);
+
+        if apps == original {
+            return false;
+        }
+
+        if apps.is_empty() {
+            self.user.remove(section, mime);
+        } else {
+            self.user.set(section, mime, apps);
+        }
+        true
+    }
+
+    /// Check whether an application has been blocked for a MIME type
+    pub fn is_removed(&self, mime: &str, app_id: &str) -> bool {
+        self.removed_associations
+            .get(mime)
+            .is_some_and(|apps| apps.iter().any(|a| a == app_id))
+    }
+
+    /// Get all applications associated with a MIME type
+    pub fn get_associations(&self, mime: &str) -> Vec<&str> {
+        let mut apps = Vec::new();
+
+        // Add default first
+        if let Some(default_apps) = self.default_apps.get(mime) {
+            for app in default_apps {
+                if !apps.contains(&app.as_str()) {
+                    apps.push(app.as_str());
+                }
+            }
+        }
+
+        // Add other associations
+        if let Some(added) = self.added_associations.get(mime) {
+            for app in added {
+                if !apps.contains(&app.as_str()) {
+                    apps.push(app.as_str());
+                }
+            }
+        }
+
+        // Filter out removed associations
+        if let Some(removed) = self.removed_associations.get(mime) {
+            apps.retain(|app| !removed.iter().any(|r| r == *app));
+        }
+
+        apps
+    }
+
+    /// Save the configuration to disk, then run the post-save hook if one
+    /// is configured and anything changed. In demo mode nothing is written.
+    pub fn save(&mut self) -> Result<()> {
+        let Some(job) = self.prepare_save()? else {
+            return Ok(());
+        };
+        let desktop_synced = job.run()?;
+        self.finish_save(job, desktop_synced);
+        Ok(())
+    }
+
+    /// Take what `save` writes, so the writing can happen off the main
+    /// thread with [`SaveJob::run`]. Returns `None` in demo mode, where the
+    /// changes count as saved right away.
+    pub fn prepare_save(&mut self) -> Result<Option<SaveJob>> {
+        if self.settings.locked {
+            bail!("Default applications are locked and can't be changed");
+        }
+
+        if self.in_memory {
+            self.saved = self.user.clone();
+            return Ok(None);
+        }
+
+        let desktop_path = if self.settings.save.write_desktop_specific {
+            Self::desktop_config_path().unwrap_or_else(|e| {
+                tracing::warn!("{:#}", e);
+                None
+            })
+        } else {
+            None
+        };
+        let legacy_path = if self.settings.save.mirror_legacy_location {
+            Self::legacy_data_path()
+                .map_err(|e| tracing::warn!("{:#}", e))
+                .ok()
+        } else {
+            None
+        };
+
+        Ok(Some(SaveJob {
+            path: self.path.clone(),
+            user: self.user.clone(),
+            changed: self.changed_mime_types(),
+            desktop_path,
+            legacy_path,
+            post_save_hook: self.settings.hooks.post_save.clone(),
+        }))
+    }
+
+    /// Record a finished [`SaveJob`]: its document is what is on disk now.
+    /// `desktop_synced` is what [`SaveJob::run`] returned.
+    pub fn finish_save(&mut self, job: SaveJob, desktop_synced: Option<PathBuf>) {
+        if let Some(desktop_path) = desktop_synced {
+            // The desktop file now agrees with the user's
+            self.overrides
+                .retain(|mime, found| found.path != desktop_path || !job.changed.contains(mime));
+        }
+        self.saved = job.user;
+    }
+
+    /// Pin the current default of each MIME type that has one, or unpin
+    /// them. Only changes the settings; see [`save_settings`](Self::save_settings).
+    pub fn set_pinned(&mut self, mimes: &[impl AsRef<str>], pinned: bool) {
+        for mime in mimes {
+            let mime = mime.as_ref();
+            if !pinned {
+                self.settings.pinned.remove(mime);
+            } else if let Some(app_id) = self.get_default(mime) {
+                let app_id = app_id.to_string();
+                self.settings.pinned.insert(mime.to_string(), app_id);
+            }
+        }
+    }
+
+    /// Whether every given MIME type is pinned to its current default
+    pub fn is_pinned(&self, mimes: &[impl AsRef<str>]) -> bool {
+        !mimes.is_empty()
+            && mimes.iter().all(|mime| {
+                let mime = mime.as_ref();
+                self.settings.pinned.get(mime).map(String::as_str) == self.get_default(mime)
+            })
+    }
+
+    /// Put pinned defaults that something else changed back, leaving the
+    /// save to the caller. Returns what was restored, with `old` the app that had replaced the
+    /// pinned one. Nothing is restored when locked.
+    pub fn restore_pinned(&mut self) -> Result<Vec<DefaultChange>> {
+        if self.settings.locked || self.in_memory {
+            return Ok(Vec::new());
+        }
+
+        let drifted: Vec<(String, String)> = self
+            .settings
+            .pinned
+            .iter()
+            .filter(|(mime, app_id)| self.get_default(mime) != Some(app_id.as_str()))
+            .map(|(mime, app_id)| (mime.clone(), app_id.clone()))
+            .collect();
+
+        let mut restored = Vec::new();
+        for (mime, app_id) in drifted {
+            let old = self.get_default(&mime).map(String::from);
+            self.set_default(&mime, &app_id)?;
+            restored.push(DefaultChange {
+                mime,
+                old,
+                new: Some(app_id),
+            });
+        }
+
+        // If the user's file already has the pinned app, a higher-priority
+        // file overrides it and writing again wouldn't help
+        let changed = self.changed_mime_types();
+        restored.retain(|change| {
+            let fixed = changed.contains(&change.mime);
+            if !fixed {
+                tracing::warn!(
+                    "Pinned default for {} is overridden by a higher-priority mimeapps.list",
+                    change.mime
+                );
+            }
+            fixed
+        });
+        Ok(restored)
+    }
+
+    /// Save xdg-chooser's own settings (skipped in demo mode)
+    pub fn save_settings(&self) -> Result<()> {
+        if self.in_memory {
+            return Ok(());
+        }
+        self.settings.save()
+    }
+
+    /// MIME types whose entries in the user's file differ from what is on disk
+    pub fn changed_mime_types(&self) -> Vec<String> {
+        let mut changed = BTreeSet::new();
+
+        for section in [
+            DEFAULT_APPLICATIONS,
+            ADDED_ASSOCIATIONS,
+            REMOVED_ASSOCIATIONS,
+        ] {
+            let before: HashMap<&str, &[String]> = self.saved.entries(section).collect();
+            let after: HashMap<&str, &[String]> = self.user.entries(section).collect();
+
+            for (mime, apps) in &after {
+                if before.get(mime) != Some(apps) {
+                    changed.insert(mime.to_string());
+                }
+            }
+            for mime in before.keys() {
+                if !after.contains_key(mime) {
+                    changed.insert(mime.to_string());
+                }
+            }
+        }
+
+        changed.into_iter().collect()
+    }
+
+    /// Get the path to the config file
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+}
+
+/// Replace `from` with `to` in a list of app IDs, where `to` isn't
+/// already listed
+fn replace_in(apps: &mut Vec<String>, from: &str, to: &str) {
+    if apps.iter().any(|app| app == to) {
+        apps.retain(|app| app != from);
+    } else {
+        for app in apps.iter_mut().filter(|app| *app == from) {
+            *app = to.to_string();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_mime_type() {
+        assert!(MimeAppsConfig::validate_mime_type("text/plain").is_ok());
+        assert!(MimeAppsConfig::validate_mime_type("application/x-desktop").is_ok());
+        assert!(MimeAppsConfig::validate_mime_type("x-scheme-handler/http").is_ok());
+        assert!(MimeAppsConfig::validate_mime_type("image/svg+xml").is_ok());
+
+        assert!(MimeAppsConfig::validate_mime_type("invalid").is_err());
+        assert!(MimeAppsConfig::validate_mime_type("/subtype").is_err());
+        assert!(MimeAppsConfig::validate_mime_type("type/").is_err());
+        assert!(MimeAppsConfig::validate_mime_type("").is_err());
+    }
+
+    #[test]
+    fn test_validate_app_id() {
+        assert!(MimeAppsConfig::validate_app_id("firefox.desktop").is_ok());
+        assert!(MimeAppsConfig::validate_app_id("org.mozilla.Firefox.desktop").is_ok());
+
+        assert!(MimeAppsConfig::validate_app_id("firefox").is_err());
+        assert!(MimeAppsConfig::validate_app_id(".desktop").is_err());
+        assert!(MimeAppsConfig::validate_app_id("").is_err());
+    }
+
+    fn config_with_user_file(content: &str) -> MimeAppsConfig {
+        let user = MimeAppsDocument::parse(content);
+        let mut config = MimeAppsConfig::default();
+        config.merge_from(ParsedMimeApps::from_document(&user));
+        config.saved = user.clone();
+        config.user = user;
+        config
+    }
+
+    #[test]
+    fn test_changed_mime_types() {
+        let mut config = config_with_user_file(
+            "[Default Applications]\ntext/plain=gedit.desktop;\nimage/png=eog.desktop;\n",
+        );
+        assert!(config.changed_mime_types().is_empty());
+
+        config.set_default("text/plain", "gedit.desktop").unwrap();
+        config.set_default("image/png", "gimp.desktop").unwrap();
+        config.remove_default("text/plain");
+        config
+            .remove_association("video/mp4", "vlc.desktop")
+            .unwrap();
+        assert_eq!(
+            config.changed_mime_types(),
+            vec!["image/png", "text/plain", "video/mp4"]
+        );
+    }
+
+    #[test]
+    fn test_pinned_defaults() {
+        let mut config = config_with_user_file(
+            "[Default Applications]\nx-scheme-handler/https=firefox.desktop;\n",
+        );
+        config.set_pinned(&["x-scheme-handler/https", "text/html"], true);
+        assert_eq!(
+            config
+                .settings
+                .pinned
+                .get("x-scheme-handler/https")
+                .map(String::as_str),
+            Some("firefox.desktop")
+        );
+        // Nothing to pin without a default
+        assert!(!config.settings.pinned.contains_key("text/html"));
+        assert!(config.is_pinned(&["x-scheme-handler/https"]));
+
+        config
+            .set_default("x-scheme-handler/https", "chrome.desktop")
+            .unwrap();
+        assert!(!config.is_pinned(&["x-scheme-handler/https"]));
+
+        // Restored in memory, for the caller to save
+        let restored = config.restore_pinned().unwrap();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].old.as_deref(), Some("chrome.desktop"));
+        assert!(config.is_pinned(&["x-scheme-handler/https"]));
+        assert_eq!(config.changed_mime_types(), ["x-scheme-handler/https"]);
+
+        config.set_pinned(&["x-scheme-handler/https"], false);
+        assert!(config.settings.pinned.is_empty());
+    }
+
+    #[test]
+    fn test_overriding_default() {
+        let mut config = config_with_user_file(
+            "[Default Applications]\ntext/plain=gedit.desktop;\ntext/html=firefox.desktop;\n",
+        );
+        config.in_memory = true;
+        for (mime, app) in [
+            ("text/plain", "code.desktop"),
+            ("text/html", "firefox.desktop"),
+            ("image/png", "eog.desktop"),
+        ] {
+            config.overrides.insert(
+                mime.to_string(),
+                Override {
+                    path: PathBuf::from("gnome-mimeapps.list"),
+                    apps: vec![app.to_string()],
+                },
+            );
+        }
+
+        // Only types the user's file sets to something else conflict
+        assert!(config.overriding_default("text/plain").is_some());
+        assert!(config.overriding_default("text/html").is_none());
+        assert!(config.overriding_default("image/png").is_none());
+
+        config.remove_override("text/plain").unwrap();
+        assert!(config.overriding_default("text/plain").is_none());
+        assert_eq!(config.get_default("text/plain"), Some("gedit.desktop"));
+    }
+
+    #[test]
+    fn test_parse_desktops() {
+        assert_eq!(parse_desktops("ubuntu:GNOME"), vec!["ubuntu", "gnome"]);
+        assert_eq!(parse_desktops("KDE::kde:"), vec!["kde"]);
+        assert_eq!(parse_desktops("X-Cinnamon"), vec!["x-cinnamon"]);
+        assert!(parse_desktops("").is_empty());
+        assert_eq!(desktop_file_name("gnome"), "gnome-mimeapps.list");
+    }
+
+    #[test]
+    fn test_drop_in_fragments() {
+        let dir = std::env::temp_dir()
+            .join(format!("xdg-chooser-test-{}", std::process::id()))
+            .join(DROP_IN_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["90-local.list", "10-site.list", "50-work.list", "README"] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+
+        let fragments = drop_in_fragments(&dir);
+        fs::remove_dir_all(dir.parent().unwrap().parent().unwrap()).unwrap();
+        assert_eq!(
+            fragments,
+            vec![
+                dir.join("90-local.list"),
+                dir.join("50-work.list"),
+                dir.join("10-site.list"),
+            ]
+        );
+        let outside = dir.parent().unwrap().join("mimeapps.list");
+        assert!(MimeAppsConfig::is_drop_in(&fragments[0]));
+        assert!(!MimeAppsConfig::is_drop_in(&outside));
+    }
+
+    #[test]
+    fn test_save_job() {
+        let path = std::env::temp_dir().join(format!(
+            "xdg-chooser-test-{}-save-job.list",
+            std::process::id()
+        ));
+        let mut config =
+            config_with_user_file("[Default Applications]\ntext/plain=gedit.desktop;\n");
+        config.path = path.clone();
+        config.settings.save.write_desktop_specific = false;
+        config.settings.save.mirror_legacy_location = false;
+        config.settings.hooks.post_save = None;
+
+        config.set_default("text/plain", "code.desktop").unwrap();
+        let job = config.prepare_save().unwrap().unwrap();
+        // Changed while the job is being written
+        config.set_default("image/png", "eog.desktop").unwrap();
+        let desktop_synced = job.run().unwrap();
+        config.finish_save(job, desktop_synced);
+
+        let written = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(written.contains("text/plain=code.desktop;"));
+        assert!(!written.contains("image/png"));
+        assert_eq!(config.changed_mime_types(), vec!["image/png"]);
+    }
+
+    #[test]
+    fn test_sync_desktop_file() {
+        let path = std::env::temp_dir().join(format!(
+            "xdg-chooser-test-{}-gnome-mimeapps.list",
+            std::process::id()
+        ));
+        fs::write(
+            &path,
+            "[Default Applications]\ntext/plain=gedit.desktop;\nimage/png=eog.desktop;\n",
+        )
+        .unwrap();
+
+        let mut config =
+            config_with_user_file("[Default Applications]\ntext/plain=gedit.desktop;\n");
+        config.set_default("text/plain", "code.desktop").unwrap();
+        sync_desktop_file(&config.user, &path, &config.changed_mime_types()).unwrap();
+
+        let synced = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            synced,
+            "[Default Applications]\ntext/plain=code.desktop;\nimage/png=eog.desktop;\n\n\
+             [Added Associations]\ntext/plain=code.desktop;\n"
+        );
+    }
+
+    #[test]
+    fn test_replace_app() {
+        let mut config = config_with_user_file(
+            "[Default Applications]\n\
+             x-scheme-handler/https=firefox.desktop;\n\
+             text/plain=gedit.desktop;\n\
+             [Added Associations]\n\
+             text/html=firefox.desktop;org.mozilla.firefox.desktop;\n\
+             [Removed Associations]\n\
+             application/pdf=firefox.desktop;\n",
+        );
+        // A default from a system file, which can only be overridden
+        config
+            .default_apps
+            .insert("text/html".to_string(), vec!["firefox.desktop".to_string()]);
+
+        let changed = config
+            .replace_app("firefox.desktop", "org.mozilla.firefox.desktop")
+            .unwrap();
+        assert_eq!(
+            changed,
+            vec!["application/pdf", "text/html", "x-scheme-handler/https"]
+        );
+        assert_eq!(
+            config.get_default("text/html"),
+            Some("org.mozilla.firefox.desktop")
+        );
+        assert_eq!(
+            config.user.to_string(),
+            "[Default Applications]\n\
+             x-scheme-handler/https=org.mozilla.firefox.desktop;\n\
+             text/plain=gedit.desktop;\n\
+             text/html=org.mozilla.firefox.desktop;\n\
+             [Added Associations]\n\
+             text/html=org.mozilla.firefox.desktop;\n\
+             x-scheme-handler/https=org.mozilla.firefox.desktop;\n\
+             [Removed Associations]\n\
+             application/pdf=org.mozilla.firefox.desktop;\n"
+        );
+    }
+
+    #[test]
+    fn test_set_default_only_touches_changed_lines() {
+        let content = "\
+# my defaults
+[Default Applications]
+text/plain=gedit.desktop
+image/png=eog.desktop
+
+[Added Associations]
+text/plain=code.desktop;gedit.desktop;
+image/png=eog.desktop;
+";
+        let mut config = config_with_user_file(content);
+
+        // Re-setting the current default must not rewrite anything
+        config.set_default("image/png", "eog.desktop").unwrap();
+        assert_eq!(config.user.to_string(), content);
+
+        config.set_default("text/plain", "code.desktop").unwrap();
+        assert_eq!(
+            config.user.to_string(),
+            content.replace("text/plain=gedit.desktop\n", "text/plain=code.desktop\n")
+        );
+        assert_eq!(config.get_default("text/plain"), Some("code.desktop"));
+    }
+
+    #[test]
+    fn test_association_edits() {
+        let mut config = config_with_user_file(
+            "[Added Associations]\nimage/png=eog.desktop;\n\n[Removed Associations]\nimage/png=gimp.desktop;\n",
+        );
+
+        assert!(config.add_association("image/png", "gimp.desktop").unwrap());
+        assert!(!config.add_association("image/png", "gimp.desktop").unwrap());
+        assert_eq!(
+            config.user.to_string(),
+            "[Added Associations]\nimage/png=eog.desktop;gimp.desktop;\n\n[Removed Associations]\n"
+        );
+
+        assert!(config.remove_association("image/png", "eog.desktop").unwrap());
+        assert_eq!(config.get_associations("image/png"), vec!["gimp.desktop"]);
+        assert_eq!(
+            config.user.to_string(),
+            "[Added Associations]\nimage/png=gimp.desktop;\n\n[Removed Associations]\nimage/png=eog.desktop;\n"
+        );
+
+        assert!(config.remove_association("image/png", "not-an-id").is_err());
+    }
+
+    #[test]
+    fn test_remove_default_updates_user_file() {
+        let mut config =
+            config_with_user_file("[Default Applications]\ntext/plain=gedit.desktop;\n");
+        assert!(config.remove_default("text/plain"));
+        assert!(!config.remove_default("text/plain"));
+
+        assert_eq!(config.get_default("text/plain"), None);
+        assert_eq!(config.user.to_string(), "[Default Applications]\n");
+    }
+
+    #[test]
+    fn test_unassociate() {
+        let mut config =
+            config_with_user_file("[Added Associations]\ntext/plain=gedit.desktop;vim.desktop;\n");
+        assert!(config.unassociate("text/plain", "gedit.desktop"));
+        assert!(!config.unassociate("text/plain", "gedit.desktop"));
+
+        assert_eq!(config.get_associations("text/plain"), vec!["vim.desktop"]);
+        assert!(!config.is_removed("text/plain", "gedit.desktop"));
+        assert_eq!(
+            config.user.to_string(),
+            "[Added Associations]\ntext/plain=vim.desktop;\n"
+        );
+    }
+}
@@ -0,0 +1,7 @@
+pub mod changes;
+pub mod diagnostics;
+pub mod document;
+pub mod mimeapps;
+pub mod settings;
+
+pub use mimeapps::MimeAppsConfig;
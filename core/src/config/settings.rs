@@ -0,0 +1,459 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::desktop::categories::{register_custom_categories, AppCategory, CustomCategory};
+use crate::utils::atomic::write_atomic;
+
+/// Version of the settings file layout written by this build
+const SETTINGS_VERSION: u32 = 1;
+
+/// Preferences for xdg-chooser itself, stored in
+/// `~/.config/xdg-chooser/settings.toml`. Missing keys take their defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Layout version of the file, used to migrate older files on load
+    pub version: u32,
+    /// How available applications are laid out
+    pub view_mode: ViewMode,
+    /// What the Test button opens, keyed by category id (e.g. `web-browser`).
+    /// An empty value launches the app without arguments.
+    pub test_payloads: HashMap<String, String>,
+    /// Commands run in response to changes
+    pub hooks: Hooks,
+    /// Application sources to skip, by provider id (`desktop-files`,
+    /// `flatpak`, `snap`, `appimage`)
+    pub disabled_providers: Vec<String>,
+    /// Categories left out of the sidebar, by id
+    pub hidden_categories: Vec<String>,
+    /// Order of the sidebar's categories, by id. Categories not listed
+    /// follow in their usual order.
+    pub category_order: Vec<String>,
+    /// Categories the user defined, listed after the built-in ones
+    pub custom_categories: Vec<CustomCategory>,
+    /// Rank apps made for the running desktop (GNOME apps on GNOME, KDE and
+    /// Qt apps on Plasma) higher when choosing which ones to show up front
+    pub prefer_native_apps: bool,
+    /// Setting a default for a whole category also sets it for the related
+    /// types the app declares (e.g. FTP links and saved web pages for browsers)
+    pub set_related_types: bool,
+    /// Advanced mode: show the exact command line before each test launch,
+    /// with one-off edits allowed
+    pub advanced: bool,
+    /// Environment given to test launches
+    pub launch: LaunchSettings,
+    /// Extra files written on save
+    pub save: SaveSettings,
+    /// Changes to which MIME types of a category are primary (set together
+    /// by Set as Default), keyed by category id
+    pub mime_types: HashMap<String, MimeTypeOverrides>,
+    /// Pinned defaults, MIME type → desktop file ID, put back whenever
+    /// another program changes them
+    pub pinned: BTreeMap<String, String>,
+    /// Read-only mode: nothing can be changed or launched. Only set by
+    /// `--locked` or `locked = true` in a system-wide settings file
+    /// (`/etc/xdg/xdg-chooser/settings.toml`), never from the user's own.
+    #[serde(skip)]
+    pub locked: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            view_mode: ViewMode::default(),
+            test_payloads: HashMap::new(),
+            hooks: Hooks::default(),
+            disabled_providers: Vec::new(),
+            hidden_categories: Vec::new(),
+            category_order: Vec::new(),
+            custom_categories: Vec::new(),
+            prefer_native_apps: false,
+            set_related_types: true,
+            advanced: false,
+            launch: LaunchSettings::default(),
+            save: SaveSettings::default(),
+            mime_types: HashMap::new(),
+            pinned: BTreeMap::new(),
+            locked: false,
+        }
+    }
+}
+
+/// How the available applications are laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ViewMode {
+    /// One row per application with its description
+    #[default]
+    List,
+    /// Large icons with names, like a launcher
+    Grid,
+}
+
+/// User commands run by xdg-chooser
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Hooks {
+    /// Shell command run after every successful save, with the changed MIME
+    /// types as arguments and in `$XDG_CHOOSER_CHANGED`
+    pub post_save: Option<String>,
+}
+
+/// How test launches are run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LaunchSettings {
+    /// Drop debugging and theming variables meant for xdg-chooser's own
+    /// GTK, so apps start as they would from the desktop shell
+    pub clean_environment: bool,
+    /// With a clean environment, also drop `LD_PRELOAD`, `LD_LIBRARY_PATH`
+    /// and other variables that change what code gets loaded
+    pub drop_loader_variables: bool,
+}
+
+/// Where saves are written besides `~/.config/mimeapps.list`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SaveSettings {
+    /// Also write `~/.local/share/applications/mimeapps.list`, the
+    /// deprecated location some older applications still read
+    pub mirror_legacy_location: bool,
+    /// Also write changed entries to `~/.config/${desktop}-mimeapps.list`
+    /// for the current desktop, which would otherwise override them
+    pub write_desktop_specific: bool,
+}
+
+/// A user's changes to a category's primary MIME types
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MimeTypeOverrides {
+    /// Types made primary, usually from the category's extended ones
+    pub promote: Vec<String>,
+    /// Primary types moved to the extended ones
+    pub demote: Vec<String>,
+}
+
+impl Settings {
+    /// Path of the settings file
+    pub fn path() -> Result<PathBuf> {
+        let xdg_dirs = xdg::BaseDirectories::with_prefix("xdg-chooser")
+            .context("Failed to determine XDG directories")?;
+
+        Ok(xdg_dirs.get_config_home().join("settings.toml"))
+    }
+
+    /// Load the settings file, falling back to defaults if it doesn't exist
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        let mut settings = if path.exists() {
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            Self::parse(&content)
+                .with_context(|| format!("Invalid settings in {}", path.display()))?
+        } else {
+            Self::default()
+        };
+
+        settings.locked = locked_by_admin();
+        register_custom_categories(&settings.custom_categories);
+        Ok(settings)
+    }
+
+    /// Write the settings file, replacing it atomically
+    pub fn save(&self) -> Result<()> {
+        let content = toml::to_string_pretty(self).context("Failed to serialize settings")?;
+        write_atomic(&Self::path()?, &content)
+    }
+
+    /// Parse settings from TOML, migrating files written by older versions
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut table: toml::Table = toml::from_str(content)?;
+        migrate(&mut table);
+        Ok(table.try_into()?)
+    }
+
+    /// The argument to test an app of this category with, if any
+    pub fn test_payload(&self, category: &AppCategory) -> Option<String> {
+        match self.test_payloads.get(category.id()) {
+            Some(payload) if payload.is_empty() => None,
+            Some(payload) => Some(expand_home(payload)),
+            None => category.default_test_payload(),
+        }
+    }
+
+    /// The category's primary MIME types with the user's promotions and
+    /// demotions applied
+    pub fn primary_mime_types(&self, category: &AppCategory) -> Vec<String> {
+        let overrides = self.mime_types.get(category.id());
+        let mut mimes: Vec<String> = category
+            .primary_mime_types()
+            .into_iter()
+            .filter(|mime| !overrides.is_some_and(|o| o.demote.iter().any(|m| m == mime)))
+            .map(String::from)
+            .collect();
+        for mime in overrides.iter().flat_map(|o| &o.promote) {
+            if !mimes.contains(mime) {
+                mimes.push(mime.clone());
+            }
+        }
+        mimes
+    }
+
+    /// The category's extended MIME types, without promoted ones and with
+    /// demoted primary ones
+    pub fn extended_mime_types(&self, category: &AppCategory) -> Vec<String> {
+        let overrides = self.mime_types.get(category.id());
+        let mut mimes: Vec<String> = overrides
+            .iter()
+            .flat_map(|o| &o.demote)
+            .filter(|mime| category.primary_mime_types().contains(&mime.as_str()))
+            .cloned()
+            .collect();
+        mimes.extend(
+            category
+                .extended_mime_types()
+                .into_iter()
+                .filter(|mime| !overrides.is_some_and(|o| o.promote.iter().any(|m| m == mime)))
+                .map(String::from),
+        );
+        mimes
+    }
+
+    /// Whether the user took the category out of the sidebar
+    pub fn is_category_hidden(&self, category: &AppCategory) -> bool {
+        self.hidden_categories.iter().any(|id| id == category.id())
+    }
+
+    pub fn set_category_hidden(&mut self, category: &AppCategory, hidden: bool) {
+        self.hidden_categories.retain(|id| id != category.id());
+        if hidden {
+            self.hidden_categories.push(category.id().to_string());
+        }
+    }
+
+    /// The categories sorted into the user's order
+    pub fn ordered_categories(&self, mut categories: Vec<AppCategory>) -> Vec<AppCategory> {
+        // Stable sort keeps the usual order among unlisted categories
+        categories.sort_by_key(|category| {
+            self.category_order
+                .iter()
+                .position(|id| id == category.id())
+                .unwrap_or(usize::MAX)
+        });
+        categories
+    }
+
+    pub fn set_category_order(&mut self, categories: &[AppCategory]) {
+        self.category_order = categories.iter().map(|c| c.id().to_string()).collect();
+    }
+
+    /// Add a user-defined category, or replace the one with the same ID.
+    /// Call [`register_custom_categories`] to make the change visible.
+    pub fn set_custom_category(&mut self, category: CustomCategory) {
+        match self
+            .custom_categories
+            .iter_mut()
+            .find(|existing| existing.id == category.id)
+        {
+            Some(existing) => *existing = category,
+            None => self.custom_categories.push(category),
+        }
+    }
+
+    /// Delete a user-defined category along with the settings kept for it.
+    /// Defaults set through it stay in mimeapps.list.
+    pub fn remove_custom_category(&mut self, id: &str) {
+        self.custom_categories.retain(|category| category.id != id);
+        self.hidden_categories.retain(|hidden| hidden != id);
+        self.category_order.retain(|ordered| ordered != id);
+        self.mime_types.remove(id);
+        self.test_payloads.remove(id);
+    }
+
+    /// Make a MIME type primary for a category, or move it to the extended
+    /// types
+    pub fn set_primary(&mut self, category: &AppCategory, mime: &str, primary: bool) {
+        let built_in = category.primary_mime_types().contains(&mime);
+        let overrides = self
+            .mime_types
+            .entry(category.id().to_string())
+            .or_default();
+
+        overrides.promote.retain(|m| m != mime);
+        overrides.demote.retain(|m| m != mime);
+        if primary && !built_in {
+            overrides.promote.push(mime.to_string());
+        } else if !primary && built_in {
+            overrides.demote.push(mime.to_string());
+        }
+
+        if overrides.promote.is_empty() && overrides.demote.is_empty() {
+            self.mime_types.remove(category.id());
+        }
+    }
+}
+
+/// Whether a system-wide settings file sets `locked = true`
+fn locked_by_admin() -> bool {
+    let Ok(xdg_dirs) = xdg::BaseDirectories::new() else {
+        return false;
+    };
+
+    xdg_dirs.get_config_dirs().iter().any(|dir| {
+        let path = dir.join("xdg-chooser/settings.toml");
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| content.parse::<toml::Table>().ok())
+            .and_then(|table| table.get("locked").and_then(toml::Value::as_bool))
+            .unwrap_or(false)
+    })
+}
+
+/// Bring a settings table written by an older version up to date. Version
+/// 1 is the first layout, so there is nothing to convert yet; changes to the
+/// layout add a step here for the versions before them.
+fn migrate(table: &mut toml::Table) {
+    table.insert(
+        "version".to_string(),
+        toml::Value::Integer(SETTINGS_VERSION.into()),
+    );
+}
+
+/// Expand a leading `~` to the user's home directory
+fn expand_home(path: &str) -> String {
+    let home = std::env::var("HOME").unwrap_or_default();
+    if path == "~" {
+        home
+    } else if let Some(rest) = path.strip_prefix("~/") {
+        format!("{}/{}", home, rest)
+    } else {
+        path.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_settings() {
+        let settings = Settings::parse(
+            r#"
+version = 1
+view_mode = "grid"
+disabled_providers = ["snap"]
+hidden_categories = ["calculator"]
+category_order = ["calendar"]
+
+[test_payloads]
+web-browser = "https://example.com/"
+terminal = ""
+
+[hooks]
+post_save = "git -C ~/dotfiles commit -qam defaults"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(settings.view_mode, ViewMode::Grid);
+        assert_eq!(settings.disabled_providers, vec!["snap"]);
+        assert!(settings.is_category_hidden(&AppCategory::Calculator));
+        assert!(!settings.is_category_hidden(&AppCategory::Calendar));
+        assert_eq!(
+            settings.ordered_categories(vec![
+                AppCategory::WebBrowser,
+                AppCategory::TextEditor,
+                AppCategory::Calendar,
+            ]),
+            vec![
+                AppCategory::Calendar,
+                AppCategory::WebBrowser,
+                AppCategory::TextEditor,
+            ]
+        );
+        assert_eq!(
+            settings.test_payload(&AppCategory::WebBrowser).as_deref(),
+            Some("https://example.com/")
+        );
+        assert_eq!(settings.test_payload(&AppCategory::TerminalEmulator), None);
+        assert_eq!(
+            settings.test_payload(&AppCategory::EmailClient),
+            AppCategory::EmailClient.default_test_payload()
+        );
+        assert_eq!(
+            settings.hooks.post_save.as_deref(),
+            Some("git -C ~/dotfiles commit -qam defaults")
+        );
+        assert_eq!(Settings::parse("").unwrap(), Settings::default());
+    }
+
+    #[test]
+    fn test_primary_mime_type_overrides() {
+        let category = AppCategory::TextEditor;
+        let mut settings = Settings::default();
+
+        settings.set_primary(&category, "application/json", true);
+        assert_eq!(
+            settings.primary_mime_types(&category),
+            vec!["text/plain", "application/json"]
+        );
+        assert!(!settings
+            .extended_mime_types(&category)
+            .contains(&"application/json".to_string()));
+
+        settings.set_primary(&category, "text/plain", false);
+        assert_eq!(
+            settings.primary_mime_types(&category),
+            vec!["application/json"]
+        );
+        assert_eq!(settings.extended_mime_types(&category)[0], "text/plain");
+
+        settings.set_primary(&category, "application/json", false);
+        settings.set_primary(&category, "text/plain", true);
+        assert!(settings.mime_types.is_empty());
+        assert_eq!(settings.primary_mime_types(&category), vec!["text/plain"]);
+    }
+
+    #[test]
+    fn test_custom_categories() {
+        let mut settings = Settings::parse(
+            r#"
+hidden_categories = ["custom-e-books"]
+category_order = ["custom-e-books", "calendar"]
+
+[[custom_categories]]
+id = "custom-e-books"
+name = "E-Books"
+mime_types = ["application/epub+zip"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            settings.custom_categories,
+            vec![CustomCategory::new(
+                "E-Books",
+                "",
+                vec!["application/epub+zip".to_string()]
+            )]
+        );
+
+        let saved = toml::to_string_pretty(&settings).unwrap();
+        assert_eq!(Settings::parse(&saved).unwrap(), settings);
+
+        let mut renamed = settings.custom_categories[0].clone();
+        renamed.name = "Books".to_string();
+        settings.set_custom_category(renamed);
+        assert_eq!(settings.custom_categories.len(), 1);
+        assert_eq!(settings.custom_categories[0].name, "Books");
+
+        settings.remove_custom_category("custom-e-books");
+        assert!(settings.custom_categories.is_empty());
+        assert!(settings.hidden_categories.is_empty());
+        assert_eq!(settings.category_order, vec!["calendar"]);
+    }
+}
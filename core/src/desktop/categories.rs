@@ -0,0 +1,611 @@
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Prefix of the IDs of user-defined categories, which keeps them apart
+/// from the built-in ones
+const CUSTOM_ID_PREFIX: &str = "custom-";
+
+/// Icon of user-defined categories that don't name one
+pub const CUSTOM_DEFAULT_ICON: &str = "applications-other";
+
+/// User-defined categories, registered from the settings when they load
+static CUSTOM_CATEGORIES: RwLock<Vec<Arc<CustomCategory>>> = RwLock::new(Vec::new());
+
+/// A category the user defined, from `[[custom_categories]]` in the
+/// settings or the New Category dialog
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CustomCategory {
+    /// Starts with `custom-` (e.g. "custom-ebook-reader")
+    pub id: String,
+    pub name: String,
+    /// Icon name from the icon theme
+    #[serde(default = "default_custom_icon")]
+    pub icon: String,
+    /// The types Set as Default changes, most important first
+    pub mime_types: Vec<String>,
+}
+
+fn default_custom_icon() -> String {
+    CUSTOM_DEFAULT_ICON.to_string()
+}
+
+impl CustomCategory {
+    /// A category with an ID made from its name
+    pub fn new(name: &str, icon: &str, mime_types: Vec<String>) -> Self {
+        let icon = icon.trim();
+        Self {
+            id: custom_id(name),
+            name: name.trim().to_string(),
+            icon: if icon.is_empty() {
+                default_custom_icon()
+            } else {
+                icon.to_string()
+            },
+            mime_types,
+        }
+    }
+}
+
+/// `custom-` followed by the name in lowercase, with dashes between words
+pub fn custom_id(name: &str) -> String {
+    let words: Vec<String> = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    format!("{}{}", CUSTOM_ID_PREFIX, words.join("-"))
+}
+
+/// Make user-defined categories known to [`AppCategory::all`] and
+/// [`AppCategory::from_id`], replacing the previous ones
+pub fn register_custom_categories(categories: &[CustomCategory]) {
+    let categories = categories
+        .iter()
+        .filter(|category| category.id.starts_with(CUSTOM_ID_PREFIX))
+        .cloned()
+        .map(Arc::new)
+        .collect();
+    *CUSTOM_CATEGORIES
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = categories;
+}
+
+/// Application category definitions with associated MIME types
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AppCategory {
+    WebBrowser,
+    EmailClient,
+    FileManager,
+    TerminalEmulator,
+    TextEditor,
+    MusicPlayer,
+    AudioEditor,
+    VideoPlayer,
+    VideoEditor,
+    ImageViewer,
+    VectorEditor,
+    DocumentViewer,
+    ArchiveManager,
+    Calculator,
+    Calendar,
+    WordProcessor,
+    Spreadsheet,
+    Database,
+    ModelViewer,
+    ScientificData,
+    VersionControl,
+    FallbackHandler,
+    /// Defined by the user
+    Custom(Arc<CustomCategory>),
+}
+
+impl AppCategory {
+    /// Returns all available categories in display order, user-defined
+    /// ones last
+    pub fn all() -> Vec<Self> {
+        let mut all = Self::built_in();
+        let custom = CUSTOM_CATEGORIES
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        all.extend(custom.iter().cloned().map(Self::Custom));
+        all
+    }
+
+    fn built_in() -> Vec<Self> {
+        vec![
+            Self::WebBrowser,
+            Self::EmailClient,
+            Self::FileManager,
+            Self::TerminalEmulator,
+            Self::TextEditor,
+            Self::MusicPlayer,
+            Self::AudioEditor,
+            Self::VideoPlayer,
+            Self::VideoEditor,
+            Self::ImageViewer,
+            Self::VectorEditor,
+            Self::DocumentViewer,
+            Self::ArchiveManager,
+            Self::Calculator,
+            Self::Calendar,
+            Self::WordProcessor,
+            Self::Spreadsheet,
+            Self::Database,
+            Self::ModelViewer,
+            Self::ScientificData,
+            Self::VersionControl,
+            Self::FallbackHandler,
+        ]
+    }
+
+    /// Stable identifier used on the command line (e.g. "web-browser")
+    pub fn id(&self) -> &str {
+        match self {
+            Self::WebBrowser => "web-browser",
+            Self::EmailClient => "email-client",
+            Self::FileManager => "file-manager",
+            Self::TerminalEmulator => "terminal",
+            Self::TextEditor => "text-editor",
+            Self::MusicPlayer => "music-player",
+            Self::AudioEditor => "audio-editor",
+            Self::VideoPlayer => "video-player",
+            Self::VideoEditor => "video-editor",
+            Self::ImageViewer => "image-viewer",
+            Self::VectorEditor => "vector-editor",
+            Self::DocumentViewer => "document-viewer",
+            Self::ArchiveManager => "archive-manager",
+            Self::Calculator => "calculator",
+            Self::Calendar => "calendar",
+            Self::WordProcessor => "word-processor",
+            Self::Spreadsheet => "spreadsheet",
+            Self::Database => "database",
+            Self::ModelViewer => "3d-model",
+            Self::ScientificData => "scientific-data",
+            Self::VersionControl => "version-control",
+            Self::FallbackHandler => "unknown-files",
+            Self::Custom(custom) => &custom.id,
+        }
+    }
+
+    /// Look up a category by its command-line identifier
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::all().into_iter().find(|c| c.id() == id)
+    }
+
+    /// Human-readable name for the category
+    pub fn display_name(&self) -> &str {
+        match self {
+            Self::WebBrowser => "Web Browser",
+            Self::EmailClient => "Email Client",
+            Self::FileManager => "File Manager",
+            Self::TerminalEmulator => "Terminal",
+            Self::TextEditor => "Text Editor",
+            Self::MusicPlayer => "Music Player",
+            Self::AudioEditor => "Audio Editor",
+            Self::VideoPlayer => "Video Player",
+            Self::VideoEditor => "Video Editor",
+            Self::ImageViewer => "Image Viewer",
+            Self::VectorEditor => "Vector Graphics Editor",
+            Self::DocumentViewer => "Document Viewer",
+            Self::ArchiveManager => "Archive Manager",
+            Self::Calculator => "Calculator",
+            Self::Calendar => "Calendar",
+            Self::WordProcessor => "Word Processor",
+            Self::Spreadsheet => "Spreadsheet",
+            Self::Database => "Database Browser",
+            Self::ModelViewer => "3D Models",
+            Self::ScientificData => "Scientific Data",
+            Self::VersionControl => "Version Control",
+            Self::FallbackHandler => "Unknown Files",
+            Self::Custom(custom) => &custom.name,
+        }
+    }
+
+    /// Icon name for this category (freedesktop icon spec)
+    pub fn icon_name(&self) -> &str {
+        match self {
+            Self::WebBrowser => "web-browser",
+            Self::EmailClient => "mail-client",
+            Self::FileManager => "system-file-manager",
+            Self::TerminalEmulator => "utilities-terminal",
+            Self::TextEditor => "accessories-text-editor",
+            Self::MusicPlayer => "applications-multimedia",
+            Self::AudioEditor => "audio-x-generic",
+            Self::VideoPlayer => "video-x-generic",
+            Self::VideoEditor => "applications-multimedia",
+            Self::ImageViewer => "image-x-generic",
+            Self::VectorEditor => "applications-graphics",
+            Self::DocumentViewer => "x-office-document",
+            Self::ArchiveManager => "package-x-generic",
+            Self::Calculator => "accessories-calculator",
+            Self::Calendar => "x-office-calendar",
+            Self::WordProcessor => "x-office-document",
+            Self::Spreadsheet => "x-office-spreadsheet",
+            Self::Database => "x-office-database",
+            Self::ModelViewer => "applications-graphics",
+            Self::ScientificData => "applications-science",
+            Self::VersionControl => "applications-development",
+            Self::FallbackHandler => "application-x-generic",
+            Self::Custom(custom) => &custom.icon,
+        }
+    }
+
+    /// Primary MIME types for this category (used for setting defaults)
+    pub fn primary_mime_types(&self) -> Vec<&str> {
+        match self {
+            Self::WebBrowser => vec![
+                "x-scheme-handler/http",
+                "x-scheme-handler/https",
+                "text/html",
+                "application/xhtml+xml",
+            ],
+            Self::EmailClient => vec![
+                "x-scheme-handler/mailto",
+                "application/x-extension-eml",
+                "message/rfc822",
+            ],
+            Self::FileManager => vec!["inode/directory"],
+            Self::TerminalEmulator => vec![], // Uses Categories instead
+            Self::TextEditor => vec!["text/plain"],
+            Self::MusicPlayer => vec![
+                "audio/mpeg",
+                "audio/mp3",
+                "audio/ogg",
+                "audio/flac",
+                "audio/x-wav",
+                "audio/x-vorbis+ogg",
+                "audio/x-mpegurl",
+                "audio/x-scpls",
+                "application/xspf+xml",
+            ],
+            Self::AudioEditor => vec![
+                "application/x-audacity-project",
+                "application/x-audacity-project+sqlite3",
+                "application/x-ardour",
+            ],
+            Self::VideoPlayer => vec![
+                "video/mp4",
+                "video/x-matroska",
+                "video/webm",
+                "video/mpeg",
+                "video/x-msvideo",
+                "video/quicktime",
+            ],
+            Self::VideoEditor => vec![
+                "application/x-kdenlive",
+                "application/x-shotcut",
+                "application/x-openshot-project",
+                "application/x-pitivi",
+            ],
+            Self::ImageViewer => vec![
+                "image/png",
+                "image/jpeg",
+                "image/gif",
+                "image/webp",
+                "image/bmp",
+            ],
+            // SVG files belong to the editor: Set as Default for image viewers
+            // leaves them alone, and previews come from thumbnailers rather
+            // than the default app. Image Viewer still lists SVG under
+            // "Individual MIME Type Settings" for viewing them instead.
+            Self::VectorEditor => vec![
+                "image/svg+xml",
+                "image/svg+xml-compressed",
+                "application/illustrator",
+                "image/x-eps",
+            ],
+            Self::DocumentViewer => vec![
+                "application/pdf",
+                "application/postscript",
+                "application/x-bzpdf",
+                "application/x-gzpdf",
+            ],
+            Self::ArchiveManager => vec![
+                "application/zip",
+                "application/x-tar",
+                "application/gzip",
+                "application/x-gzip",
+                "application/x-bzip2",
+                "application/x-xz",
+                "application/x-7z-compressed",
+                "application/vnd.rar",
+                "application/x-rar",
+                "application/zstd",
+                "application/x-lz4",
+                "application/x-lzip",
+                "application/x-lrzip",
+                "application/x-cpio",
+            ],
+            Self::Calculator => vec![], // Uses Categories instead
+            Self::Calendar => vec![
+                "text/calendar",
+                "application/x-extension-ics",
+                "application/x-vcalendar",
+                "x-scheme-handler/webcal",
+                "x-scheme-handler/webcals",
+            ],
+            Self::WordProcessor => vec![
+                "application/msword",
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+                "application/vnd.oasis.opendocument.text",
+                "application/rtf",
+            ],
+            Self::Spreadsheet => vec![
+                "application/vnd.ms-excel",
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+                "application/vnd.oasis.opendocument.spreadsheet",
+                "text/csv",
+            ],
+            Self::Database => vec!["application/vnd.sqlite3", "application/x-sqlite3"],
+            Self::ModelViewer => vec![
+                "model/stl",
+                "model/obj",
+                "model/gltf+json",
+                "model/gltf-binary",
+                "application/x-blender",
+            ],
+            Self::ScientificData => vec![
+                "image/fits",
+                "application/dicom",
+                "application/x-hdf",
+                "application/x-hdf5",
+                "application/x-netcdf",
+            ],
+            // "Clone" links on forges
+            Self::VersionControl => vec![
+                "x-scheme-handler/git",
+                "x-scheme-handler/git+ssh",
+                "x-scheme-handler/svn",
+                "x-scheme-handler/svn+ssh",
+            ],
+            // Files nothing more specific claims
+            Self::FallbackHandler => vec!["application/octet-stream"],
+            Self::Custom(custom) => custom.mime_types.iter().map(String::as_str).collect(),
+        }
+    }
+
+    /// Extended MIME types (shown in drill-down view)
+    pub fn extended_mime_types(&self) -> Vec<&str> {
+        match self {
+            Self::WebBrowser => vec![
+                "x-scheme-handler/ftp",
+                "application/x-mimearchive",
+                "multipart/related",
+                "text/mml",
+                "application/x-webarchive",
+            ],
+            Self::MusicPlayer => vec![
+                "audio/aac",
+                "audio/mp4",
+                "audio/x-m4a",
+                "audio/x-aiff",
+                "audio/x-ape",
+                "audio/opus",
+                "audio/x-opus+ogg",
+            ],
+            // Music Player's types, for people who'd rather edit recordings
+            // than play them
+            Self::AudioEditor => vec!["audio/x-wav", "audio/x-aiff", "audio/flac"],
+            Self::VideoPlayer => vec![
+                "video/x-flv",
+                "video/3gpp",
+                "video/ogg",
+                "video/x-ogm+ogg",
+            ],
+            Self::ImageViewer => vec![
+                "image/svg+xml",
+                "image/tiff",
+                "image/x-icon",
+                "image/heic",
+                "image/heif",
+                "image/avif",
+            ],
+            // "Open in IDE" links usually name one editor, so these are set
+            // one at a time
+            Self::VersionControl => vec![
+                "x-scheme-handler/ssh",
+                "x-scheme-handler/vscode",
+                "x-scheme-handler/vscode-insiders",
+                "x-scheme-handler/vscodium",
+                "x-scheme-handler/jetbrains",
+            ],
+            Self::TextEditor => vec![
+                "text/x-csrc",
+                "text/x-c++src",
+                "text/x-python",
+                "text/x-shellscript",
+                "text/x-rust",
+                "application/json",
+                "application/xml",
+                "text/markdown",
+                "text/x-ini",
+                "application/x-wine-extension-ini",
+                "text/x-yaml",
+                "text/x-toml",
+                "text/x-cmake",
+                "text/x-makefile",
+                "text/x-log",
+                "application/x-desktop",
+                "text/x-java",
+                "text/x-javascript",
+                "text/x-typescript",
+                "text/css",
+                "text/html",
+            ],
+            _ => vec![],
+        }
+    }
+
+    /// Extended MIME types that setting a default for the whole category also
+    /// covers, for apps that declare them (unless `set_related_types` is off)
+    pub fn related_mime_types(&self) -> Vec<&str> {
+        match self {
+            Self::WebBrowser => self.extended_mime_types(),
+            _ => vec![],
+        }
+    }
+
+    /// Desktop categories to search for (for apps without MIME types)
+    pub fn desktop_categories(&self) -> Vec<&'static str> {
+        match self {
+            Self::Calculator => vec!["Calculator"],
+            Self::Calendar => vec!["Calendar"],
+            Self::TerminalEmulator => vec!["TerminalEmulator"],
+            Self::WebBrowser => vec!["WebBrowser"],
+            Self::EmailClient => vec!["Email"],
+            Self::FileManager => vec!["FileManager"],
+            Self::TextEditor => vec!["TextEditor"],
+            Self::MusicPlayer => vec!["Audio", "Music", "Player"],
+            Self::AudioEditor => vec!["AudioEditing", "AudioVideoEditing"],
+            Self::VideoPlayer => vec!["Video", "AudioVideo"],
+            Self::VideoEditor => vec!["VideoEditing", "AudioVideoEditing"],
+            Self::ImageViewer => vec!["Viewer", "Graphics"],
+            Self::VectorEditor => vec!["VectorGraphics"],
+            Self::DocumentViewer => vec!["Viewer", "Office"],
+            Self::ArchiveManager => vec!["Archiving", "Compression"],
+            Self::WordProcessor => vec!["WordProcessor", "Office"],
+            Self::Spreadsheet => vec!["Spreadsheet", "Office"],
+            Self::Database => vec!["Database"],
+            Self::ModelViewer => vec!["3DGraphics"],
+            Self::ScientificData => vec!["DataVisualization"],
+            Self::VersionControl => vec!["RevisionControl", "IDE"],
+            // Unknown files are often plain text without an extension
+            Self::FallbackHandler => vec!["TextEditor"],
+            // Only apps declaring the category's types are listed
+            Self::Custom(_) => vec![],
+        }
+    }
+
+    /// Whether the sidebar leaves the category out when no installed
+    /// application fits it, for niche categories most people never need
+    pub fn hidden_when_empty(&self) -> bool {
+        matches!(self, Self::ScientificData)
+    }
+
+    /// What the Test button opens when the settings don't say otherwise.
+    /// Launching most apps with no arguments verifies little, so give them
+    /// something they should handle.
+    pub fn default_test_payload(&self) -> Option<String> {
+        // Only use sample files that are actually installed
+        let existing = |path: &str| Path::new(path).exists().then(|| path.to_string());
+
+        match self {
+            Self::WebBrowser => Some("https://example.org/".to_string()),
+            Self::EmailClient => Some("mailto:?subject=xdg-chooser%20test".to_string()),
+            Self::FileManager => std::env::var("HOME").ok(),
+            Self::TextEditor => existing("/etc/os-release"),
+            Self::MusicPlayer => existing("/usr/share/sounds/freedesktop/stereo/complete.oga"),
+            _ => None,
+        }
+    }
+
+    /// Get the primary MIME type used for querying the current default
+    pub fn default_query_mime(&self) -> Option<&str> {
+        self.primary_mime_types().first().copied()
+    }
+
+    /// Whether the user defined this category
+    pub fn is_custom(&self) -> bool {
+        matches!(self, Self::Custom(_))
+    }
+
+    /// Find the category whose page shows a MIME type, preferring one that
+    /// lists it as primary over one that only has it in the drill-down
+    pub fn for_mime_type(mime: &str) -> Option<Self> {
+        let all = Self::all();
+        all.iter()
+            .find(|category| category.primary_mime_types().contains(&mime))
+            .or_else(|| {
+                all.iter()
+                    .find(|category| category.extended_mime_types().contains(&mime))
+            })
+            .cloned()
+    }
+}
+
+/// Categories are written as their command-line ID (e.g. "web-browser")
+impl Serialize for AppCategory {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.id())
+    }
+}
+
+impl<'de> Deserialize<'de> for AppCategory {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = String::deserialize(deserializer)?;
+        Self::from_id(&id)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown category '{}'", id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serde_uses_ids() {
+        let json = serde_json::to_string(&AppCategory::TerminalEmulator).unwrap();
+        assert_eq!(json, r#""terminal""#);
+        assert_eq!(
+            serde_json::from_str::<AppCategory>(&json).unwrap(),
+            AppCategory::TerminalEmulator
+        );
+        assert!(serde_json::from_str::<AppCategory>(r#""browser""#).is_err());
+    }
+
+    #[test]
+    fn test_custom_id() {
+        assert_eq!(custom_id("E-Book Readers"), "custom-e-book-readers");
+        assert_eq!(custom_id("  CAD / 3D  "), "custom-cad-3d");
+        assert_eq!(
+            CustomCategory::new("Maps", " ", vec![]).icon,
+            CUSTOM_DEFAULT_ICON
+        );
+    }
+
+    #[test]
+    fn test_for_mime_type() {
+        assert_eq!(
+            AppCategory::for_mime_type("application/pdf"),
+            Some(AppCategory::DocumentViewer)
+        );
+        // text/html is primary for browsers and extended for editors
+        assert_eq!(
+            AppCategory::for_mime_type("text/html"),
+            Some(AppCategory::WebBrowser)
+        );
+        assert_eq!(
+            AppCategory::for_mime_type("text/x-rust"),
+            Some(AppCategory::TextEditor)
+        );
+        assert_eq!(
+            AppCategory::for_mime_type("audio/x-mpegurl"),
+            Some(AppCategory::MusicPlayer)
+        );
+        // Editors list wav files too, but playing them is the usual default
+        assert_eq!(
+            AppCategory::for_mime_type("audio/x-wav"),
+            Some(AppCategory::MusicPlayer)
+        );
+        assert_eq!(
+            AppCategory::for_mime_type("x-scheme-handler/ftp"),
+            Some(AppCategory::WebBrowser)
+        );
+        // SVG is primary for vector editors and extended for image viewers
+        assert_eq!(
+            AppCategory::for_mime_type("image/svg+xml"),
+            Some(AppCategory::VectorEditor)
+        );
+        assert_eq!(
+            AppCategory::for_mime_type("x-scheme-handler/vscode"),
+            Some(AppCategory::VersionControl)
+        );
+        assert_eq!(
+            AppCategory::for_mime_type("application/octet-stream"),
+            Some(AppCategory::FallbackHandler)
+        );
+        assert_eq!(AppCategory::for_mime_type("application/x-nothing"), None);
+    }
+}
@@ -0,0 +1,306 @@
+use std::path::Path;
+
+use super::entry::AppEntry;
+use super::providers::Provider;
+
+/// Synthetic applications: desktop file ID, name, generic name, icon,
+/// categories and MIME types
+const DEMO_APPS: &[(&str, &str, &str, &str, &str, &str)] = &[
+    (
+        "firefox.desktop",
+        "Firefox",
+        "Web Browser",
+        "firefox",
+        "Network;WebBrowser;",
+        "text/html;application/xhtml+xml;x-scheme-handler/http;x-scheme-handler/https;",
+    ),
+    (
+        "chromium.desktop",
+        "Chromium",
+        "Web Browser",
+        "chromium",
+        "Network;WebBrowser;",
+        "text/html;x-scheme-handler/http;x-scheme-handler/https;application/pdf;",
+    ),
+    (
+        "thunderbird.desktop",
+        "Thunderbird",
+        "Mail Client",
+        "thunderbird",
+        "Network;Email;",
+        "x-scheme-handler/mailto;message/rfc822;",
+    ),
+    (
+        "org.gnome.Geary.desktop",
+        "Geary",
+        "Mail Client",
+        "org.gnome.Geary",
+        "Network;Email;",
+        "x-scheme-handler/mailto;",
+    ),
+    (
+        "org.gnome.Nautilus.desktop",
+        "Files",
+        "File Manager",
+        "org.gnome.Nautilus",
+        "System;FileManager;",
+        "inode/directory;",
+    ),
+    (
+        "org.kde.dolphin.desktop",
+        "Dolphin",
+        "File Manager",
+        "system-file-manager",
+        "System;FileManager;",
+        "inode/directory;",
+    ),
+    (
+        "org.gnome.Console.desktop",
+        "Console",
+        "Terminal",
+        "org.gnome.Console",
+        "System;TerminalEmulator;",
+        "",
+    ),
+    (
+        "Alacritty.desktop",
+        "Alacritty",
+        "Terminal",
+        "Alacritty",
+        "System;TerminalEmulator;",
+        "",
+    ),
+    (
+        "org.gnome.TextEditor.desktop",
+        "Text Editor",
+        "Text Editor",
+        "org.gnome.TextEditor",
+        "Utility;TextEditor;",
+        "text/plain;text/markdown;application/x-shellscript;",
+    ),
+    (
+        "code.desktop",
+        "Visual Studio Code",
+        "Text Editor",
+        "vscode",
+        "Development;IDE;TextEditor;",
+        "text/plain;text/markdown;text/x-python;application/json;inode/directory;x-scheme-handler/vscode;",
+    ),
+    (
+        "org.gnome.Rhythmbox3.desktop",
+        "Rhythmbox",
+        "Music Player",
+        "org.gnome.Rhythmbox3",
+        "AudioVideo;Audio;Player;",
+        "audio/mpeg;audio/flac;audio/ogg;audio/x-vorbis+ogg;",
+    ),
+    (
+        "vlc.desktop",
+        "VLC media player",
+        "Media player",
+        "vlc",
+        "AudioVideo;Player;Video;",
+        "video/mp4;video/x-matroska;video/webm;audio/mpeg;audio/flac;",
+    ),
+    (
+        "mpv.desktop",
+        "mpv Media Player",
+        "Multimedia player",
+        "mpv",
+        "AudioVideo;Audio;Video;Player;",
+        "video/mp4;video/x-matroska;video/webm;",
+    ),
+    (
+        "org.audacityteam.Audacity.desktop",
+        "Audacity",
+        "Sound Editor",
+        "audacity",
+        "AudioVideo;Audio;AudioVideoEditing;",
+        "application/x-audacity-project;audio/x-wav;audio/flac;audio/mpeg;",
+    ),
+    (
+        "org.kde.kdenlive.desktop",
+        "Kdenlive",
+        "Video Editor",
+        "kdenlive",
+        "Qt;KDE;AudioVideo;AudioVideoEditing;",
+        "application/x-kdenlive;video/mp4;video/x-matroska;",
+    ),
+    (
+        "org.gnome.Loupe.desktop",
+        "Image Viewer",
+        "Image Viewer",
+        "org.gnome.Loupe",
+        "Graphics;Viewer;",
+        "image/png;image/jpeg;image/gif;image/webp;image/svg+xml;",
+    ),
+    (
+        "gimp.desktop",
+        "GNU Image Manipulation Program",
+        "Image Editor",
+        "gimp",
+        "Graphics;2DGraphics;RasterGraphics;",
+        "image/png;image/jpeg;image/gif;",
+    ),
+    (
+        "sqlitebrowser.desktop",
+        "DB Browser for SQLite",
+        "Database Browser",
+        "sqlitebrowser",
+        "Development;Utility;Database;",
+        "application/vnd.sqlite3;application/x-sqlite3;",
+    ),
+    (
+        "blender.desktop",
+        "Blender",
+        "3D Modeler",
+        "blender",
+        "Graphics;3DGraphics;",
+        "application/x-blender;model/gltf+json;model/gltf-binary;model/obj;model/stl;",
+    ),
+    (
+        "org.inkscape.Inkscape.desktop",
+        "Inkscape",
+        "Vector Graphics Editor",
+        "org.inkscape.Inkscape",
+        "Graphics;VectorGraphics;GTK;",
+        "image/svg+xml;image/svg+xml-compressed;application/illustrator;image/x-eps;application/pdf;",
+    ),
+    (
+        "org.gnome.Evince.desktop",
+        "Document Viewer",
+        "Document Viewer",
+        "org.gnome.Evince",
+        "Office;Viewer;",
+        "application/pdf;application/postscript;image/vnd.djvu;",
+    ),
+    (
+        "org.kde.okular.desktop",
+        "Okular",
+        "Document Viewer",
+        "okular",
+        "Office;Viewer;",
+        "application/pdf;application/epub+zip;",
+    ),
+    (
+        "org.gnome.FileRoller.desktop",
+        "File Roller",
+        "Archive Manager",
+        "org.gnome.FileRoller",
+        "Utility;Archiving;Compression;",
+        "application/zip;application/x-tar;application/x-7z-compressed;application/gzip;",
+    ),
+    (
+        "org.gnome.GHex.desktop",
+        "GHex",
+        "Hex Editor",
+        "org.gnome.GHex",
+        "GNOME;GTK;Development;Utility;",
+        "application/octet-stream;",
+    ),
+    (
+        "org.gnome.Calculator.desktop",
+        "Calculator",
+        "Calculator",
+        "org.gnome.Calculator",
+        "Utility;Calculator;",
+        "",
+    ),
+    (
+        "org.gnome.Calendar.desktop",
+        "Calendar",
+        "Calendar",
+        "org.gnome.Calendar",
+        "Office;Calendar;",
+        "text/calendar;x-scheme-handler/webcal;x-scheme-handler/webcals;",
+    ),
+    (
+        "libreoffice-writer.desktop",
+        "LibreOffice Writer",
+        "Word Processor",
+        "libreoffice-writer",
+        "Office;WordProcessor;",
+        "application/vnd.oasis.opendocument.text;application/msword;application/vnd.openxmlformats-officedocument.wordprocessingml.document;",
+    ),
+    (
+        "libreoffice-calc.desktop",
+        "LibreOffice Calc",
+        "Spreadsheet",
+        "libreoffice-calc",
+        "Office;Spreadsheet;",
+        "application/vnd.oasis.opendocument.spreadsheet;application/vnd.ms-excel;text/csv;",
+    ),
+];
+
+/// User mimeapps.list for demo mode: some categories fully configured, some
+/// partially, some not at all
+pub const DEMO_MIMEAPPS: &str = "\
+[Default Applications]
+text/html=firefox.desktop;
+x-scheme-handler/http=firefox.desktop;
+x-scheme-handler/https=firefox.desktop;
+x-scheme-handler/mailto=thunderbird.desktop;
+message/rfc822=thunderbird.desktop;
+inode/directory=org.gnome.Nautilus.desktop;
+text/plain=org.gnome.TextEditor.desktop;
+video/mp4=vlc.desktop;
+image/png=org.gnome.Loupe.desktop;
+image/jpeg=org.gnome.Loupe.desktop;
+application/pdf=org.gnome.Evince.desktop;
+
+[Added Associations]
+text/html=firefox.desktop;chromium.desktop;
+application/pdf=org.gnome.Evince.desktop;org.kde.okular.desktop;
+
+[Removed Associations]
+application/pdf=chromium.desktop;
+";
+
+/// Fake but realistic applications for screenshots, UI work and running
+/// without any desktop files. Never touches the filesystem.
+pub struct DemoProvider;
+
+impl Provider for DemoProvider {
+    fn id(&self) -> &'static str {
+        "demo"
+    }
+
+    fn discover(&self, locales: &[String]) -> Vec<AppEntry> {
+        DEMO_APPS
+            .iter()
+            .filter_map(|(id, name, generic_name, icon, categories, mime_types)| {
+                let exec = id.trim_end_matches(".desktop").to_lowercase();
+                let content = format!(
+                    "[Desktop Entry]\nType=Application\nName={}\nGenericName={}\n\
+                     Icon={}\nExec={} %U\nCategories={}\nMimeType={}\n",
+                    name, generic_name, icon, exec, categories, mime_types
+                );
+                AppEntry::parse(&content, &Path::new("/demo").join(id), locales)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::document::{MimeAppsDocument, DEFAULT_APPLICATIONS};
+
+    #[test]
+    fn test_demo_defaults_point_at_demo_apps() {
+        let apps = DemoProvider.discover(&[]);
+        assert_eq!(apps.len(), DEMO_APPS.len());
+
+        let doc = MimeAppsDocument::parse(DEMO_MIMEAPPS);
+        for (mime, defaults) in doc.entries(DEFAULT_APPLICATIONS) {
+            for id in defaults {
+                assert!(
+                    apps.iter().any(|app| *app.id == **id),
+                    "{} for {}",
+                    id,
+                    mime
+                );
+            }
+        }
+    }
+}
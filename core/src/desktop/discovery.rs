@@ -0,0 +1,662 @@
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::categories::AppCategory;
+use super::demo::DemoProvider;
+use super::entry::AppEntry;
+use super::mime_db::MimeDatabase;
+use super::native::{current_desktops, native_score};
+use super::providers::{default_providers, flatpak_dirs, snap_dirs, Provider};
+use crate::config::settings::Settings;
+
+/// Why an application is listed under a category
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatchReason {
+    /// It declares one of the category's MIME types (`mime`), directly or
+    /// through a wildcard such as `audio/*` (`declared`)
+    MimeType { mime: String, declared: String },
+    /// It lists the scheme of one of the category's `x-scheme-handler/`
+    /// types in X-KDE-Protocols
+    Protocol(String),
+    /// It has one of the category's desktop categories
+    DesktopCategory(&'static str),
+}
+
+impl MatchReason {
+    /// Short explanation, such as "Declares image/*, which covers image/png"
+    pub fn description(&self) -> String {
+        match self {
+            Self::MimeType { mime, declared } if mime == declared => {
+                format!("Declares {}", mime)
+            }
+            Self::MimeType { mime, declared } => {
+                format!("Declares {}, which covers {}", declared, mime)
+            }
+            Self::Protocol(protocol) => {
+                format!("Lists the {} protocol in X-KDE-Protocols", protocol)
+            }
+            Self::DesktopCategory(category) => {
+                format!("In the desktop category \u{201c}{}\u{201d}", category)
+            }
+        }
+    }
+}
+
+/// Registry of all discovered applications.
+///
+/// The desktop file IDs, MIME types and categories of the entries are
+/// interned (one `Arc<str>` per distinct string) and the indexes use the
+/// same strings, so the `text/plain` of hundreds of apps is stored once.
+pub struct AppRegistry {
+    /// All discovered applications, keyed by desktop file ID
+    apps: HashMap<Arc<str>, AppEntry>,
+    /// Applications indexed by MIME type
+    by_mime: HashMap<Arc<str>, Vec<Arc<str>>>,
+    /// Applications indexed by desktop category (lowercased)
+    by_category: HashMap<Arc<str>, Vec<Arc<str>>>,
+}
+
+/// The distinct strings of the entries being indexed
+#[derive(Default)]
+struct StringTable(HashSet<Arc<str>>);
+
+impl StringTable {
+    /// The shared copy of `s`, added on first use
+    fn intern(&mut self, s: &str) -> Arc<str> {
+        if let Some(shared) = self.0.get(s) {
+            return Arc::clone(shared);
+        }
+        let shared: Arc<str> = Arc::from(s);
+        self.0.insert(Arc::clone(&shared));
+        shared
+    }
+}
+
+impl AppRegistry {
+    /// Scan the system for applications, using the providers that aren't
+    /// disabled in the settings. There is deliberately no constructor
+    /// scanning every provider, so nothing lists apps the user turned off.
+    pub fn with_settings(settings: &Settings) -> Self {
+        let providers: Vec<Box<dyn Provider>> = default_providers()
+            .into_iter()
+            .filter(|provider| {
+                !settings
+                    .disabled_providers
+                    .iter()
+                    .any(|id| id == provider.id())
+            })
+            .collect();
+
+        Self::from_providers(&providers)
+    }
+
+    /// The synthetic applications of demo mode, without touching the
+    /// filesystem
+    pub fn demo() -> Self {
+        let providers: Vec<Box<dyn Provider>> = vec![Box::new(DemoProvider)];
+        Self::from_providers(&providers)
+    }
+
+    /// Collect applications from the given providers, earlier ones first
+    pub fn from_providers(providers: &[Box<dyn Provider>]) -> Self {
+        let locales = get_locales();
+        let mut registry = Self {
+            apps: HashMap::new(),
+            by_mime: HashMap::new(),
+            by_category: HashMap::new(),
+        };
+        let mut strings = StringTable::default();
+
+        for provider in providers {
+            let apps = provider.discover(&locales);
+            tracing::debug!(
+                "Provider {} found {} applications",
+                provider.id(),
+                apps.len()
+            );
+            for app in apps {
+                registry.index_app(app, &mut strings);
+            }
+        }
+
+        registry
+    }
+
+    fn index_app(&mut self, mut app: AppEntry, strings: &mut StringTable) {
+        // Don't override user apps with system apps (first one wins)
+        if self.apps.contains_key(&app.id) {
+            return;
+        }
+        app.id = strings.intern(&app.id);
+        for mime in &mut app.mime_types {
+            *mime = strings.intern(mime);
+        }
+        for cat in &mut app.categories {
+            *cat = strings.intern(cat);
+        }
+
+        // Index by MIME types
+        for mime in &app.mime_types {
+            index(&mut self.by_mime, Arc::clone(mime), &app.id);
+        }
+        for protocol in &app.protocols {
+            let mime = strings.intern(&format!("x-scheme-handler/{}", protocol));
+            index(&mut self.by_mime, mime, &app.id);
+        }
+
+        // Index by categories
+        for cat in &app.categories {
+            index(
+                &mut self.by_category,
+                strings.intern(&cat.to_lowercase()),
+                &app.id,
+            );
+        }
+
+        self.apps.insert(Arc::clone(&app.id), app);
+    }
+
+    /// Get an application by its desktop file ID
+    pub fn get_app(&self, id: &str) -> Option<&AppEntry> {
+        self.apps.get(id)
+    }
+
+    /// The application a command line launches: a desktop file ID is looked
+    /// up directly, otherwise the first app whose Exec runs the same program
+    pub fn app_for_command(&self, command: &str) -> Option<&AppEntry> {
+        let program = shell_words::split(command).ok()?.into_iter().next()?;
+        if program.ends_with(".desktop") {
+            return self.get_app(&program);
+        }
+
+        let name = Path::new(&program).file_name()?;
+        self.all_apps().into_iter().find(|app| {
+            app.exec
+                .as_deref()
+                .and_then(|exec| shell_words::split(exec).ok())
+                .and_then(|argv| argv.into_iter().next())
+                .is_some_and(|exec| Path::new(&exec).file_name() == Some(name))
+        })
+    }
+
+    /// Get all applications that support a MIME type
+    pub fn apps_for_mime(&self, mime: &str) -> Vec<&AppEntry> {
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut app_ids: Vec<&str> = Vec::new();
+
+        // First check direct matches
+        if let Some(direct_matches) = self.by_mime.get(mime) {
+            for id in direct_matches {
+                if seen.insert(id) {
+                    app_ids.push(id);
+                }
+            }
+        }
+
+        // Also check pattern matches (e.g., apps that declare "audio/*")
+        if let Some((main_type, _subtype)) = mime.split_once('/') {
+            let pattern = format!("{}/*", main_type);
+            if let Some(pattern_apps) = self.by_mime.get(pattern.as_str()) {
+                for id in pattern_apps {
+                    if seen.insert(id) {
+                        app_ids.push(id);
+                    }
+                }
+            }
+        }
+
+        app_ids
+            .into_iter()
+            .filter_map(|id| self.apps.get(id))
+            .collect()
+    }
+
+    /// Get all applications with a given desktop category
+    pub fn apps_for_category(&self, category: &str) -> Vec<&AppEntry> {
+        self.by_category
+            .get(category.to_lowercase().as_str())
+            .map(|ids| ids.iter().filter_map(|id| self.apps.get(id)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Get all applications for an AppCategory (combines MIME and desktop
+    /// category search), through its MIME types with the user's promotions
+    /// and demotions
+    pub fn apps_for_app_category(
+        &self,
+        category: &AppCategory,
+        settings: &Settings,
+    ) -> Vec<&AppEntry> {
+        let mut seen = HashSet::new();
+        let mut apps = Vec::new();
+
+        // Search by MIME types
+        for mime in settings.primary_mime_types(category) {
+            for app in self.apps_for_mime(&mime) {
+                if seen.insert(&app.id) {
+                    apps.push(app);
+                }
+            }
+        }
+
+        // Search by desktop categories
+        for cat in category.desktop_categories() {
+            for app in self.apps_for_category(cat) {
+                if seen.insert(&app.id) {
+                    apps.push(app);
+                }
+            }
+        }
+
+        // Sort by name
+        apps.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        apps
+    }
+
+    /// The categories to offer, in display order: all of them except those
+    /// hidden when no installed application fits them
+    pub fn visible_categories(&self, settings: &Settings) -> Vec<AppCategory> {
+        AppCategory::all()
+            .into_iter()
+            .filter(|category| {
+                !category.hidden_when_empty()
+                    || !self.apps_for_app_category(category, settings).is_empty()
+            })
+            .collect()
+    }
+
+    /// Get the applications for an AppCategory, most relevant first (ties by
+    /// name). With `prefer_native_apps` set, apps made for the running
+    /// desktop or its toolkit get a boost.
+    pub fn ranked_apps_for_app_category(
+        &self,
+        category: &AppCategory,
+        settings: &Settings,
+    ) -> Vec<&AppEntry> {
+        let desktops = if settings.prefer_native_apps {
+            current_desktops()
+        } else {
+            Vec::new()
+        };
+        let primary = settings.primary_mime_types(category);
+
+        let mut apps = self.apps_for_app_category(category, settings);
+        // Stable sort keeps the name order among equally relevant apps
+        apps.sort_by_key(|app| {
+            std::cmp::Reverse(
+                Self::relevance(app, category, &primary) + native_score(app, &desktops),
+            )
+        });
+        apps
+    }
+
+    /// Score how well an application fits a category. Explicitly declaring
+    /// the category's primary MIME types weighs most, so dedicated handlers
+    /// rank above apps matched through wildcards or desktop categories alone.
+    fn relevance(app: &AppEntry, category: &AppCategory, primary: &[String]) -> usize {
+        let declared = primary
+            .iter()
+            .filter(|mime| app.declares(mime) || app.has_protocol_for(mime))
+            .count();
+
+        let in_category = category
+            .desktop_categories()
+            .iter()
+            .any(|cat| app.has_category(cat));
+
+        declared * 2 + usize::from(in_category)
+    }
+
+    /// Why an application is listed for a category: the category's MIME
+    /// types it declares (in the order `apps_for_mime` would match them),
+    /// with the user's promotions and demotions, and the category's desktop
+    /// categories it has
+    pub fn match_reasons(
+        app: &AppEntry,
+        category: &AppCategory,
+        settings: &Settings,
+    ) -> Vec<MatchReason> {
+        let mut reasons = Vec::new();
+
+        for mime in settings.primary_mime_types(category) {
+            let wildcard = mime
+                .split_once('/')
+                .map(|(main_type, _)| format!("{}/*", main_type));
+            let declared = app.mime_types.iter().find(|m| ***m == *mime).or_else(|| {
+                app.mime_types
+                    .iter()
+                    .find(|m| Some(&***m) == wildcard.as_deref())
+            });
+            if let Some(declared) = declared {
+                reasons.push(MatchReason::MimeType {
+                    mime: mime.clone(),
+                    declared: declared.to_string(),
+                });
+            } else if let Some(protocol) = mime
+                .strip_prefix("x-scheme-handler/")
+                .filter(|_| app.has_protocol_for(&mime))
+            {
+                reasons.push(MatchReason::Protocol(protocol.to_string()));
+            }
+        }
+
+        for cat in category.desktop_categories() {
+            if app.has_category(cat) {
+                reasons.push(MatchReason::DesktopCategory(cat));
+            }
+        }
+
+        reasons
+    }
+
+    /// Search applications by name
+    pub fn search(&self, query: &str) -> Vec<&AppEntry> {
+        let query = query.to_lowercase();
+        let mut results: Vec<&AppEntry> = self
+            .apps
+            .values()
+            .filter(|app| {
+                app.name.to_lowercase().contains(&query)
+                    || app
+                        .generic_name
+                        .as_ref()
+                        .is_some_and(|g| g.to_lowercase().contains(&query))
+                    || app
+                        .comment
+                        .as_ref()
+                        .is_some_and(|c| c.to_lowercase().contains(&query))
+            })
+            .collect();
+
+        results.sort_by(|a, b| a.name.cmp(&b.name));
+        results
+    }
+
+    /// Every MIME type and URL scheme some application declares it handles
+    pub fn declared_mime_types(&self) -> impl Iterator<Item = &str> {
+        self.by_mime.keys().map(|mime| &**mime)
+    }
+
+    /// Every MIME type the shared MIME database defines or an installed
+    /// application declares, sorted
+    pub fn known_mime_types(&self) -> Vec<String> {
+        let mut types: Vec<String> = MimeDatabase::get()
+            .types()
+            .chain(self.declared_mime_types())
+            .map(str::to_string)
+            .collect();
+        types.sort_unstable();
+        types.dedup();
+        types
+    }
+
+    /// Get all applications
+    pub fn all_apps(&self) -> Vec<&AppEntry> {
+        let mut apps: Vec<&AppEntry> = self.apps.values().collect();
+        apps.sort_by(|a, b| a.name.cmp(&b.name));
+        apps
+    }
+}
+
+/// Add an app ID to an index under an interned key
+fn index(map: &mut HashMap<Arc<str>, Vec<Arc<str>>>, key: Arc<str>, id: &Arc<str>) {
+    map.entry(key).or_default().push(Arc::clone(id));
+}
+
+/// Get the list of locales to try for localized values
+fn get_locales() -> Vec<String> {
+    let mut locales = Vec::new();
+
+    // Try LANGUAGE first (colon-separated list)
+    if let Ok(langs) = env::var("LANGUAGE") {
+        for lang in langs.split(':') {
+            if !lang.is_empty() {
+                locales.push(lang.to_string());
+            }
+        }
+    }
+
+    // Try LC_ALL, LC_MESSAGES, LANG
+    for var in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+        if let Ok(locale) = env::var(var) {
+            // Strip encoding suffix (e.g., ".UTF-8") but preserve @modifier
+            // Format can be: lang_COUNTRY.ENCODING@MODIFIER or lang_COUNTRY@MODIFIER
+            let locale = if let Some((base, modifier)) = locale.split_once('@') {
+                // Has modifier - strip encoding from base but keep modifier
+                let base = base.split('.').next().unwrap_or(base);
+                format!("{}@{}", base, modifier)
+            } else {
+                // No modifier - just strip encoding
+                locale.split('.').next().unwrap_or(&locale).to_string()
+            };
+
+            if !locale.is_empty() && locale != "C" && locale != "POSIX" {
+                if !locales.contains(&locale) {
+                    locales.push(locale);
+                }
+            }
+        }
+    }
+
+    locales
+}
+
+/// Locate the .desktop file for a desktop file ID, including entries the
+/// registry skips (NoDisplay/Hidden) and IDs that map to subdirectories
+/// (e.g. "kde4-dolphin.desktop" → "kde4/dolphin.desktop")
+pub fn find_desktop_file(id: &str) -> Option<PathBuf> {
+    for dir in all_application_dirs() {
+        let path = dir.join(id);
+        if path.is_file() {
+            return Some(path);
+        }
+
+        let mut prefix = dir;
+        let mut rest = id;
+        while let Some((subdir, tail)) = rest.split_once('-') {
+            prefix = prefix.join(subdir);
+            let path = prefix.join(tail);
+            if path.is_file() {
+                return Some(path);
+            }
+            rest = tail;
+        }
+    }
+
+    None
+}
+
+/// Every directory desktop files are looked up in: the XDG ones, then the
+/// Flatpak and Snap exports
+pub fn all_application_dirs() -> Vec<PathBuf> {
+    let mut dirs = get_application_dirs();
+    dirs.extend(flatpak_dirs());
+    dirs.extend(snap_dirs());
+    dirs
+}
+
+/// The user's own application directory
+pub fn user_application_dir() -> Option<PathBuf> {
+    if let Ok(data_home) = env::var("XDG_DATA_HOME") {
+        Some(PathBuf::from(data_home).join("applications"))
+    } else if let Ok(home) = env::var("HOME") {
+        Some(PathBuf::from(home).join(".local/share/applications"))
+    } else {
+        None
+    }
+}
+
+/// Get the list of directories to scan for .desktop files
+pub(super) fn get_application_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    // User directory first (higher priority)
+    dirs.extend(user_application_dir());
+
+    // System directories
+    if let Ok(data_dirs) = env::var("XDG_DATA_DIRS") {
+        for dir in data_dirs.split(':') {
+            if !dir.is_empty() {
+                dirs.push(PathBuf::from(dir).join("applications"));
+            }
+        }
+    } else {
+        // Default system directories
+        dirs.push(PathBuf::from("/usr/local/share/applications"));
+        dirs.push(PathBuf::from("/usr/share/applications"));
+    }
+
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::MimeTypeOverrides;
+
+    #[test]
+    fn test_visible_categories_skip_empty_niche_ones() {
+        let registry = AppRegistry::demo();
+
+        let visible = registry.visible_categories(&Settings::default());
+        assert!(!visible.contains(&AppCategory::ScientificData));
+        assert_eq!(visible.len(), AppCategory::all().len() - 1);
+    }
+
+    #[test]
+    fn test_match_reasons() {
+        let mut app = AppEntry::parse(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name=Player\n\
+             Exec=player\n\
+             MimeType=audio/*;audio/flac;\n\
+             Categories=AudioVideo;Audio;\n",
+            Path::new("player.desktop"),
+            &[],
+        )
+        .unwrap();
+
+        let reasons =
+            AppRegistry::match_reasons(&app, &AppCategory::MusicPlayer, &Settings::default());
+        assert!(reasons.contains(&MatchReason::MimeType {
+            mime: "audio/flac".to_string(),
+            declared: "audio/flac".to_string(),
+        }));
+        assert!(reasons.contains(&MatchReason::MimeType {
+            mime: "audio/mpeg".to_string(),
+            declared: "audio/*".to_string(),
+        }));
+        assert!(reasons.contains(&MatchReason::DesktopCategory("Audio")));
+        assert_eq!(
+            reasons[0].description(),
+            "Declares audio/*, which covers audio/mpeg"
+        );
+
+        app.mime_types.clear();
+        assert_eq!(
+            AppRegistry::match_reasons(&app, &AppCategory::MusicPlayer, &Settings::default())
+                .iter()
+                .map(MatchReason::description)
+                .collect::<Vec<_>>(),
+            vec!["In the desktop category \u{201c}Audio\u{201d}"]
+        );
+    }
+
+    #[test]
+    fn test_promoted_and_demoted_types() {
+        let entry = |id: &str, mime: &str| {
+            AppEntry::parse(
+                &format!(
+                    "[Desktop Entry]\nType=Application\nName={}\nExec={}\nMimeType={};\n",
+                    id, id, mime
+                ),
+                Path::new(&format!("{}.desktop", id)),
+                &[],
+            )
+            .unwrap()
+        };
+        let mut registry = AppRegistry::from_providers(&[]);
+        let mut strings = StringTable::default();
+        registry.index_app(entry("jq-viewer", "application/json"), &mut strings);
+        registry.index_app(entry("notepad", "text/plain"), &mut strings);
+        let ids = |settings: &Settings| -> Vec<String> {
+            registry
+                .ranked_apps_for_app_category(&AppCategory::TextEditor, settings)
+                .into_iter()
+                .map(|app| app.id.to_string())
+                .collect()
+        };
+
+        let mut settings = Settings::default();
+        assert_eq!(ids(&settings), vec!["notepad.desktop"]);
+
+        settings.mime_types.insert(
+            AppCategory::TextEditor.id().to_string(),
+            MimeTypeOverrides {
+                promote: vec!["application/json".to_string()],
+                demote: vec!["text/plain".to_string()],
+            },
+        );
+        assert_eq!(ids(&settings), vec!["jq-viewer.desktop"]);
+        let app = registry.get_app("jq-viewer.desktop").unwrap();
+        assert_eq!(
+            AppRegistry::match_reasons(app, &AppCategory::TextEditor, &settings),
+            vec![MatchReason::MimeType {
+                mime: "application/json".to_string(),
+                declared: "application/json".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_kde_protocols_count_as_scheme_handlers() {
+        let app = AppEntry::parse(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name=Konqueror\n\
+             Exec=konqueror %u\n\
+             MimeType=text/html;\n\
+             X-KDE-Protocols=http,HTTPS,ftp\n",
+            Path::new("konqueror.desktop"),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(app.protocols, vec!["http", "https", "ftp"]);
+
+        let mut registry = AppRegistry::from_providers(&[]);
+        registry.index_app(app, &mut StringTable::default());
+        assert_eq!(registry.apps_for_mime("x-scheme-handler/https").len(), 1);
+        assert!(registry.apps_for_mime("x-scheme-handler/mailto").is_empty());
+
+        let app = registry.get_app("konqueror.desktop").unwrap();
+        assert!(app.supports_mime_type("x-scheme-handler/ftp"));
+        assert!(
+            AppRegistry::match_reasons(app, &AppCategory::WebBrowser, &Settings::default())
+                .contains(&MatchReason::Protocol("http".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_entry_strings_are_shared() {
+        fn assert_send<T: Send>() {}
+        assert_send::<AppRegistry>();
+
+        let registry = AppRegistry::demo();
+        let mime = |id: &str| {
+            let app = registry.get_app(id).unwrap();
+            Arc::clone(
+                app.mime_types
+                    .iter()
+                    .find(|m| &***m == "text/html")
+                    .unwrap(),
+            )
+        };
+        assert!(Arc::ptr_eq(
+            &mime("firefox.desktop"),
+            &mime("chromium.desktop")
+        ));
+    }
+}
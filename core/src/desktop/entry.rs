@@ -1,12 +1,17 @@
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::native::desktop_name;
 
 /// Wrapper around a parsed .desktop file with convenient accessors
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppEntry {
     /// Desktop file ID (e.g., "firefox.desktop")
-    pub id: String,
+    pub id: Arc<str>,
     /// Localized application name
     pub name: String,
     /// Generic name (e.g., "Web Browser")
@@ -17,16 +22,34 @@ pub struct AppEntry {
     pub icon: Option<String>,
     /// Exec command line
     pub exec: Option<String>,
+    /// StartupWMClass, the window class or app ID its windows use
+    pub startup_wm_class: Option<String>,
     /// Whether the app needs a terminal
     pub terminal: bool,
+    /// StartupNotify: the app tells the desktop when its window is up, so it
+    /// can be given a startup notification ID or activation token
+    pub startup_notify: bool,
+    /// SingleMainWindow: the app has one main window, and launching it
+    /// again only brings that back
+    pub single_main_window: bool,
     /// NoDisplay flag (hidden from menus)
     pub no_display: bool,
     /// Hidden flag
     pub hidden: bool,
     /// Supported MIME types
-    pub mime_types: Vec<String>,
+    pub mime_types: Vec<Arc<str>>,
+    /// URL schemes listed in KDE's `X-KDE-Protocols` (lowercased), which
+    /// count as `x-scheme-handler/` types
+    pub protocols: Vec<String>,
     /// Application categories
-    pub categories: Vec<String>,
+    pub categories: Vec<Arc<str>>,
+    /// Lowercase desktop environments listed in OnlyShowIn (without any
+    /// `X-` prefix) or used as an `X-<Desktop>-` key prefix (e.g. "gnome",
+    /// "kde", "cinnamon")
+    pub desktop_keys: Vec<String>,
+    /// Application ID from `X-Flatpak`, which Flatpak adds to the desktop
+    /// files it exports
+    pub flatpak: Option<String>,
     /// Path to the .desktop file
     pub path: PathBuf,
 }
@@ -71,7 +94,7 @@ impl AppEntry {
         }
 
         // Get the desktop file name as ID
-        let id = path.file_name()?.to_str()?.to_string();
+        let id = Arc::from(path.file_name()?.to_str()?);
 
         // Get localized name with fallback
         let name = Self::get_localized(&values, "Name", locales)?;
@@ -90,7 +113,11 @@ impl AppEntry {
         let comment = Self::get_localized(&values, "Comment", locales);
         let icon = values.get("Icon").cloned();
         let exec = values.get("Exec").cloned();
+        let startup_wm_class = values.get("StartupWMClass").cloned();
         let terminal = values.get("Terminal").map(|v| v == "true").unwrap_or(false);
+        let startup_notify = values.get("StartupNotify").is_some_and(|v| v == "true");
+        let single_main_window = values.get("SingleMainWindow").is_some_and(|v| v == "true");
+        let flatpak = values.get("X-Flatpak").cloned();
 
         // Parse MIME types
         let mime_types = values
@@ -98,7 +125,18 @@ impl AppEntry {
             .map(|s| {
                 s.split(';')
                     .filter(|s| !s.is_empty())
-                    .map(|s| s.trim().to_string())
+                    .map(|s| Arc::from(s.trim()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // KDE apps list the URL schemes they open as "X-KDE-Protocols=http,https"
+        let protocols = values
+            .get("X-KDE-Protocols")
+            .map(|s| {
+                s.split([',', ';'])
+                    .map(|s| s.trim().to_lowercase())
+                    .filter(|s| !s.is_empty())
                     .collect()
             })
             .unwrap_or_default();
@@ -109,10 +147,29 @@ impl AppEntry {
             .map(|s| {
                 s.split(';')
                     .filter(|s| !s.is_empty())
-                    .map(|s| s.trim().to_string())
+                    .map(|s| Arc::from(s.trim()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // Desktops the entry is written for
+        let mut desktop_keys: Vec<String> = values
+            .get("OnlyShowIn")
+            .map(|s| {
+                s.split(';')
+                    .filter(|s| !s.is_empty())
+                    .map(|s| desktop_name(s.trim()))
                     .collect()
             })
             .unwrap_or_default();
+        for key in values.keys() {
+            if let Some((desktop, _)) = key.strip_prefix("X-").and_then(|k| k.split_once('-')) {
+                let desktop = desktop.to_lowercase();
+                if !desktop_keys.contains(&desktop) {
+                    desktop_keys.push(desktop);
+                }
+            }
+        }
 
         Some(Self {
             id,
@@ -121,11 +178,17 @@ impl AppEntry {
             comment,
             icon,
             exec,
+            startup_wm_class,
             terminal,
+            startup_notify,
+            single_main_window,
             no_display,
             hidden,
             mime_types,
+            protocols,
             categories,
+            desktop_keys,
+            flatpak,
             path: path.to_path_buf(),
         })
     }
@@ -176,7 +239,19 @@ impl AppEntry {
     pub fn supports_mime_type(&self, mime: &str) -> bool {
         self.mime_types
             .iter()
-            .any(|m| m == mime || Self::mime_matches_pattern(m, mime))
+            .any(|m| &**m == mime || Self::mime_matches_pattern(m, mime))
+            || self.has_protocol_for(mime)
+    }
+
+    /// Check if this app lists a MIME type as is, without wildcards
+    pub fn declares(&self, mime: &str) -> bool {
+        self.mime_types.iter().any(|m| &**m == mime)
+    }
+
+    /// Check if an `x-scheme-handler/` type's scheme is in X-KDE-Protocols
+    pub fn has_protocol_for(&self, mime: &str) -> bool {
+        mime.strip_prefix("x-scheme-handler/")
+            .is_some_and(|scheme| self.protocols.iter().any(|p| p == scheme))
     }
 
     /// Check if this app has a given desktop category
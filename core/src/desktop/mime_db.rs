@@ -0,0 +1,224 @@
+use std::collections::{BTreeSet, HashMap};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Data read from the shared MIME database (`mime/` in the XDG data dirs)
+#[derive(Debug, Default)]
+pub struct MimeDatabase {
+    /// File extensions per MIME type, most relevant first (without the dot)
+    extensions: HashMap<String, Vec<String>>,
+    /// Canonical MIME type of each alias (e.g. application/x-pdf)
+    aliases: HashMap<String, String>,
+    /// Types each MIME type is a subclass of
+    parents: HashMap<String, Vec<String>>,
+    /// Every MIME type the database defines
+    types: BTreeSet<String>,
+}
+
+impl MimeDatabase {
+    /// The system MIME database, loaded on first use
+    pub fn get() -> &'static Self {
+        static DATABASE: OnceLock<MimeDatabase> = OnceLock::new();
+        DATABASE.get_or_init(Self::load)
+    }
+
+    /// Load the database from every XDG data directory
+    fn load() -> Self {
+        let mut db = Self::default();
+
+        for dir in mime_dirs() {
+            if let Ok(content) = fs::read_to_string(dir.join("globs2")) {
+                db.add_globs2(&content);
+            }
+            if let Ok(content) = fs::read_to_string(dir.join("aliases")) {
+                db.add_aliases(&content);
+            }
+            if let Ok(content) = fs::read_to_string(dir.join("subclasses")) {
+                db.add_subclasses(&content);
+            }
+            if let Ok(content) = fs::read_to_string(dir.join("types")) {
+                db.types.extend(content.lines().map(str::to_string));
+            }
+        }
+
+        db
+    }
+
+    /// Add entries from a `globs2` file (`weight:mime/type:glob[:flags]`).
+    /// Only simple `*.ext` globs are kept as extensions.
+    fn add_globs2(&mut self, content: &str) {
+        let mut globs: Vec<(u32, &str, &str)> = Vec::new();
+
+        for line in content.lines() {
+            if line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split(':');
+            let (Some(weight), Some(mime), Some(glob)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+
+            let Some(extension) = glob.strip_prefix("*.") else {
+                continue;
+            };
+            if extension.contains(['*', '?', '[']) {
+                continue;
+            }
+
+            globs.push((weight.parse().unwrap_or(50), mime, extension));
+        }
+
+        // Highest weight first, keeping file order among equal weights
+        globs.sort_by_key(|(weight, _, _)| std::cmp::Reverse(*weight));
+
+        for (_, mime, extension) in globs {
+            let extensions = self.extensions.entry(mime.to_string()).or_default();
+            if !extensions.iter().any(|e| e.eq_ignore_ascii_case(extension)) {
+                extensions.push(extension.to_string());
+            }
+        }
+    }
+
+    /// Add entries from an `aliases` file (`alias canonical` per line),
+    /// keeping those from higher priority directories
+    fn add_aliases(&mut self, content: &str) {
+        for (alias, canonical) in content.lines().filter_map(|line| line.split_once(' ')) {
+            self.aliases
+                .entry(alias.to_string())
+                .or_insert_with(|| canonical.trim().to_string());
+        }
+    }
+
+    /// Add entries from a `subclasses` file (`type parent` per line)
+    fn add_subclasses(&mut self, content: &str) {
+        for (mime, parent) in content.lines().filter_map(|line| line.split_once(' ')) {
+            let parents = self.parents.entry(mime.to_string()).or_default();
+            let parent = parent.trim();
+            if !parents.iter().any(|p| p == parent) {
+                parents.push(parent.to_string());
+            }
+        }
+    }
+
+    /// The canonical name of a MIME type that may be an alias
+    pub fn unalias<'a>(&'a self, mime: &'a str) -> &'a str {
+        self.aliases.get(mime).map_or(mime, String::as_str)
+    }
+
+    /// A MIME type followed by the types it is a subclass of, nearest first,
+    /// which is the order to look for a handler in. Every `text/*` type
+    /// falls back to text/plain; application/octet-stream is left out, as
+    /// only a handler the user picked for unknown files should open
+    /// arbitrary data.
+    pub fn lineage(&self, mime: &str) -> Vec<String> {
+        let mut lineage = vec![self.unalias(mime).to_string()];
+
+        let mut i = 0;
+        while i < lineage.len() {
+            for parent in self.parents.get(&lineage[i]).into_iter().flatten() {
+                let parent = self.unalias(parent).to_string();
+                if parent != "application/octet-stream" && !lineage.contains(&parent) {
+                    lineage.push(parent);
+                }
+            }
+            i += 1;
+        }
+
+        if lineage[0].starts_with("text/") && !lineage.iter().any(|m| m == "text/plain") {
+            lineage.push("text/plain".to_string());
+        }
+        lineage
+    }
+
+    /// Every MIME type the database defines, sorted
+    pub fn types(&self) -> impl Iterator<Item = &str> {
+        self.types.iter().map(String::as_str)
+    }
+
+    /// Typical file extensions for a MIME type, most relevant first
+    pub fn extensions(&self, mime: &str) -> &[String] {
+        self.extensions
+            .get(mime)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+/// Directories holding the shared MIME database, highest priority first
+fn mime_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    if let Ok(data_home) = env::var("XDG_DATA_HOME") {
+        dirs.push(PathBuf::from(data_home).join("mime"));
+    } else if let Ok(home) = env::var("HOME") {
+        dirs.push(PathBuf::from(home).join(".local/share/mime"));
+    }
+
+    let data_dirs =
+        env::var("XDG_DATA_DIRS").unwrap_or_else(|_| "/usr/local/share:/usr/share".to_string());
+    for dir in data_dirs.split(':') {
+        if !dir.is_empty() {
+            dirs.push(PathBuf::from(dir).join("mime"));
+        }
+    }
+
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_globs2_extensions() {
+        let mut db = MimeDatabase::default();
+        db.add_globs2(
+            "# comment\n\
+             50:image/jpeg:*.jpg\n\
+             50:image/jpeg:*.jpeg\n\
+             50:image/jpeg:*.JPG:cs\n\
+             80:application/x-7z-compressed:*.7z\n\
+             50:text/x-makefile:makefile\n\
+             50:text/x-log:*.log.[0-9]\n\
+             60:image/jpeg:*.jpe\n",
+        );
+
+        assert_eq!(db.extensions("image/jpeg"), ["jpe", "jpg", "jpeg"]);
+        assert_eq!(db.extensions("application/x-7z-compressed"), ["7z"]);
+        assert!(db.extensions("text/x-makefile").is_empty());
+        assert!(db.extensions("text/x-log").is_empty());
+    }
+
+    #[test]
+    fn test_lineage() {
+        let mut db = MimeDatabase::default();
+        db.add_aliases("application/x-pdf application/pdf\ntext/x-markdown text/markdown\n");
+        db.add_subclasses(
+            "application/x-compressed-tar application/gzip\n\
+             application/x-compressed-tar application/x-tar\n\
+             application/gzip application/octet-stream\n\
+             text/markdown text/plain\n",
+        );
+
+        assert_eq!(db.unalias("application/x-pdf"), "application/pdf");
+        assert_eq!(db.lineage("application/x-pdf"), ["application/pdf"]);
+        assert_eq!(
+            db.lineage("application/x-compressed-tar"),
+            [
+                "application/x-compressed-tar",
+                "application/gzip",
+                "application/x-tar",
+            ]
+        );
+        assert_eq!(
+            db.lineage("text/x-markdown"),
+            ["text/markdown", "text/plain"]
+        );
+        assert_eq!(db.lineage("text/x-rust"), ["text/x-rust", "text/plain"]);
+    }
+}
@@ -0,0 +1,7 @@
+pub mod categories;
+pub mod demo;
+pub mod discovery;
+pub mod entry;
+pub mod mime_db;
+pub mod native;
+pub mod providers;
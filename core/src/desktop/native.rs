@@ -0,0 +1,90 @@
+use std::env;
+
+use super::entry::AppEntry;
+
+/// Running desktop environments from `XDG_CURRENT_DESKTOP`, lowercase and
+/// without any `X-` prefix (e.g. "ubuntu:GNOME" → ["ubuntu", "gnome"])
+pub fn current_desktops() -> Vec<String> {
+    env::var("XDG_CURRENT_DESKTOP")
+        .unwrap_or_default()
+        .split(':')
+        .filter(|desktop| !desktop.is_empty())
+        .map(desktop_name)
+        .collect()
+}
+
+/// Lowercase desktop name without the `X-` prefix of unregistered ones, so
+/// "X-Cinnamon" matches `X-Cinnamon-` keys
+pub fn desktop_name(desktop: &str) -> String {
+    let desktop = desktop.to_lowercase();
+    match desktop.strip_prefix("x-") {
+        Some(name) => name.to_string(),
+        None => desktop,
+    }
+}
+
+/// Interface toolkit (as a desktop category) that apps of a desktop use
+fn toolkit(desktop: &str) -> Option<&'static str> {
+    match desktop {
+        "kde" | "lxqt" | "dde" | "deepin" => Some("Qt"),
+        "gnome" | "ubuntu" | "unity" | "budgie" | "pantheon" | "xfce" | "mate" | "cinnamon"
+        | "lxde" => Some("GTK"),
+        _ => None,
+    }
+}
+
+/// How much an app belongs on one of `desktops`: 2 when it was made for
+/// that desktop (its Categories, OnlyShowIn or `X-<Desktop>-` keys say so),
+/// 1 when it only uses the desktop's toolkit, 0 otherwise
+pub fn native_score(app: &AppEntry, desktops: &[String]) -> usize {
+    desktops
+        .iter()
+        .map(|desktop| {
+            if app.has_category(desktop) || app.desktop_keys.contains(desktop) {
+                2
+            } else if toolkit(desktop).is_some_and(|toolkit| app.has_category(toolkit)) {
+                1
+            } else {
+                0
+            }
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn entry(extra: &str) -> AppEntry {
+        let content = format!(
+            "[Desktop Entry]\nType=Application\nName=Test\nExec=test\n{}\n",
+            extra
+        );
+        AppEntry::parse(&content, Path::new("test.desktop"), &[]).unwrap()
+    }
+
+    #[test]
+    fn test_native_score() {
+        let gnome = vec!["ubuntu".to_string(), "gnome".to_string()];
+        let kde = vec!["kde".to_string()];
+
+        let nautilus = entry("Categories=GNOME;GTK;Utility;");
+        assert_eq!(native_score(&nautilus, &gnome), 2);
+        assert_eq!(native_score(&nautilus, &kde), 0);
+
+        let dolphin = entry("Categories=Qt;System;\nX-KDE-Protocols=file");
+        assert_eq!(native_score(&dolphin, &kde), 2);
+        assert_eq!(native_score(&dolphin, &gnome), 0);
+
+        let qt_app = entry("Categories=Qt;Utility;");
+        assert_eq!(native_score(&qt_app, &kde), 1);
+
+        let nemo = entry("Categories=GTK;\nOnlyShowIn=X-Cinnamon;");
+        assert_eq!(native_score(&nemo, &["cinnamon".to_string()]), 2);
+        assert_eq!(native_score(&nemo, &gnome), 1);
+
+        assert_eq!(native_score(&nautilus, &[]), 0);
+    }
+}
@@ -0,0 +1,185 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::discovery::{get_application_dirs, user_application_dir};
+use super::entry::AppEntry;
+
+/// A source of installed applications.
+///
+/// Providers are queried in order and the first one to report a desktop file
+/// ID wins, so sources listed earlier take precedence.
+pub trait Provider {
+    /// Short identifier used to enable or disable the provider in settings
+    fn id(&self) -> &'static str;
+
+    /// Discover the applications this provider knows about
+    fn discover(&self, locales: &[String]) -> Vec<AppEntry>;
+}
+
+/// All built-in providers, in precedence order
+pub fn default_providers() -> Vec<Box<dyn Provider>> {
+    vec![
+        Box::new(DesktopFileProvider),
+        Box::new(FlatpakProvider),
+        Box::new(SnapProvider),
+        Box::new(AppImageProvider),
+    ]
+}
+
+/// Regular `.desktop` files from the XDG application directories, excluding
+/// those that belong to another provider
+pub struct DesktopFileProvider;
+
+impl Provider for DesktopFileProvider {
+    fn id(&self) -> &'static str {
+        "desktop-files"
+    }
+
+    fn discover(&self, locales: &[String]) -> Vec<AppEntry> {
+        let foreign: Vec<PathBuf> = flatpak_dirs().into_iter().chain(snap_dirs()).collect();
+
+        get_application_dirs()
+            .iter()
+            .filter(|dir| !foreign.contains(dir))
+            .flat_map(|dir| scan_directory(dir, locales))
+            .filter(|app| !is_appimage(app))
+            .collect()
+    }
+}
+
+/// Applications exported by system and per-user Flatpak installations
+pub struct FlatpakProvider;
+
+impl Provider for FlatpakProvider {
+    fn id(&self) -> &'static str {
+        "flatpak"
+    }
+
+    fn discover(&self, locales: &[String]) -> Vec<AppEntry> {
+        flatpak_dirs()
+            .iter()
+            .flat_map(|dir| scan_directory(dir, locales))
+            .collect()
+    }
+}
+
+/// Applications installed as snaps
+pub struct SnapProvider;
+
+impl Provider for SnapProvider {
+    fn id(&self) -> &'static str {
+        "snap"
+    }
+
+    fn discover(&self, locales: &[String]) -> Vec<AppEntry> {
+        snap_dirs()
+            .iter()
+            .flat_map(|dir| scan_directory(dir, locales))
+            .collect()
+    }
+}
+
+/// AppImages integrated into the desktop (by appimaged, AppImageLauncher,
+/// Gear Lever and the like), recognized by an Exec line that runs an AppImage
+pub struct AppImageProvider;
+
+impl Provider for AppImageProvider {
+    fn id(&self) -> &'static str {
+        "appimage"
+    }
+
+    fn discover(&self, locales: &[String]) -> Vec<AppEntry> {
+        // Integration tools only ever write to the user's directory
+        user_application_dir()
+            .map(|dir| scan_directory(&dir, locales))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(is_appimage)
+            .collect()
+    }
+}
+
+/// Flatpak export directories, user installation first
+pub fn flatpak_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    if let Ok(data_home) = env::var("XDG_DATA_HOME") {
+        dirs.push(PathBuf::from(data_home).join("flatpak/exports/share/applications"));
+    } else if let Ok(home) = env::var("HOME") {
+        dirs.push(PathBuf::from(home).join(".local/share/flatpak/exports/share/applications"));
+    }
+    dirs.push(PathBuf::from("/var/lib/flatpak/exports/share/applications"));
+
+    dirs
+}
+
+/// Whether a desktop entry is a Flatpak export
+pub fn is_flatpak(app: &AppEntry) -> bool {
+    flatpak_dirs().iter().any(|dir| app.path.starts_with(dir))
+}
+
+/// Snap's desktop file directory
+pub fn snap_dirs() -> Vec<PathBuf> {
+    vec![PathBuf::from("/var/lib/snapd/desktop/applications")]
+}
+
+/// Whether a desktop entry launches an AppImage
+fn is_appimage(app: &AppEntry) -> bool {
+    app.exec
+        .as_deref()
+        .and_then(|exec| shell_words::split(exec).ok())
+        .and_then(|parts| parts.into_iter().next())
+        .is_some_and(|program| program.to_lowercase().ends_with(".appimage"))
+}
+
+/// Parse every `.desktop` file directly inside a directory
+fn scan_directory(dir: &Path, locales: &[String]) -> Vec<AppEntry> {
+    let entries = match fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(e) => {
+            tracing::debug!("Could not read directory {}: {}", dir.display(), e);
+            return Vec::new();
+        }
+    };
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        // Only process .desktop files
+        .filter(|path| path.extension().is_some_and(|ext| ext == "desktop"))
+        .filter_map(|path| {
+            let entry = AppEntry::from_path(&path, locales);
+            if entry.is_none() {
+                tracing::debug!(
+                    "Skipping {}: unreadable, hidden, or not an application",
+                    path.display()
+                );
+            }
+            entry
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(exec: &str) -> AppEntry {
+        let content = format!(
+            "[Desktop Entry]\nType=Application\nName=Test\nExec={}\n",
+            exec
+        );
+        AppEntry::parse(&content, Path::new("test.desktop"), &[]).unwrap()
+    }
+
+    #[test]
+    fn test_is_appimage() {
+        assert!(is_appimage(&entry(
+            "/home/me/Applications/Tool-x86_64.AppImage %U"
+        )));
+        assert!(is_appimage(&entry("\"/opt/My Apps/tool.appimage\"")));
+        assert!(!is_appimage(&entry("firefox %u")));
+        assert!(!is_appimage(&entry("appimage-manager --list")));
+    }
+}
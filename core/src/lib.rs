@@ -0,0 +1,24 @@
+//! The association logic behind xdg-chooser, without GTK: reading and
+//! writing `mimeapps.list`, parsing and discovering desktop entries, and
+//! grouping applications into categories such as web browsers or image
+//! viewers. The GUI, the command line and the terminal interface all use it,
+//! and so can launchers and file managers.
+//!
+//! ```no_run
+//! use xdg_chooser_core::config::MimeAppsConfig;
+//! use xdg_chooser_core::desktop::discovery::AppRegistry;
+//!
+//! let config = MimeAppsConfig::load()?;
+//! let registry = AppRegistry::with_settings(&config.settings);
+//! if let Some(app) = config
+//!     .get_default("text/html")
+//!     .and_then(|id| registry.get_app(id))
+//! {
+//!     println!("Links open in {}", app.name);
+//! }
+//! # anyhow::Ok(())
+//! ```
+
+pub mod config;
+pub mod desktop;
+pub mod utils;
@@ -0,0 +1,64 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+/// Replace `path` with `content` so readers see either the old or the new
+/// file, never a partial one, and the new one survives a crash: write a
+/// temp file, fsync it, rename it over the target, then fsync the directory
+/// so the rename itself is on disk.
+pub fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    // Ensure parent directory exists
+    let parent = path.parent().filter(|p| !p.as_os_str().is_empty());
+    if let Some(parent) = parent {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    }
+
+    let temp_path = path.with_extension("tmp");
+    let mut file = fs::File::create(&temp_path)
+        .with_context(|| format!("Failed to create {}", temp_path.display()))?;
+
+    file.write_all(content.as_bytes())
+        .with_context(|| format!("Failed to write to {}", temp_path.display()))?;
+
+    file.sync_all()?;
+
+    fs::rename(&temp_path, path).with_context(|| {
+        format!(
+            "Failed to rename {} to {}",
+            temp_path.display(),
+            path.display()
+        )
+    })?;
+
+    // The rename lives in the directory; without this a crash can still
+    // bring back the old file on some filesystems
+    let dir = parent.unwrap_or(Path::new("."));
+    fs::File::open(dir)
+        .and_then(|dir| dir.sync_all())
+        .with_context(|| format!("Failed to sync directory {}", dir.display()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic() {
+        let dir = std::env::temp_dir().join(format!("xdg-chooser-atomic-{}", std::process::id()));
+        let path = dir.join("nested/mimeapps.list");
+
+        write_atomic(&path, "first\n").unwrap();
+        write_atomic(&path, "second\n").unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        let leftover = path.with_extension("tmp").exists();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(content, "second\n");
+        assert!(!leftover);
+    }
+}
@@ -0,0 +1,395 @@
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result};
+
+use crate::config::settings::LaunchSettings;
+use crate::desktop::entry::AppEntry;
+
+/// Variables a clean launch removes: debugging and theming overrides that
+/// were aimed at xdg-chooser's own GTK, and our logging
+const CHOOSER_ENV_VARS: &[&str] = &[
+    "GTK_DEBUG",
+    "GDK_DEBUG",
+    "GSK_DEBUG",
+    "GSK_RENDERER",
+    "GDK_BACKEND",
+    "GTK_THEME",
+    "GTK_INSPECTOR_DISPLAY",
+    "GOBJECT_DEBUG",
+    "G_DEBUG",
+    "G_MESSAGES_DEBUG",
+    "G_ENABLE_DIAGNOSTIC",
+    "RUST_LOG",
+    "RUST_BACKTRACE",
+];
+
+/// Variables that change what code a process loads, removed when
+/// `drop_loader_variables` is set as well
+const LOADER_ENV_VARS: &[&str] = &[
+    "LD_PRELOAD",
+    "LD_LIBRARY_PATH",
+    "LD_AUDIT",
+    "GTK_MODULES",
+    "GTK_PATH",
+    "GIO_EXTRA_MODULES",
+    "GI_TYPELIB_PATH",
+];
+
+/// Parsed exec command
+struct ParsedExec {
+    program: String,
+    args: Vec<String>,
+}
+
+/// Remove desktop entry field codes from an exec string
+/// Field codes: %f, %F, %u, %U, %d, %D, %n, %N, %i, %c, %k, %v, %m
+fn remove_field_codes(exec: &str) -> String {
+    let mut result = String::with_capacity(exec.len());
+    let mut chars = exec.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '%' {
+            if let Some(&next) = chars.peek() {
+                // Check if this is a field code
+                if matches!(next, 'f' | 'F' | 'u' | 'U' | 'd' | 'D' | 'n' | 'N' | 'i' | 'c' | 'k' | 'v' | 'm' | '%')
+                {
+                    if next == '%' {
+                        // %% means literal %
+                        result.push('%');
+                    }
+                    chars.next(); // consume the field code character
+                    continue;
+                }
+            }
+        }
+        result.push(c);
+    }
+
+    result
+}
+
+/// Parse an Exec line from a desktop entry using proper shell-like parsing
+fn parse_exec(exec: &str) -> Result<ParsedExec> {
+    // First remove field codes
+    let cleaned = remove_field_codes(exec);
+
+    // Parse using shell-words to handle quotes and escapes properly
+    let parts = shell_words::split(&cleaned)
+        .with_context(|| format!("Invalid Exec command syntax: {}", exec))?;
+
+    let program = parts
+        .first()
+        .context("Empty Exec command")?
+        .to_string();
+
+    let args = parts.into_iter().skip(1).collect();
+
+    Ok(ParsedExec { program, args })
+}
+
+/// The command line a test launch spawns: the Exec line with field codes
+/// processed, opening `file` if one is given. Apps without file field codes
+/// get the file appended as the last argument. Flatpak apps are started
+/// with [`flatpak_command`].
+pub fn test_command(app: &AppEntry, file: Option<&str>) -> Result<Vec<String>> {
+    if let Some(app_id) = &app.flatpak {
+        return flatpak_command(app_id, app.exec.as_deref(), file);
+    }
+    let exec = app.exec.as_ref().context("No Exec field in desktop entry")?;
+
+    let Some(file_path) = file else {
+        let parsed = parse_exec(exec)?;
+        return Ok(std::iter::once(parsed.program).chain(parsed.args).collect());
+    };
+
+    // Check if the exec line contains file-related field codes
+    let has_file_codes = exec.contains("%f")
+        || exec.contains("%F")
+        || exec.contains("%u")
+        || exec.contains("%U");
+
+    // Replace file field codes with the file path, quoted so paths with
+    // spaces stay one argument
+    let quoted = shell_words::quote(file_path);
+    let exec_with_file = exec
+        .replace("%f", &quoted)
+        .replace("%F", &quoted)
+        .replace("%u", &quoted)
+        .replace("%U", &quoted);
+
+    let parsed = parse_exec(&exec_with_file)?;
+    let mut argv: Vec<String> = std::iter::once(parsed.program).chain(parsed.args).collect();
+
+    // Only add file as explicit argument if no field codes were present
+    if !has_file_codes {
+        argv.push(file_path.to_string());
+    }
+
+    Ok(argv)
+}
+
+/// The command line starting a Flatpak app through `flatpak run`, so it
+/// gets its sandbox and portals, and a running instance is reused. The
+/// options and arguments the exported Exec line gives (`--branch`,
+/// `--command` and so on) are kept, but its `@@` file forwarding markers
+/// are rebuilt: `file` is passed between them with `--file-forwarding`, so
+/// the sandbox is granted access to it through the document portal.
+fn flatpak_command(app_id: &str, exec: Option<&str>, file: Option<&str>) -> Result<Vec<String>> {
+    let words = match exec {
+        Some(exec) => shell_words::split(&remove_field_codes(exec))
+            .with_context(|| format!("Invalid Exec command syntax: {}", exec))?,
+        None => Vec::new(),
+    };
+
+    // `[/usr/bin/]flatpak run [options] <app-id> [args]`, if it is one
+    let runs_flatpak = words.len() > 1
+        && Path::new(&words[0]).file_name() == Some("flatpak".as_ref())
+        && words[1] == "run";
+    let (program, rest) = if runs_flatpak {
+        (words[0].clone(), &words[2..])
+    } else {
+        ("flatpak".to_string(), &[][..])
+    };
+
+    let app_index = rest.iter().position(|word| word == app_id);
+    let (options, args) = match app_index {
+        Some(index) => (&rest[..index], &rest[index + 1..]),
+        None => (&[][..], &[][..]),
+    };
+
+    let mut argv = vec![program, "run".to_string()];
+    argv.extend(
+        options
+            .iter()
+            .filter(|option| *option != "--file-forwarding")
+            .cloned(),
+    );
+    if file.is_some() {
+        argv.push("--file-forwarding".to_string());
+    }
+    argv.push(app_id.to_string());
+    argv.extend(
+        args.iter()
+            .filter(|arg| !matches!(arg.as_str(), "@@" | "@@u"))
+            .cloned(),
+    );
+    if let Some(file) = file {
+        // `@@u` also converts file:// URIs and passes other URIs on as is
+        let uri = file.contains("://");
+        argv.extend([
+            if uri { "@@u" } else { "@@" }.to_string(),
+            file.to_string(),
+            "@@".to_string(),
+        ]);
+    }
+    Ok(argv)
+}
+
+/// Remove the variables a clean launch shouldn't pass on
+fn sanitize_environment(cmd: &mut Command, launch: &LaunchSettings) {
+    if !launch.clean_environment {
+        return;
+    }
+    for var in CHOOSER_ENV_VARS {
+        cmd.env_remove(var);
+    }
+    if launch.drop_loader_variables {
+        for var in LOADER_ENV_VARS {
+            cmd.env_remove(var);
+        }
+    }
+}
+
+/// Spawn a command line detached from our process group. An
+/// `activation_token` is passed on in `XDG_ACTIVATION_TOKEN` (Wayland) and
+/// `DESKTOP_STARTUP_ID` (X11), so the app's window may take the focus and
+/// ends the startup notification.
+pub fn spawn_detached(
+    argv: &[String],
+    launch: &LaunchSettings,
+    activation_token: Option<&str>,
+) -> Result<()> {
+    let (program, args) = argv.split_first().context("Empty command")?;
+
+    let mut cmd = Command::new(program);
+    cmd.args(args);
+    sanitize_environment(&mut cmd, launch);
+    if let Some(token) = activation_token {
+        cmd.env("XDG_ACTIVATION_TOKEN", token)
+            .env("DESKTOP_STARTUP_ID", token);
+    }
+
+    // Detach from parent process using process_group (safer than pre_exec + setsid)
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // Create a new process group so the child doesn't receive signals from this terminal
+        cmd.process_group(0);
+    }
+
+    let mut child = cmd
+        .spawn()
+        .with_context(|| format!("Failed to launch {}", program))?;
+    tracing::info!("Launched {}", shell_words::join(argv));
+
+    // Reap the child and report how it ended
+    let program = program.clone();
+    std::thread::spawn(move || match child.wait() {
+        Ok(status) if status.success() => tracing::info!("{} exited", program),
+        Ok(status) => tracing::warn!("{} exited with {}", program, status),
+        Err(e) => tracing::warn!("Failed to wait for {}: {}", program, e),
+    });
+
+    Ok(())
+}
+
+/// Launch an application with a file argument
+pub fn launch_app_with_file(
+    app: &AppEntry,
+    file_path: &str,
+    launch: &LaunchSettings,
+) -> Result<()> {
+    spawn_detached(&test_command(app, Some(file_path))?, launch, None)
+}
+
+/// Run the user's post-save hook through `sh -c`, passing the changed MIME
+/// types both as positional arguments and in `XDG_CHOOSER_CHANGED`.
+///
+/// The hook runs in the background; a failing exit status is only logged.
+pub fn run_post_save_hook(hook: &str, mimeapps: &Path, changed: &[String]) -> Result<()> {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c")
+        .arg(hook)
+        .arg("xdg-chooser-hook") // $0
+        .args(changed)
+        .env("XDG_CHOOSER_CHANGED", changed.join(" "))
+        .env("XDG_CHOOSER_MIMEAPPS", mimeapps);
+
+    let mut child = cmd
+        .spawn()
+        .with_context(|| format!("Failed to run post-save hook: {}", hook))?;
+
+    // Reap the child so it doesn't linger as a zombie
+    let hook = hook.to_string();
+    std::thread::spawn(move || match child.wait() {
+        Ok(status) if !status.success() => {
+            tracing::warn!("Post-save hook `{}` exited with {}", hook, status);
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Failed to wait for post-save hook `{}`: {}", hook, e),
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_field_codes() {
+        assert_eq!(remove_field_codes("firefox %u"), "firefox ");
+        assert_eq!(remove_field_codes("gedit %F"), "gedit ");
+        assert_eq!(remove_field_codes("app --flag %f --other"), "app --flag  --other");
+        assert_eq!(remove_field_codes("echo %%"), "echo %");
+        assert_eq!(remove_field_codes("no codes here"), "no codes here");
+    }
+
+    #[test]
+    fn test_parse_exec_with_quotes() {
+        let parsed = parse_exec(r#""/usr/bin/my app" --flag "value with spaces""#).unwrap();
+        assert_eq!(parsed.program, "/usr/bin/my app");
+        assert_eq!(parsed.args, vec!["--flag", "value with spaces"]);
+    }
+
+    #[test]
+    fn test_test_command() {
+        let app = AppEntry::parse(
+            "[Desktop Entry]\nType=Application\nName=Viewer\nExec=viewer --new %U\n",
+            Path::new("/tmp/viewer.desktop"),
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(test_command(&app, None).unwrap(), vec!["viewer", "--new"]);
+        assert_eq!(
+            test_command(&app, Some("/tmp/a file.pdf")).unwrap(),
+            vec!["viewer", "--new", "/tmp/a file.pdf"]
+        );
+    }
+
+    #[test]
+    fn test_flatpak_command() {
+        let app = AppEntry::parse(
+            "[Desktop Entry]\nType=Application\nName=Firefox\n\
+             Exec=/usr/bin/flatpak run --branch=stable --arch=x86_64 --command=firefox \
+             --file-forwarding org.mozilla.firefox --new-window @@u %u @@\n\
+             X-Flatpak=org.mozilla.firefox\n",
+            Path::new("/var/lib/flatpak/exports/share/applications/org.mozilla.firefox.desktop"),
+            &[],
+        )
+        .unwrap();
+
+        let run = "/usr/bin/flatpak run --branch=stable --arch=x86_64 --command=firefox";
+        assert_eq!(
+            test_command(&app, None).unwrap().join(" "),
+            format!("{} org.mozilla.firefox --new-window", run)
+        );
+        assert_eq!(
+            test_command(&app, Some("https://example.com/"))
+                .unwrap()
+                .join(" "),
+            format!(
+                "{} --file-forwarding org.mozilla.firefox --new-window @@u https://example.com/ @@",
+                run
+            )
+        );
+        assert_eq!(
+            test_command(&app, Some("/tmp/a page.html")).unwrap()[7..],
+            ["--new-window", "@@", "/tmp/a page.html", "@@"]
+        );
+
+        // Exec lines that don't run flatpak fall back to a plain `flatpak run`
+        assert_eq!(
+            flatpak_command("org.example.App", Some("app %f"), Some("/tmp/x"))
+                .unwrap()
+                .join(" "),
+            "flatpak run --file-forwarding org.example.App @@ /tmp/x @@"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_environment() {
+        let removed = |launch: LaunchSettings| {
+            let mut cmd = Command::new("true");
+            sanitize_environment(&mut cmd, &launch);
+            cmd.get_envs()
+                .filter(|(_, value)| value.is_none())
+                .map(|(key, _)| key.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+
+        assert!(removed(LaunchSettings::default()).is_empty());
+
+        let clean = removed(LaunchSettings {
+            clean_environment: true,
+            drop_loader_variables: false,
+        });
+        assert!(clean.contains(&"GTK_DEBUG".to_string()));
+        assert!(!clean.contains(&"LD_PRELOAD".to_string()));
+
+        let strict = removed(LaunchSettings {
+            clean_environment: true,
+            drop_loader_variables: true,
+        });
+        assert!(strict.contains(&"LD_PRELOAD".to_string()));
+    }
+
+    #[test]
+    fn test_parse_exec_simple() {
+        let parsed = parse_exec("firefox --new-window").unwrap();
+        assert_eq!(parsed.program, "firefox");
+        assert_eq!(parsed.args, vec!["--new-window"]);
+    }
+}
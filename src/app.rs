@@ -1,37 +1,276 @@
+use clap::Parser;
 use gtk::prelude::*;
-use gtk::{gio, glib, Application};
+use gtk::subclass::prelude::*;
+use gtk::{gio, glib};
 
+use crate::cli::Cli;
+use crate::config::MimeAppsConfig;
+use crate::desktop::categories::AppCategory;
+use crate::desktop::discovery::AppRegistry;
+use crate::desktop::mime_db::MimeDatabase;
+use crate::ui::open_with_dialog::create_open_with_dialog;
+use crate::utils::mime::file_mime_type;
 use crate::window::MainWindow;
 
 const APP_ID: &str = "org.gtkchooser.GtkChooser";
 
+/// How long a D-Bus activated instance waits for its first request, and
+/// lingers once its window is closed
+const SERVICE_TIMEOUT_MS: u32 = 10_000;
+
+mod imp {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    #[derive(Default)]
+    pub struct ChooserApplication {
+        /// Token from the launch that is being handled, if the launcher
+        /// provided one
+        pub activation_token: RefCell<Option<String>>,
+        /// The main window, once the first launch created it
+        pub window: RefCell<Option<MainWindow>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ChooserApplication {
+        const NAME: &'static str = "XdgChooserApplication";
+        type Type = super::ChooserApplication;
+        type ParentType = gtk::Application;
+    }
+
+    impl ObjectImpl for ChooserApplication {}
+
+    impl ApplicationImpl for ChooserApplication {
+        fn before_emit(&self, platform_data: &glib::Variant) {
+            // A second launch forwards its activation token (Wayland) or
+            // startup ID (X11) here; without it the compositor won't let
+            // the existing window take focus
+            let dict = glib::VariantDict::new(Some(platform_data));
+            let token = dict
+                .lookup::<String>("activation-token")
+                .ok()
+                .flatten()
+                .or_else(|| dict.lookup::<String>("desktop-startup-id").ok().flatten());
+            self.activation_token.replace(token);
+
+            self.parent_before_emit(platform_data);
+        }
+    }
+
+    impl GtkApplicationImpl for ChooserApplication {}
+}
+
+glib::wrapper! {
+    /// GtkApplication that remembers the activation token of each launch
+    pub struct ChooserApplication(ObjectSubclass<imp::ChooserApplication>)
+        @extends gio::Application, gtk::Application,
+        @implements gio::ActionGroup, gio::ActionMap;
+}
+
+impl ChooserApplication {
+    /// Take the activation token forwarded with the current launch
+    fn take_activation_token(&self) -> Option<String> {
+        self.imp().activation_token.take()
+    }
+
+    /// Bring a window to the front, using the launch's activation token so
+    /// the compositor grants focus instead of just flashing the taskbar
+    fn present_window(&self, window: &impl IsA<gtk::Window>) {
+        if let Some(token) = self.take_activation_token() {
+            window.set_startup_id(&token);
+        }
+        window.present();
+    }
+
+    /// Show the main window for a launch, creating it on the first one.
+    /// Later launches only navigate; `--locked` and `--demo` apply to the
+    /// window they create.
+    fn show_window(&self, options: &StartupOptions) {
+        if let Some(file) = &options.choose {
+            self.show_open_with(file, options);
+            return;
+        }
+
+        let existing = self.imp().window.borrow().clone();
+        let window = existing.unwrap_or_else(|| {
+            let window = MainWindow::new(self.upcast_ref(), options);
+            self.imp().window.replace(Some(window.clone()));
+            window
+        });
+
+        let target = options.target();
+        if let Some((category, _)) = &target {
+            window.show_category(category);
+        }
+        self.present_window(window.window());
+
+        // A file opened with the chooser shows the row of its own type
+        if let Some((category, lineage)) = &target {
+            window.reveal_mime_type(category, lineage);
+        }
+    }
+
+    /// Show the Open With dialog of `--choose` on its own, with fresh data
+    /// so a running window's unsaved state doesn't matter
+    fn show_open_with(&self, file: &gio::File, options: &StartupOptions) {
+        let (config, registry) = options.load_data();
+        let dialog = create_open_with_dialog(self.upcast_ref(), file, config, registry);
+        self.present_window(&dialog);
+    }
+}
+
+/// Command-line options that affect the window (or the terminal interface)
+#[derive(Debug, Clone, Default)]
+pub struct StartupOptions {
+    /// Open the window read-only (`--locked`)
+    pub locked: bool,
+    /// Use synthetic applications and defaults (`--demo`)
+    pub demo: bool,
+    /// Page to open at (`--category`)
+    pub category: Option<AppCategory>,
+    /// MIME type whose category page to open at (`--mime`)
+    pub mime: Option<String>,
+    /// File whose type's category page to open at (`--file`, or a file
+    /// opened through D-Bus)
+    pub file: Option<gio::File>,
+    /// File or URI to pick an application for (`--choose`), instead of
+    /// showing the window
+    pub choose: Option<gio::File>,
+}
+
+impl StartupOptions {
+    /// The associations and applications to show: the real ones, or demo
+    /// data with `--demo`. Applies `--locked` to the settings.
+    pub fn load_data(&self) -> (MimeAppsConfig, AppRegistry) {
+        let (mut config, registry) = if self.demo {
+            (MimeAppsConfig::demo(), AppRegistry::demo())
+        } else {
+            let config = MimeAppsConfig::load().unwrap_or_else(|e| {
+                tracing::warn!("Failed to load config: {}, using defaults", e);
+                MimeAppsConfig::default()
+            });
+            let registry = AppRegistry::with_settings(&config.settings);
+            (config, registry)
+        };
+
+        config.settings.locked |= self.locked;
+        (config, registry)
+    }
+
+    /// The category page requested by `--category`, `--mime` or `--file`.
+    /// Types no page shows fall back to the types they are subclasses of.
+    pub fn target_category(&self) -> Option<AppCategory> {
+        self.target().map(|(category, _)| category)
+    }
+
+    /// The category page requested, with the requested MIME type followed
+    /// by the types it is a subclass of (empty for `--category`)
+    pub fn target(&self) -> Option<(AppCategory, Vec<String>)> {
+        if let Some(category) = &self.category {
+            return Some((category.clone(), Vec::new()));
+        }
+        let mime = match (&self.mime, &self.file) {
+            (Some(mime), _) => mime.clone(),
+            (None, Some(file)) => match file_mime_type(file) {
+                Ok(mime) => mime,
+                Err(e) => {
+                    tracing::warn!("Can't open at the page for a file: {:#}", e);
+                    return None;
+                }
+            },
+            (None, None) => return None,
+        };
+
+        let lineage = MimeDatabase::get().lineage(&mime);
+        let category = lineage
+            .iter()
+            .find_map(|mime| AppCategory::for_mime_type(mime));
+        if category.is_none() {
+            tracing::warn!("No category page shows {}", mime);
+        }
+        category.map(|category| (category, lineage))
+    }
+}
+
+impl From<&Cli> for StartupOptions {
+    fn from(cli: &Cli) -> Self {
+        Self {
+            locked: cli.locked,
+            demo: cli.demo,
+            category: cli.category.clone(),
+            mime: cli.mime.clone(),
+            file: cli.file.as_ref().map(gio::File::for_path),
+            choose: cli.choose.as_deref().map(gio::File::for_commandline_arg),
+        }
+    }
+}
+
 /// Main application struct
 pub struct GtkChooserApp {
-    app: Application,
+    app: ChooserApplication,
 }
 
 impl GtkChooserApp {
     pub fn new() -> Self {
-        let app = Application::builder()
-            .application_id(APP_ID)
-            .flags(gio::ApplicationFlags::default())
+        // Command lines go to the primary instance, so launching again with
+        // --category navigates the open window instead of starting another
+        let app: ChooserApplication = glib::Object::builder()
+            .property("application-id", APP_ID)
+            .property(
+                "flags",
+                gio::ApplicationFlags::HANDLES_COMMAND_LINE | gio::ApplicationFlags::HANDLES_OPEN,
+            )
             .build();
 
         Self { app }
     }
 
+    /// Stay running without a window until a D-Bus call (Activate, Open or
+    /// ActivateAction of org.freedesktop.Application) arrives, as the service
+    /// started for the `DBusActivatable` desktop file
+    pub fn wait_for_activation(&self) {
+        self.app.set_inactivity_timeout(SERVICE_TIMEOUT_MS);
+    }
+
     pub fn run(&self) -> glib::ExitCode {
-        // Connect activate signal
+        self.app.connect_command_line(|app, command_line| {
+            // The launching process already validated these with clap
+            let options = match Cli::try_parse_from(command_line.arguments()) {
+                // Relative paths are relative to the launching process
+                Ok(cli) => StartupOptions {
+                    file: cli
+                        .file
+                        .as_ref()
+                        .map(|path| command_line.create_file_for_arg(path)),
+                    choose: cli
+                        .choose
+                        .as_ref()
+                        .map(|path| command_line.create_file_for_arg(path)),
+                    ..StartupOptions::from(&cli)
+                },
+                Err(e) => {
+                    tracing::warn!("Ignoring forwarded arguments: {}", e);
+                    StartupOptions::default()
+                }
+            };
+            app.show_window(&options);
+            glib::ExitCode::SUCCESS
+        });
+
+        // D-Bus activation and `gapplication launch` don't pass a command line
         self.app.connect_activate(|app| {
-            // Check if window already exists
-            if let Some(window) = app.active_window() {
-                window.present();
-                return;
-            }
+            app.show_window(&StartupOptions::default());
+        });
 
-            // Create new window
-            let window = MainWindow::new(app);
-            window.present();
+        // Files opened through D-Bus (Open With in a file manager, or
+        // `gapplication launch <id> FILE`) show the page for the first
+        // one's type
+        self.app.connect_open(|app, files, _hint| {
+            app.show_window(&StartupOptions {
+                file: files.first().cloned(),
+                ..StartupOptions::default()
+            });
         });
 
         // Set up application actions
@@ -49,6 +288,43 @@ impl GtkChooserApp {
         });
         self.app.add_action(&quit_action);
 
+        // Revert button of the external change notification, with the
+        // (MIME type, previous app) pairs to restore
+        let revert_action = gio::SimpleAction::new(
+            "revert-defaults",
+            Some(&Vec::<(String, String)>::static_variant_type()),
+        );
+        let app = self.app.clone();
+        revert_action.connect_activate(move |_, parameter| {
+            let Some(previous) = parameter.and_then(|p| p.get::<Vec<(String, String)>>()) else {
+                return;
+            };
+            if let Some(window) = app.imp().window.borrow().as_ref() {
+                window.revert_defaults(&previous);
+            }
+        });
+        self.app.add_action(&revert_action);
+
+        // Open at a category's page by ID, for settings panels activating
+        // the app over D-Bus (ActivateAction "show-category" ["web-browser"])
+        let show_category_action =
+            gio::SimpleAction::new("show-category", Some(glib::VariantTy::STRING));
+        let app = self.app.clone();
+        show_category_action.connect_activate(move |_, parameter| {
+            let Some(id) = parameter.and_then(|p| p.str()) else {
+                return;
+            };
+            let Some(category) = AppCategory::from_id(id) else {
+                tracing::warn!("Unknown category '{}'", id);
+                return;
+            };
+            app.show_window(&StartupOptions {
+                category: Some(category),
+                ..StartupOptions::default()
+            });
+        });
+        self.app.add_action(&show_category_action);
+
         // Set keyboard shortcuts
         self.app.set_accels_for_action("app.quit", &["<Ctrl>q"]);
     }
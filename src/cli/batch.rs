@@ -0,0 +1,169 @@
+use std::fmt;
+use std::io::{self, Read};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::config::MimeAppsConfig;
+
+use super::BatchArgs;
+
+/// A single change to apply to the user's mimeapps.list
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(tag = "op", rename_all = "kebab-case")]
+enum Operation {
+    Set { mime: String, app: String },
+    Unset { mime: String },
+    AddAssociation { mime: String, app: String },
+    RemoveAssociation { mime: String, app: String },
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Set { mime, app } => write!(f, "set {} {}", mime, app),
+            Self::Unset { mime } => write!(f, "unset {}", mime),
+            Self::AddAssociation { mime, app } => write!(f, "add-association {} {}", mime, app),
+            Self::RemoveAssociation { mime, app } => {
+                write!(f, "remove-association {} {}", mime, app)
+            }
+        }
+    }
+}
+
+impl Operation {
+    fn apply(&self, config: &mut MimeAppsConfig) -> Result<()> {
+        match self {
+            Self::Set { mime, app } => config.set_default(mime, app),
+            Self::Unset { mime } => {
+                config.remove_default(mime);
+                Ok(())
+            }
+            Self::AddAssociation { mime, app } => config.add_association(mime, app).map(|_| ()),
+            Self::RemoveAssociation { mime, app } => {
+                config.remove_association(mime, app).map(|_| ())
+            }
+        }
+    }
+}
+
+/// Read operations from stdin, apply them all and save once.
+/// Nothing is written if any operation fails.
+pub fn batch(args: BatchArgs) -> Result<()> {
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .context("Failed to read operations from stdin")?;
+
+    let operations = parse_operations(&input)?;
+    let mut config = MimeAppsConfig::load()?;
+
+    for (index, operation) in operations.iter().enumerate() {
+        operation
+            .apply(&mut config)
+            .with_context(|| format!("Operation {} ({}) failed", index + 1, operation))?;
+
+        if args.dry_run {
+            println!("{}", operation);
+        }
+    }
+
+    if args.dry_run {
+        println!("Dry run: {} operation(s) not saved", operations.len());
+    } else {
+        config.save()?;
+        println!("Applied {} operation(s)", operations.len());
+    }
+
+    Ok(())
+}
+
+/// Parse either a JSON array of operations or one operation per line
+fn parse_operations(input: &str) -> Result<Vec<Operation>> {
+    if input.trim_start().starts_with('[') {
+        return serde_json::from_str(input).context("Invalid JSON operation list");
+    }
+
+    let mut operations = Vec::new();
+
+    for (index, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let operation = parse_line(line).with_context(|| format!("Line {}", index + 1))?;
+        operations.push(operation);
+    }
+
+    Ok(operations)
+}
+
+fn parse_line(line: &str) -> Result<Operation> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+
+    let operation = match words.as_slice() {
+        ["set", mime, app] => Operation::Set {
+            mime: mime.to_string(),
+            app: app.to_string(),
+        },
+        ["unset", mime] => Operation::Unset {
+            mime: mime.to_string(),
+        },
+        ["add-association", mime, app] => Operation::AddAssociation {
+            mime: mime.to_string(),
+            app: app.to_string(),
+        },
+        ["remove-association", mime, app] => Operation::RemoveAssociation {
+            mime: mime.to_string(),
+            app: app.to_string(),
+        },
+        _ => bail!("Unrecognized operation '{}'", line),
+    };
+
+    Ok(operation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lines() {
+        let operations = parse_operations(
+            "# browser\nset x-scheme-handler/https firefox.desktop\n\nunset text/plain\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            operations,
+            vec![
+                Operation::Set {
+                    mime: "x-scheme-handler/https".into(),
+                    app: "firefox.desktop".into(),
+                },
+                Operation::Unset {
+                    mime: "text/plain".into(),
+                },
+            ]
+        );
+
+        assert!(parse_operations("set text/plain").is_err());
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let operations = parse_operations(
+            r#"[{"op": "remove-association", "mime": "image/png", "app": "gimp.desktop"}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            operations,
+            vec![Operation::RemoveAssociation {
+                mime: "image/png".into(),
+                app: "gimp.desktop".into(),
+            }]
+        );
+    }
+}
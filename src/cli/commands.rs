@@ -0,0 +1,615 @@
+use std::io;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use clap::CommandFactory;
+use serde::Serialize;
+
+use crate::config::document::DEFAULT_APPLICATIONS;
+use crate::config::export::{self, ExportedDefaults};
+use crate::config::import::{apply_export, parse_export, parse_handlr, parse_mimeo};
+use crate::config::presets::load_presets;
+use crate::config::report::Report;
+use crate::config::settings::Settings;
+use crate::config::MimeAppsConfig;
+use crate::desktop::discovery::{find_desktop_file, AppRegistry};
+use crate::desktop::duplicates::flatpak_duplicates;
+use crate::desktop::portal::{portal_choices, PortalChoice};
+use crate::desktop::scheme_review::review_scheme_handlers;
+use crate::utils::atomic::write_atomic;
+
+use super::open::find_handler;
+use super::{
+    parse_category, ApplyPresetArgs, AssociationArgs, CheckArgs, Cli, DuplicatesArgs, ExportArgs,
+    ExportFormat, FlatpakHandlersArgs, GenerateManArgs, HandlersArgs, ImportArgs, ImportSource,
+    InstallVendorArgs, PinArgs, PreferredCopy, PresetsArgs, ReportArgs, ReportFormat,
+    ReviewSchemesArgs, SearchArgs, UnsetArgs,
+};
+
+/// Remove user defaults for a MIME type or every primary type of a category
+pub fn unset(args: UnsetArgs) -> Result<()> {
+    let mut config = MimeAppsConfig::load()?;
+
+    let mimes: Vec<String> = match (&args.mime, &args.category) {
+        (Some(mime), _) => vec![mime.clone()],
+        (None, Some(category)) => config.settings.primary_mime_types(category),
+        (None, None) => unreachable!("clap requires a MIME type or --category"),
+    };
+
+    let mut changed = false;
+    for mime in &mimes {
+        // Read before the default goes
+        let apps: Vec<String> = config
+            .user_document()
+            .get(DEFAULT_APPLICATIONS, mime)
+            .map(<[String]>::to_vec)
+            .unwrap_or_default();
+
+        if config.remove_default(mime) {
+            println!("Removed default for {}", mime);
+            changed = true;
+        } else {
+            println!("No user default set for {}", mime);
+        }
+
+        if args.unassociate {
+            for app in &apps {
+                if config.unassociate(mime, app) {
+                    println!("Dropped {} from the associations of {}", app, mime);
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    if changed {
+        config.save()?;
+    }
+
+    Ok(())
+}
+
+/// Pin or unpin the current defaults of a MIME type or a category
+pub fn pin(args: PinArgs, pinned: bool) -> Result<()> {
+    let mut config = MimeAppsConfig::load()?;
+    if config.settings.locked {
+        bail!("Default applications are locked and can't be changed");
+    }
+
+    let mimes: Vec<String> = match (&args.mime, &args.category) {
+        (Some(mime), _) => vec![mime.clone()],
+        (None, Some(category)) => config.settings.primary_mime_types(category),
+        (None, None) => unreachable!("clap requires a MIME type or --category"),
+    };
+
+    config.set_pinned(&mimes, pinned);
+    config.save_settings()?;
+
+    for mime in &mimes {
+        match (pinned, config.settings.pinned.get(mime)) {
+            (true, Some(app_id)) => println!("Pinned {} to {}", mime, app_id),
+            (true, None) => println!("No default set for {}, not pinned", mime),
+            (false, _) => println!("Unpinned {}", mime),
+        }
+    }
+
+    Ok(())
+}
+
+/// Add an application to the added associations of a MIME type
+pub fn add_association(args: AssociationArgs) -> Result<()> {
+    let mut config = MimeAppsConfig::load()?;
+
+    if config.add_association(&args.mime, &args.app_id)? {
+        config.save()?;
+        println!("Associated {} with {}", args.app_id, args.mime);
+    } else {
+        println!("{} is already associated with {}", args.app_id, args.mime);
+    }
+
+    Ok(())
+}
+
+/// Add an application to the removed associations of a MIME type
+pub fn remove_association(args: AssociationArgs) -> Result<()> {
+    let mut config = MimeAppsConfig::load()?;
+
+    if config.remove_association(&args.mime, &args.app_id)? {
+        config.save()?;
+        println!("Blocked {} for {}", args.app_id, args.mime);
+    } else {
+        println!("{} is already blocked for {}", args.app_id, args.mime);
+    }
+
+    Ok(())
+}
+
+/// An application `handlers --format json` prints
+#[derive(Debug, Serialize)]
+struct HandlerOutput<'a> {
+    id: &'a str,
+    /// `None` when it isn't installed
+    name: Option<&'a str>,
+    /// "default", "associated" or "installed"
+    source: &'a str,
+}
+
+/// Print the candidate applications for a MIME type the way a file manager
+/// resolves them: configured defaults and associations first, then every
+/// installed application declaring the type, minus removed associations
+pub fn handlers(args: HandlersArgs) -> Result<()> {
+    let config = MimeAppsConfig::load()?;
+    let registry = AppRegistry::with_settings(&config.settings);
+    let mime = args.mime.as_str();
+
+    let default = config.get_default(mime);
+    let mut candidates: Vec<(&str, &str)> = config
+        .get_associations(mime)
+        .into_iter()
+        .map(|id| {
+            (
+                id,
+                if Some(id) == default {
+                    "default"
+                } else {
+                    "associated"
+                },
+            )
+        })
+        .collect();
+
+    for app in registry.apps_for_mime(mime) {
+        if !config.is_removed(mime, &app.id) && !candidates.iter().any(|(id, _)| *id == &*app.id) {
+            candidates.push((&app.id, "installed"));
+        }
+    }
+
+    if args.output.is_json() {
+        let output: Vec<HandlerOutput> = candidates
+            .iter()
+            .map(|&(id, source)| HandlerOutput {
+                id,
+                name: registry.get_app(id).map(|app| app.name.as_str()),
+                source,
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    if candidates.is_empty() {
+        println!("No applications handle {}", mime);
+        return Ok(());
+    }
+
+    for (id, source) in candidates {
+        let name = registry
+            .get_app(id)
+            .map(|app| app.name.as_str())
+            .unwrap_or("(not installed)");
+        println!("{}\t{}\t{}", id, name, source);
+    }
+
+    Ok(())
+}
+
+/// Whether the app is what opens the target: the handler `open` would
+/// pick for the MIME type, or for every primary type of the category
+pub fn check(args: CheckArgs) -> Result<bool> {
+    let config = MimeAppsConfig::load()?;
+    let registry = AppRegistry::with_settings(&config.settings);
+
+    let mimes = if args.target.contains('/') {
+        vec![args.target]
+    } else {
+        let category = parse_category(&args.target).map_err(anyhow::Error::msg)?;
+        config.settings.primary_mime_types(&category)
+    };
+    let app_id = if args.app_id.ends_with(".desktop") {
+        args.app_id
+    } else {
+        format!("{}.desktop", args.app_id)
+    };
+
+    Ok(!mimes.is_empty()
+        && mimes.iter().all(|mime| {
+            find_handler(&config, &registry, mime).is_some_and(|(_, app)| *app.id == *app_id)
+        }))
+}
+
+/// List scheme handlers with something unusual about them, one per line
+/// with the reasons indented below
+pub fn review_schemes(args: ReviewSchemesArgs) -> Result<()> {
+    let config = MimeAppsConfig::load()?;
+    let registry = AppRegistry::with_settings(&config.settings);
+    let reviews = review_scheme_handlers(&registry, &config.effective_defaults());
+
+    if args.output.is_json() {
+        println!("{}", serde_json::to_string_pretty(&reviews)?);
+        return Ok(());
+    }
+
+    if reviews.is_empty() {
+        println!("No scheme handler stands out");
+        return Ok(());
+    }
+
+    for review in &reviews {
+        let default_for = if review.default_for.is_empty() {
+            String::new()
+        } else {
+            format!(", default for {}", review.default_for.join(", "))
+        };
+        println!(
+            "{} ({}): {}{}",
+            review.name,
+            review.path.display(),
+            review.schemes.join(", "),
+            default_for
+        );
+        for risk in &review.risks {
+            println!("  - {}", risk.description());
+        }
+    }
+
+    Ok(())
+}
+
+/// A pair `duplicates --format json` prints, with the desktop file IDs of
+/// both copies
+#[derive(Debug, Serialize)]
+struct DuplicateOutput<'a> {
+    name: &'a str,
+    native: &'a str,
+    flatpak: &'a str,
+}
+
+/// List native/Flatpak duplicates, or with `--prefer` point every entry
+/// naming one copy at the other
+pub fn duplicates(args: DuplicatesArgs) -> Result<()> {
+    let mut config = MimeAppsConfig::load()?;
+    let registry = AppRegistry::with_settings(&config.settings);
+    let duplicates = flatpak_duplicates(&registry);
+
+    if args.output.is_json() {
+        let output: Vec<DuplicateOutput> = duplicates
+            .iter()
+            .map(|duplicate| DuplicateOutput {
+                name: &duplicate.native.name,
+                native: &duplicate.native.id,
+                flatpak: &duplicate.flatpak.id,
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    if duplicates.is_empty() {
+        println!("No application is installed both natively and as a Flatpak");
+        return Ok(());
+    }
+
+    let Some(prefer) = args.prefer else {
+        for duplicate in &duplicates {
+            println!(
+                "{}\t{}\t{}",
+                duplicate.native.name, duplicate.native.id, duplicate.flatpak.id
+            );
+        }
+        return Ok(());
+    };
+
+    let mut changed = false;
+    for duplicate in &duplicates {
+        let (from, to) = match prefer {
+            PreferredCopy::Native => (duplicate.flatpak, duplicate.native),
+            PreferredCopy::Flatpak => (duplicate.native, duplicate.flatpak),
+        };
+        let mimes = config.replace_app(&from.id, &to.id)?;
+        if !mimes.is_empty() {
+            println!("{} -> {}: {}", from.id, to.id, mimes.join(", "));
+            changed = true;
+        }
+    }
+
+    if changed {
+        config.save()?;
+    } else {
+        println!("Nothing refers to the other copies");
+    }
+    Ok(())
+}
+
+/// Print each handler choice the OpenURI portal recorded for a Flatpak app
+/// as tab-separated requester, type, handler, whether it is remembered and
+/// the system default, noting remembered choices that differ from it
+pub fn flatpak_handlers(args: FlatpakHandlersArgs) -> Result<()> {
+    let config = MimeAppsConfig::load()?;
+    let choices = portal_choices()?;
+
+    if args.output.is_json() {
+        let output: Vec<ChoiceOutput> = choices
+            .iter()
+            .map(|choice| ChoiceOutput {
+                choice,
+                default: config.get_default(&choice.content_type),
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    if choices.is_empty() {
+        println!("No Flatpak app has opened files or links through the portal");
+        return Ok(());
+    }
+
+    let mut differing = 0;
+    for choice in &choices {
+        let default = config.get_default(&choice.content_type);
+        let state = if choice.remembered {
+            "remembered"
+        } else {
+            "asks"
+        };
+        println!(
+            "{}\t{}\t{}\t{}\t{}",
+            choice.requester,
+            choice.content_type,
+            choice.handler,
+            state,
+            default.unwrap_or("(none)")
+        );
+        if choice.remembered && default.is_some_and(|d| d != choice.handler) {
+            differing += 1;
+        }
+    }
+
+    if differing > 0 {
+        eprintln!(
+            "{} remembered choices differ from the system default; reset them with \
+             `flatpak permission-remove desktop-used-apps <type>`",
+            differing
+        );
+    }
+
+    Ok(())
+}
+
+/// A portal choice `flatpak-handlers --format json` prints, with the system
+/// default it is compared to
+#[derive(Debug, Serialize)]
+struct ChoiceOutput<'a> {
+    #[serde(flatten)]
+    choice: &'a PortalChoice,
+    default: Option<&'a str>,
+}
+
+/// Print matching applications as tab-separated desktop ID, name and MIME types
+pub fn search(args: SearchArgs) -> Result<()> {
+    let settings = Settings::load()?;
+    let registry = AppRegistry::with_settings(&settings);
+    let apps = registry.search(&args.query);
+
+    if args.output.is_json() {
+        println!("{}", serde_json::to_string_pretty(&apps)?);
+        return Ok(());
+    }
+
+    for app in apps {
+        println!("{}\t{}\t{}", app.id, app.name, app.mime_types.join(";"));
+    }
+
+    Ok(())
+}
+
+/// Render man pages for the CLI, either the main page to stdout or one page
+/// per subcommand into a directory
+pub fn generate_man(args: GenerateManArgs) -> Result<()> {
+    let command = Cli::command();
+
+    match args.output_dir {
+        Some(dir) => {
+            std::fs::create_dir_all(&dir)
+                .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+            clap_mangen::generate_to(command, &dir)
+                .with_context(|| format!("Failed to write man pages to {}", dir.display()))?;
+        }
+        None => clap_mangen::Man::new(command).render(&mut io::stdout())?,
+    }
+
+    Ok(())
+}
+
+/// Print the available presets as tab-separated ID, name and source
+pub fn presets(args: PresetsArgs) -> Result<()> {
+    let presets = load_presets();
+    if args.output.is_json() {
+        println!("{}", serde_json::to_string_pretty(&presets)?);
+        return Ok(());
+    }
+
+    for preset in presets {
+        let source = preset.source.map_or("", |source| source.label());
+        println!("{}\t{}\t{}", preset.id, preset.name, source);
+    }
+
+    Ok(())
+}
+
+/// Set every default a preset recommends that isn't already in place
+pub fn apply_preset(args: ApplyPresetArgs) -> Result<()> {
+    let Some(preset) = load_presets().into_iter().find(|p| p.id == args.preset) else {
+        bail!("No preset named '{}'", args.preset);
+    };
+
+    let mut config = MimeAppsConfig::load()?;
+    let mut changed = 0;
+
+    for (mime, app_id) in preset.assignments(&config.settings) {
+        if config.get_default(&mime) == Some(app_id.as_str()) {
+            continue;
+        }
+        config
+            .set_default(&mime, &app_id)
+            .with_context(|| format!("Preset sets an invalid default for {}", mime))?;
+        println!("{}\t{}", mime, app_id);
+        changed += 1;
+    }
+
+    if changed == 0 {
+        println!("Already matches {}", preset.name);
+    } else if args.dry_run {
+        println!("Dry run: {} default(s) not saved", changed);
+    } else {
+        config.save()?;
+        println!("Applied {} default(s) from {}", changed, preset.name);
+    }
+
+    Ok(())
+}
+
+/// Print the defaults report to stdout
+pub fn report(args: ReportArgs) -> Result<()> {
+    let config = MimeAppsConfig::load()?;
+    let registry = AppRegistry::with_settings(&config.settings);
+    let report = Report::new(&config, &registry);
+
+    match args.format {
+        ReportFormat::Markdown => print!("{}", report.to_markdown()),
+        ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        ReportFormat::Html => print!("{}", report.to_html()),
+    }
+
+    Ok(())
+}
+
+/// Print the user's associations for configuration management
+pub fn export(args: ExportArgs) -> Result<()> {
+    let config = MimeAppsConfig::load()?;
+
+    match args.format {
+        ExportFormat::Ini => print!("{}", export::to_ini(&config)),
+        ExportFormat::Ansible => print!("{}", export::to_ansible(&config)),
+        ExportFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&ExportedDefaults::new(&config))?
+        ),
+    }
+
+    Ok(())
+}
+
+/// Write the user's defaults to `<data dir>/applications/mimeapps.list`,
+/// replacing the file there
+pub fn install_vendor(args: InstallVendorArgs) -> Result<()> {
+    let config = MimeAppsConfig::load()?;
+    let content = export::to_vendor(&config);
+
+    if args.dry_run {
+        print!("{}", content);
+        return Ok(());
+    }
+
+    let path = args.data_dir.join("applications").join("mimeapps.list");
+    write_atomic(&path, &content)?;
+    println!("Wrote {}", path.display());
+    Ok(())
+}
+
+/// Set defaults and associations from a JSON export, or defaults from
+/// handlr's or mimeo's rules, mapping their commands to installed
+/// applications. What can't be applied is listed on stderr.
+pub fn import(args: ImportArgs) -> Result<()> {
+    let path = match args.file {
+        Some(path) => path,
+        None => {
+            let relative = match args.from {
+                ImportSource::Json => unreachable!("clap requires the file of a JSON import"),
+                ImportSource::Handlr => "handlr/handlr.toml",
+                ImportSource::Mimeo => "mimeo/associations.txt",
+            };
+            xdg::BaseDirectories::new()
+                .context("Failed to determine XDG directories")?
+                .get_config_home()
+                .join(relative)
+        }
+    };
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let rules = match args.from {
+        ImportSource::Json => return import_export(&content, &path, args.dry_run),
+        ImportSource::Handlr => parse_handlr(&content)?,
+        ImportSource::Mimeo => parse_mimeo(&content),
+    };
+
+    let mut config = MimeAppsConfig::load()?;
+    let registry = AppRegistry::with_settings(&config.settings);
+    let mut changed = 0;
+
+    for rule in rules {
+        for pattern in &rule.skipped {
+            eprintln!(
+                "Skipped {} for {}: not a MIME type or URL scheme",
+                pattern, rule.command
+            );
+        }
+        let Some(app) = registry.app_for_command(&rule.command) else {
+            if !rule.mime_types.is_empty() {
+                eprintln!("Skipped {}: no installed application runs it", rule.command);
+            }
+            continue;
+        };
+
+        for mime in &rule.mime_types {
+            if config.get_default(mime) == Some(&*app.id) {
+                continue;
+            }
+            config.set_default(mime, &app.id)?;
+            println!("{}\t{}", mime, app.id);
+            changed += 1;
+        }
+    }
+
+    if changed == 0 {
+        println!("Nothing to import");
+    } else if args.dry_run {
+        println!("Dry run: {} default(s) not saved", changed);
+    } else {
+        config.save()?;
+        println!("Imported {} default(s) from {}", changed, path.display());
+    }
+
+    Ok(())
+}
+
+/// Apply a JSON export from another machine, skipping applications that
+/// aren't installed here
+fn import_export(content: &str, path: &Path, dry_run: bool) -> Result<()> {
+    let exported = parse_export(content)?;
+    let mut config = MimeAppsConfig::load()?;
+    let registry = AppRegistry::with_settings(&config.settings);
+
+    // NoDisplay handlers count too, which the registry leaves out
+    let outcome = apply_export(&mut config, &exported, |id| {
+        registry.get_app(id).is_some() || find_desktop_file(id).is_some()
+    });
+    for line in &outcome.applied {
+        println!("{}", line);
+    }
+    for skipped in &outcome.skipped {
+        eprintln!("Skipped {}", skipped);
+    }
+
+    let changed = outcome.applied.len();
+    if changed == 0 {
+        println!("Nothing to import");
+    } else if dry_run {
+        println!("Dry run: {} change(s) not saved", changed);
+    } else {
+        config.save()?;
+        println!("Imported {} change(s) from {}", changed, path.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,249 @@
+use anyhow::Result;
+use clap::{Arg, Command, CommandFactory};
+use clap_complete::Shell;
+
+use crate::config::settings::Settings;
+use crate::desktop::categories::AppCategory;
+use crate::desktop::discovery::AppRegistry;
+
+use super::{Cli, CompleteValuesArgs, CompletionShell, CompletionsArgs};
+
+/// Bash: ask `complete-values` first, and fall back to the generated
+/// function for subcommands and options
+const BASH_HOOK: &str = r#"
+_xdg_chooser_values() {
+    local values
+    if values=$(xdg-chooser complete-values "$COMP_CWORD" "${COMP_WORDS[@]}" 2>/dev/null); then
+        local IFS=$'\n'
+        COMPREPLY=($(compgen -W "$values" -- "${COMP_WORDS[COMP_CWORD]}"))
+        return 0
+    fi
+    _xdg__chooser "$@"
+}
+"#;
+
+/// The end of clap's zsh script, which registers its function or, when
+/// autoloaded from `fpath`, runs it
+const ZSH_REGISTRATION: &str = r#"if [ "$funcstack[1]" = "_xdg-chooser" ]; then
+    _xdg-chooser "$@"
+else
+    compdef _xdg-chooser xdg-chooser
+fi"#;
+
+/// Zsh: the same as for bash, with `words` counting from 1
+const ZSH_HOOK: &str = r#"_xdg_chooser_values() {
+    local out
+    if out=$(xdg-chooser complete-values $((CURRENT - 1)) "${words[@]}" 2>/dev/null); then
+        local -a values
+        values=(${(f)out})
+        compadd -a values
+        return 0
+    fi
+    _xdg-chooser "$@"
+}
+
+if [ "$funcstack[1]" = "_xdg-chooser" ]; then
+    _xdg_chooser_values "$@"
+else
+    compdef _xdg_chooser_values xdg-chooser
+fi"#;
+
+/// Fish: completions of their own wherever `complete-values` has some
+const FISH_HOOK: &str = r#"
+function __xdg_chooser_values
+    set -l tokens (commandline -opc)
+    xdg-chooser complete-values (count $tokens) $tokens (commandline -ct) 2>/dev/null
+end
+
+complete -c xdg-chooser -n '__xdg_chooser_values >/dev/null' -f -a '(__xdg_chooser_values)'
+"#;
+
+/// What a word being completed can be, listed from this machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueKind {
+    MimeType,
+    AppId,
+    Category,
+    /// `check` takes either
+    MimeTypeOrCategory,
+}
+
+/// Print the completion script for a shell: clap's for the subcommands and
+/// options, with a hook in front that completes MIME types, desktop file
+/// IDs and categories through `complete-values`
+pub fn completions(args: CompletionsArgs) -> Result<()> {
+    print!("{}", script(args.shell));
+    Ok(())
+}
+
+fn script(shell: CompletionShell) -> String {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    let generator = match shell {
+        CompletionShell::Bash => Shell::Bash,
+        CompletionShell::Zsh => Shell::Zsh,
+        CompletionShell::Fish => Shell::Fish,
+    };
+    let mut script = Vec::new();
+    clap_complete::generate(generator, &mut command, name, &mut script);
+    let script = String::from_utf8_lossy(&script);
+
+    match shell {
+        CompletionShell::Bash => {
+            script.replace(
+                "complete -F _xdg__chooser ",
+                "complete -F _xdg_chooser_values ",
+            ) + BASH_HOOK
+        }
+        CompletionShell::Zsh => script.replace(ZSH_REGISTRATION, ZSH_HOOK),
+        CompletionShell::Fish => script.into_owned() + FISH_HOOK,
+    }
+}
+
+/// Print the values the word at `index` of a command line can take, one
+/// per line. Returns false, printing nothing, when it isn't one of the
+/// arguments completed from this machine, so the script falls back to
+/// clap's completions.
+pub fn complete_values(args: CompleteValuesArgs) -> Result<bool> {
+    // Built, so the subcommands know the global options
+    let mut command = Cli::command();
+    command.build();
+    let Some(kind) = value_kind(&command, &args.words, args.index) else {
+        return Ok(false);
+    };
+
+    // Loading the settings registers the user's own categories
+    let settings = Settings::load().unwrap_or_default();
+    let categories = || AppCategory::all().into_iter().map(|c| c.id().to_string());
+    let values: Vec<String> = match kind {
+        ValueKind::MimeType => AppRegistry::with_settings(&settings).known_mime_types(),
+        ValueKind::AppId => AppRegistry::with_settings(&settings)
+            .all_apps()
+            .into_iter()
+            .map(|app| app.id.to_string())
+            .collect(),
+        ValueKind::Category => categories().collect(),
+        ValueKind::MimeTypeOrCategory => categories()
+            .chain(AppRegistry::with_settings(&settings).known_mime_types())
+            .collect(),
+    };
+
+    for value in values {
+        println!("{}", value);
+    }
+    Ok(true)
+}
+
+/// What the word at `index` of a command line (the program being word 0)
+/// is the value of: an option before it that takes one, or else the
+/// positional argument it is
+fn value_kind(command: &Command, words: &[String], index: usize) -> Option<ValueKind> {
+    let mut current = command;
+    let mut in_subcommand = false;
+    let mut positional = 0;
+    let mut pending: Option<&Arg> = None;
+
+    for word in words.get(1..index)? {
+        if pending.take().is_some() {
+            continue;
+        }
+        if let Some(long) = word.strip_prefix("--") {
+            // `--mime=text/plain` carries its value
+            if !long.contains('=') {
+                pending = current
+                    .get_arguments()
+                    .find(|arg| arg.get_long() == Some(long))
+                    .filter(|arg| arg.get_action().takes_values());
+            }
+            continue;
+        }
+        if word.starts_with('-') && word.len() > 1 {
+            continue;
+        }
+        match current.find_subcommand(word).filter(|_| !in_subcommand) {
+            Some(subcommand) => {
+                current = subcommand;
+                in_subcommand = true;
+            }
+            None => positional += 1,
+        }
+    }
+
+    let arg = match pending {
+        Some(arg) => arg,
+        None if words.get(index).is_some_and(|word| word.starts_with('-')) => return None,
+        None => current.get_positionals().nth(positional)?,
+    };
+    match (current.get_name(), arg.get_id().as_str()) {
+        ("check", "target") => Some(ValueKind::MimeTypeOrCategory),
+        (_, "mime") => Some(ValueKind::MimeType),
+        (_, "app_id") => Some(ValueKind::AppId),
+        (_, "category") => Some(ValueKind::Category),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind(line: &str) -> Option<ValueKind> {
+        // The word being completed is the last, empty here
+        let mut words: Vec<String> = line.split_whitespace().map(str::to_string).collect();
+        words.push(String::new());
+        let mut command = Cli::command();
+        command.build();
+        value_kind(&command, &words, words.len() - 1)
+    }
+
+    #[test]
+    fn test_value_kind() {
+        assert_eq!(kind("xdg-chooser unset"), Some(ValueKind::MimeType));
+        assert_eq!(
+            kind("xdg-chooser unset --category"),
+            Some(ValueKind::Category)
+        );
+        assert_eq!(
+            kind("xdg-chooser add-association image/png"),
+            Some(ValueKind::AppId)
+        );
+        assert_eq!(kind("xdg-chooser list-mimes"), Some(ValueKind::AppId));
+        assert_eq!(
+            kind("xdg-chooser check"),
+            Some(ValueKind::MimeTypeOrCategory)
+        );
+        assert_eq!(
+            kind("xdg-chooser check web-browser"),
+            Some(ValueKind::AppId)
+        );
+        assert_eq!(
+            kind("xdg-chooser -v --log-file x.log --mime"),
+            Some(ValueKind::MimeType)
+        );
+        // Subcommands and files are clap's to complete
+        assert_eq!(kind("xdg-chooser"), None);
+        assert_eq!(kind("xdg-chooser open"), None);
+        assert_eq!(kind("xdg-chooser --file"), None);
+        assert_eq!(kind("xdg-chooser unset text/plain"), None);
+        // Global options take their value after the subcommand too
+        assert_eq!(
+            kind("xdg-chooser unset --log-file x.log"),
+            Some(ValueKind::MimeType)
+        );
+    }
+
+    #[test]
+    fn test_scripts_register_the_hook() {
+        let bash = script(CompletionShell::Bash);
+        assert!(bash.contains("_xdg__chooser() {"));
+        assert!(bash.contains("complete -F _xdg_chooser_values -o nosort"));
+        assert!(!bash.contains("complete -F _xdg__chooser "));
+
+        let zsh = script(CompletionShell::Zsh);
+        assert!(zsh.contains("_xdg-chooser() {"));
+        assert!(zsh.contains("compdef _xdg_chooser_values xdg-chooser"));
+        assert!(!zsh.contains(ZSH_REGISTRATION));
+
+        assert!(script(CompletionShell::Fish).ends_with(FISH_HOOK));
+    }
+}
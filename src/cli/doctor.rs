@@ -0,0 +1,245 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+
+use crate::config::diagnostics::Severity;
+use crate::config::MimeAppsConfig;
+use crate::desktop::discovery::{all_application_dirs, find_desktop_file, AppRegistry};
+
+use super::open::find_handler;
+use super::DoctorArgs;
+
+/// A problem `doctor` found, with what to do about it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    pub problem: String,
+    pub fix: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}\n  Fix: {}",
+            self.severity, self.problem, self.fix
+        )
+    }
+}
+
+/// Check the merged configuration, the installed applications and the
+/// directories they come from for common problems, failing if any of them
+/// is an error. The JSON output is an array of the findings, empty when
+/// there are none.
+pub fn doctor(args: DoctorArgs) -> Result<()> {
+    let config = MimeAppsConfig::load()?;
+    let registry = AppRegistry::with_settings(&config.settings);
+
+    let findings = diagnose(&config, &registry, &all_application_dirs());
+    if args.output.is_json() {
+        println!("{}", serde_json::to_string_pretty(&findings)?);
+    } else if findings.is_empty() {
+        println!("No problems found");
+    } else {
+        for finding in &findings {
+            println!("{}", finding);
+        }
+    }
+
+    let errors = findings
+        .iter()
+        .filter(|finding| finding.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        bail!(
+            "{} error(s), {} warning(s)",
+            errors,
+            findings.len() - errors
+        );
+    }
+    Ok(())
+}
+
+/// Every problem found, errors first. `app_dirs` are the application
+/// directories to check that the registry could read.
+pub fn diagnose(
+    config: &MimeAppsConfig,
+    registry: &AppRegistry,
+    app_dirs: &[PathBuf],
+) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    findings.extend(app_dirs.iter().filter_map(|dir| check_app_dir(dir)));
+    findings.extend(missing_defaults(config, registry));
+    findings.extend(config.diagnostics().iter().map(|diagnostic| Finding {
+        severity: diagnostic.severity,
+        problem: diagnostic.to_string(),
+        fix: format!(
+            "Correct or delete line {} of {}; `xdg-chooser lint {}` checks it again",
+            diagnostic.line,
+            diagnostic.path.display(),
+            diagnostic.path.display()
+        ),
+    }));
+    findings.extend(overridden_defaults(config));
+    findings.extend(unhandled_types(config, registry));
+
+    // Stable, so each kind keeps its order
+    findings.sort_by_key(|finding| finding.severity != Severity::Error);
+    findings
+}
+
+/// An application directory that exists but can't be listed, so none of
+/// its applications show up
+fn check_app_dir(dir: &Path) -> Option<Finding> {
+    let error = match fs::read_dir(dir) {
+        Ok(_) => return None,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+        Err(e) => e,
+    };
+    Some(Finding {
+        severity: Severity::Error,
+        problem: format!(
+            "Can't read the application directory {}: {}",
+            dir.display(),
+            error
+        ),
+        fix: format!(
+            "Make it readable (e.g. `chmod a+rx {}`) or remove it from XDG_DATA_DIRS",
+            dir.display()
+        ),
+    })
+}
+
+/// Defaults naming a desktop file that doesn't exist anywhere, which file
+/// managers silently skip
+fn missing_defaults(config: &MimeAppsConfig, registry: &AppRegistry) -> Vec<Finding> {
+    let user_path = MimeAppsConfig::user_config_path().ok();
+    let mut defaults: Vec<(String, String)> = config.effective_defaults().into_iter().collect();
+    defaults.sort();
+
+    defaults
+        .into_iter()
+        .filter(|(_, id)| registry.get_app(id).is_none() && find_desktop_file(id).is_none())
+        .map(|(mime, id)| {
+            let source = config.default_source(&mime);
+            let fix = match source {
+                Some(path) if Some(path) != user_path.as_deref() => format!(
+                    "Delete the {} line from {}, or choose another default to override it",
+                    mime,
+                    path.display()
+                ),
+                _ => format!(
+                    "Choose another default in xdg-chooser, or clear it with \
+                     `xdg-chooser unset {}`",
+                    mime
+                ),
+            };
+            let problem = match source {
+                Some(path) => format!(
+                    "The default for {} is {}, which is not installed (set in {})",
+                    mime,
+                    id,
+                    path.display()
+                ),
+                None => format!("The default for {} is {}, which is not installed", mime, id),
+            };
+            Finding {
+                severity: Severity::Error,
+                problem,
+                fix,
+            }
+        })
+        .collect()
+}
+
+/// Defaults the user's file sets that a desktop-specific or other higher
+/// priority file overrides with a different app
+fn overridden_defaults(config: &MimeAppsConfig) -> Vec<Finding> {
+    let mut mimes: Vec<String> = config.effective_defaults().into_keys().collect();
+    mimes.sort();
+
+    mimes
+        .into_iter()
+        .filter_map(|mime| {
+            let found = config.overriding_default(&mime)?;
+            Some(Finding {
+                severity: Severity::Warning,
+                problem: format!(
+                    "Your default for {} has no effect: {} sets {} instead",
+                    mime,
+                    found.path.display(),
+                    found.apps.join(", ")
+                ),
+                fix: format!(
+                    "Delete the {} line from {}, or set the default again in \
+                     xdg-chooser and let it remove the override",
+                    mime,
+                    found.path.display()
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Primary types of the shown categories that nothing opens, not even
+/// through a type they are a subclass of
+fn unhandled_types(config: &MimeAppsConfig, registry: &AppRegistry) -> Vec<Finding> {
+    let settings = &config.settings;
+    settings
+        .ordered_categories(registry.visible_categories(settings))
+        .into_iter()
+        .filter(|category| !settings.is_category_hidden(category))
+        .filter_map(|category| {
+            let unhandled: Vec<String> = settings
+                .primary_mime_types(&category)
+                .into_iter()
+                .filter(|mime| find_handler(config, registry, mime).is_none())
+                .collect();
+            if unhandled.is_empty() {
+                return None;
+            }
+            Some(Finding {
+                severity: Severity::Warning,
+                problem: format!(
+                    "{}: no application opens {}",
+                    category.display_name(),
+                    unhandled.join(", ")
+                ),
+                fix: format!(
+                    "Install one, or offer an installed one with \
+                     `xdg-chooser add-association {} <app>.desktop`",
+                    unhandled[0]
+                ),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnose() {
+        let registry = AppRegistry::demo();
+        let mut config = MimeAppsConfig::demo();
+
+        let findings = diagnose(&config, &registry, &[]);
+        assert!(findings
+            .iter()
+            .all(|finding| finding.severity == Severity::Warning));
+
+        config
+            .set_default("text/x-doctor-test", "uninstalled-editor.desktop")
+            .unwrap();
+        let findings = diagnose(&config, &registry, &[]);
+        let first = &findings[0];
+        assert_eq!(first.severity, Severity::Error);
+        assert!(first.problem.contains("uninstalled-editor.desktop"));
+        assert!(first.fix.contains("`xdg-chooser unset text/x-doctor-test`"));
+    }
+}
@@ -0,0 +1,38 @@
+use std::fs;
+
+use anyhow::{bail, Context, Result};
+
+use crate::config::diagnostics::{check_document, Severity};
+use crate::config::document::MimeAppsDocument;
+
+use super::LintArgs;
+
+/// Check a mimeapps.list file and report problems, failing if any errors
+/// (or, with `--strict`, warnings) were found. The JSON output is an array
+/// of them, empty when there are none.
+pub fn lint(args: LintArgs) -> Result<()> {
+    let content = fs::read_to_string(&args.file)
+        .with_context(|| format!("Failed to read {}", args.file.display()))?;
+    let doc = MimeAppsDocument::parse(&content);
+
+    let diagnostics = check_document(&doc, &args.file, !args.no_installed_check);
+    if args.output.is_json() {
+        println!("{}", serde_json::to_string_pretty(&diagnostics)?);
+    } else {
+        for diagnostic in &diagnostics {
+            println!("{}", diagnostic);
+        }
+    }
+
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+
+    if errors > 0 || (args.strict && warnings > 0) {
+        bail!("{} error(s), {} warning(s)", errors, warnings);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::config::MimeAppsConfig;
+use crate::desktop::discovery::AppRegistry;
+
+use super::ListMimesArgs;
+
+/// What `list-mimes` prints about an application
+#[derive(Debug, Serialize)]
+pub struct AppMimeTypes<'a> {
+    pub id: String,
+    /// `None` when it isn't installed
+    pub name: Option<&'a str>,
+    /// Types it is the default for in the merged configuration
+    pub default_for: Vec<String>,
+    /// Types its desktop file declares, wildcards such as `image/*`
+    /// included
+    pub declares: Vec<&'a str>,
+}
+
+/// Print the MIME types an application is the default for and those it
+/// declares, each sorted
+pub fn list_mimes(args: ListMimesArgs) -> Result<()> {
+    let config = MimeAppsConfig::load()?;
+    let registry = AppRegistry::with_settings(&config.settings);
+    let app_id = if args.app_id.ends_with(".desktop") {
+        args.app_id
+    } else {
+        format!("{}.desktop", args.app_id)
+    };
+    let mimes = app_mime_types(&config, &registry, &app_id);
+
+    if args.output.is_json() {
+        println!("{}", serde_json::to_string_pretty(&mimes)?);
+        return Ok(());
+    }
+
+    if mimes.name.is_none() {
+        eprintln!("{} is not installed", mimes.id);
+    }
+    println!("Default for:");
+    print_list(&mimes.default_for);
+    println!("Declares:");
+    print_list(&mimes.declares);
+
+    Ok(())
+}
+
+fn print_list(mimes: &[impl AsRef<str>]) {
+    if mimes.is_empty() {
+        println!("  (none)");
+    }
+    for mime in mimes {
+        println!("  {}", mime.as_ref());
+    }
+}
+
+/// The types `app_id` opens by default and those it declares, from the
+/// merged configuration and the installed desktop files
+pub fn app_mime_types<'a>(
+    config: &MimeAppsConfig,
+    registry: &'a AppRegistry,
+    app_id: &str,
+) -> AppMimeTypes<'a> {
+    let mut default_for: Vec<String> = config
+        .effective_defaults()
+        .into_iter()
+        .filter(|(_, id)| id == app_id)
+        .map(|(mime, _)| mime)
+        .collect();
+    default_for.sort();
+
+    let app = registry.get_app(app_id);
+    let mut declares: Vec<&str> = app
+        .map(|app| app.mime_types.iter().map(|mime| &**mime).collect())
+        .unwrap_or_default();
+    declares.sort_unstable();
+    declares.dedup();
+
+    AppMimeTypes {
+        id: app_id.to_string(),
+        name: app.map(|app| app.name.as_str()),
+        default_for,
+        declares,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_mime_types() {
+        let registry = AppRegistry::demo();
+        let config = MimeAppsConfig::demo();
+
+        let firefox = app_mime_types(&config, &registry, "firefox.desktop");
+        assert_eq!(firefox.name, Some("Firefox"));
+        assert_eq!(
+            firefox.default_for,
+            vec![
+                "text/html",
+                "x-scheme-handler/http",
+                "x-scheme-handler/https"
+            ]
+        );
+        assert!(firefox.declares.contains(&"application/xhtml+xml"));
+
+        let chromium = app_mime_types(&config, &registry, "chromium.desktop");
+        assert!(chromium.default_for.is_empty());
+        assert!(chromium.declares.contains(&"application/pdf"));
+
+        let missing = app_mime_types(&config, &registry, "missing.desktop");
+        assert_eq!(missing.name, None);
+        assert!(missing.declares.is_empty());
+    }
+}
@@ -0,0 +1,572 @@
+mod batch;
+mod commands;
+mod completions;
+mod doctor;
+mod lint;
+mod list_mimes;
+pub mod open;
+mod status;
+mod watch;
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+use gtk::glib;
+
+use crate::config::settings::Settings;
+use crate::desktop::categories::AppCategory;
+
+/// Desktop-agnostic default application chooser for Linux.
+///
+/// Run without a subcommand to open the graphical chooser.
+#[derive(Debug, Parser)]
+#[command(name = "xdg-chooser", version, about)]
+pub struct Cli {
+    /// Open read-only: show the current associations without allowing
+    /// changes or test launches
+    #[arg(long)]
+    pub locked: bool,
+
+    /// Show made-up applications and defaults instead of the real ones;
+    /// nothing is read from or written to disk
+    #[arg(long)]
+    pub demo: bool,
+
+    /// Open at a category's page (e.g. web-browser). If the chooser is
+    /// already running, its window switches to the page instead.
+    #[arg(long, value_parser = parse_category, conflicts_with_all = ["mime", "file"])]
+    pub category: Option<AppCategory>,
+
+    /// Open at the page of the category that handles a MIME type
+    /// (e.g. application/pdf)
+    #[arg(long, conflicts_with = "file")]
+    pub mime: Option<String>,
+
+    /// Open at the page of the category that handles a file's type
+    #[arg(long, value_name = "PATH")]
+    pub file: Option<PathBuf>,
+
+    /// Show a compact "Open With" dialog for a file or URI: pick an
+    /// application to open it with, optionally keeping it as the default
+    /// for the type
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["category", "mime", "file", "tui"]
+    )]
+    pub choose: Option<PathBuf>,
+
+    /// Use the interface in the terminal instead of opening a window, for
+    /// servers and TTY sessions
+    #[arg(long)]
+    pub tui: bool,
+
+    /// Start without a window and wait for D-Bus activation (used by the
+    /// D-Bus service file; GApplication handles the flag itself)
+    #[arg(long, hide = true, conflicts_with = "tui")]
+    pub gapplication_service: bool,
+
+    /// Log more detail: -v for debug messages, -vv for trace
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    pub verbose: u8,
+
+    /// Also write the log to this file, rotated daily (e.g.
+    /// ~/xdg-chooser.log becomes ~/xdg-chooser.2024-05-01.log)
+    #[arg(long, value_name = "PATH", global = true)]
+    pub log_file: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Remove the user default for a MIME type or a whole category
+    Unset(UnsetArgs),
+    /// Keep the current default of a MIME type or category, restoring it
+    /// when another program changes it
+    Pin(PinArgs),
+    /// Stop restoring the default of a MIME type or category
+    Unpin(PinArgs),
+    /// Add an application to the "Open With" list for a MIME type
+    AddAssociation(AssociationArgs),
+    /// Block an application from being offered for a MIME type
+    RemoveAssociation(AssociationArgs),
+    /// List every application offered for a MIME type, in resolution order
+    Handlers(HandlersArgs),
+    /// List the MIME types an application is the default for and those it
+    /// declares
+    ListMimes(ListMimesArgs),
+    /// Exit with status 0 if an application is what opens a MIME type, or
+    /// every primary type of a category, 1 otherwise and 2 on errors
+    Check(CheckArgs),
+    /// Open a file or URI with its default application, like xdg-open
+    Open(OpenArgs),
+    /// List URL scheme handlers that are installed for you alone, newly
+    /// added or run a shell, which links could be used to exploit
+    ReviewSchemes(ReviewSchemesArgs),
+    /// List applications installed both natively and as a Flatpak, and
+    /// optionally switch every association to one of the two
+    Duplicates(DuplicatesArgs),
+    /// Show which handlers Flatpak apps open files and links with through
+    /// the portal, and where that differs from the default
+    FlatpakHandlers(FlatpakHandlersArgs),
+    /// Search installed applications by name, generic name or description
+    Search(SearchArgs),
+    /// Check a mimeapps.list file for syntax errors and broken references
+    Lint(LintArgs),
+    /// Look for defaults naming missing applications, types nothing opens,
+    /// malformed mimeapps.list lines, overridden defaults and unreadable
+    /// application directories, with how to fix each
+    Doctor(DoctorArgs),
+    /// Print every category with its default application and how many
+    /// installed applications could serve it
+    Status(StatusArgs),
+    /// Generate manual pages from the command-line definitions
+    GenerateMan(GenerateManArgs),
+    /// Print a completion script for a shell, which also completes MIME
+    /// types, desktop file IDs and categories known on this machine
+    Completions(CompletionsArgs),
+    /// List the values the word at INDEX of a command line can take, for
+    /// the completion scripts
+    #[command(hide = true)]
+    CompleteValues(CompleteValuesArgs),
+    /// Apply a list of operations read from stdin in a single save
+    Batch(BatchArgs),
+    /// Print default application changes in any mimeapps.list as they happen
+    Watch(WatchArgs),
+    /// List the presets from presets.d directories
+    Presets(PresetsArgs),
+    /// Set the defaults recommended by a preset
+    ApplyPreset(ApplyPresetArgs),
+    /// Print every category's defaults, where they come from and any
+    /// problems, as Markdown, JSON or HTML
+    Report(ReportArgs),
+    /// Print the associations in your mimeapps.list for configuration
+    /// management tools
+    Export(ExportArgs),
+    /// Set defaults and associations from a JSON export, or from the rules
+    /// of another handler manager
+    Import(ImportArgs),
+    /// Write your defaults as the vendor mimeapps.list of a data directory,
+    /// for distributions and image builds
+    InstallVendor(InstallVendorArgs),
+}
+
+/// The `--format` of the subcommands that list or report something
+#[derive(Debug, Args)]
+pub struct OutputArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+impl OutputArgs {
+    pub fn is_json(&self) -> bool {
+        self.format == OutputFormat::Json
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Lines for people to read
+    Text,
+    /// JSON whose fields are kept stable for other tools
+    Json,
+}
+
+#[derive(Debug, Args)]
+pub struct UnsetArgs {
+    /// MIME type or URL scheme handler to clear (e.g. text/plain)
+    #[arg(required_unless_present = "category", conflicts_with = "category")]
+    pub mime: Option<String>,
+
+    /// Clear every primary MIME type of a category (e.g. web-browser)
+    #[arg(long, value_parser = parse_category)]
+    pub category: Option<AppCategory>,
+
+    /// Also drop the applications the default named from your Added
+    /// Associations of the type, without blocking them
+    #[arg(long)]
+    pub unassociate: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct PinArgs {
+    /// MIME type or URL scheme handler (e.g. x-scheme-handler/https)
+    #[arg(required_unless_present = "category", conflicts_with = "category")]
+    pub mime: Option<String>,
+
+    /// Every primary MIME type of a category (e.g. web-browser)
+    #[arg(long, value_parser = parse_category)]
+    pub category: Option<AppCategory>,
+}
+
+#[derive(Debug, Args)]
+pub struct AssociationArgs {
+    /// MIME type or URL scheme handler (e.g. image/png)
+    pub mime: String,
+
+    /// Desktop file ID of the application (e.g. org.gimp.GIMP.desktop)
+    pub app_id: String,
+}
+
+#[derive(Debug, Args)]
+pub struct HandlersArgs {
+    /// MIME type or URL scheme handler (e.g. text/plain)
+    pub mime: String,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct ListMimesArgs {
+    /// Desktop file ID of the application, the `.desktop` suffix being
+    /// optional (e.g. org.gnome.Loupe)
+    pub app_id: String,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct CheckArgs {
+    /// Category (e.g. web-browser), or MIME type or URL scheme handler
+    /// (e.g. x-scheme-handler/https)
+    pub target: String,
+
+    /// Desktop file ID of the application, the `.desktop` suffix being
+    /// optional (e.g. firefox)
+    pub app_id: String,
+}
+
+#[derive(Debug, Args)]
+pub struct OpenArgs {
+    /// File path or URI to open (e.g. report.pdf, https://example.org/)
+    pub target: String,
+
+    /// Print the MIME type, chosen application and command instead of
+    /// launching it
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct SearchArgs {
+    /// Text to look for (case-insensitive)
+    pub query: String,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct DuplicatesArgs {
+    /// Rewrite every default and association naming the other copy of
+    /// each duplicate to this one
+    #[arg(long, value_enum, conflicts_with = "format")]
+    pub prefer: Option<PreferredCopy>,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PreferredCopy {
+    /// The distribution's package
+    Native,
+    /// The Flatpak
+    Flatpak,
+}
+
+#[derive(Debug, Args)]
+pub struct FlatpakHandlersArgs {
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct DoctorArgs {
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct StatusArgs {
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct ReviewSchemesArgs {
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct ReportArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = ReportFormat::Markdown)]
+    pub format: ReportFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    /// A Markdown document with a table per category
+    Markdown,
+    /// The same data as JSON, for scripts
+    Json,
+    /// A standalone HTML page with the installed applications of each
+    /// category and their icons
+    Html,
+}
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = ExportFormat::Ini)]
+    pub format: ExportFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// A mimeapps.list fragment
+    Ini,
+    /// Ansible tasks using community.general.ini_file, one per entry
+    Ansible,
+    /// Every default and association as JSON, which `import` reads back on
+    /// another machine
+    Json,
+}
+
+#[derive(Debug, Args)]
+pub struct InstallVendorArgs {
+    /// Data directory to write applications/mimeapps.list under; point it
+    /// into an image's root (e.g. rootfs/usr/share) when staging a build
+    #[arg(long, value_name = "DIR", default_value = "/usr/share")]
+    pub data_dir: PathBuf,
+
+    /// Print the file instead of writing it
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ImportArgs {
+    /// Tool whose configuration to read
+    #[arg(long, value_enum, default_value_t = ImportSource::Json)]
+    pub from: ImportSource,
+
+    /// File to read: the JSON export, or another tool's configuration
+    /// instead of its usual location (~/.config/handlr/handlr.toml or
+    /// ~/.config/mimeo/associations.txt)
+    #[arg(required_if_eq("from", "json"))]
+    pub file: Option<PathBuf>,
+
+    /// Print the defaults that would change without saving
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImportSource {
+    /// A file written by `export --format json`
+    Json,
+    /// handlr's regex handlers
+    Handlr,
+    /// mimeo's associations file
+    Mimeo,
+}
+
+#[derive(Debug, Args)]
+pub struct LintArgs {
+    /// Path of the mimeapps.list file to check
+    pub file: PathBuf,
+
+    /// Treat warnings as errors
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Don't report applications that are not installed on this machine
+    #[arg(long)]
+    pub no_installed_check: bool,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct GenerateManArgs {
+    /// Write a page per subcommand into this directory instead of printing
+    /// the main page to stdout
+    #[arg(long)]
+    pub output_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct CompletionsArgs {
+    /// Shell to print the script for
+    #[arg(value_enum)]
+    pub shell: CompletionShell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+#[derive(Debug, Args)]
+pub struct CompleteValuesArgs {
+    /// Position of the word being completed in WORDS
+    pub index: usize,
+
+    /// The command line, starting with the program
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    pub words: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct BatchArgs {
+    /// Validate and print the operations without saving
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct WatchArgs {
+    /// Also put pinned defaults back when something changes them
+    #[arg(long)]
+    pub guard: bool,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct PresetsArgs {
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct ApplyPresetArgs {
+    /// Preset ID, the file name without .toml (see `presets`)
+    pub preset: String,
+
+    /// Print the defaults that would change without saving
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Parse a category identifier, listing the valid ones on error
+fn parse_category(id: &str) -> Result<AppCategory, String> {
+    // User-defined categories are registered when the settings load
+    let found = AppCategory::from_id(id).or_else(|| {
+        Settings::load().ok()?;
+        AppCategory::from_id(id)
+    });
+    found.ok_or_else(|| {
+        let all = AppCategory::all();
+        let valid: Vec<&str> = all.iter().map(|c| c.id()).collect();
+        format!(
+            "unknown category '{}' (expected one of: {})",
+            id,
+            valid.join(", ")
+        )
+    })
+}
+
+/// Exit status of `check` when it fails, to tell errors from a mismatch
+const CHECK_ERROR: glib::ExitCode = glib::ExitCode::new(2);
+
+/// Run a command-line subcommand, returning the status to exit with
+pub fn run(command: Command) -> Result<glib::ExitCode> {
+    match command {
+        Command::Unset(args) => commands::unset(args),
+        Command::Pin(args) => commands::pin(args, true),
+        Command::Unpin(args) => commands::pin(args, false),
+        Command::AddAssociation(args) => commands::add_association(args),
+        Command::RemoveAssociation(args) => commands::remove_association(args),
+        Command::Handlers(args) => commands::handlers(args),
+        Command::ListMimes(args) => list_mimes::list_mimes(args),
+        Command::Check(args) => {
+            // Only the status matters, so no message goes with a mismatch
+            return Ok(match commands::check(args) {
+                Ok(true) => glib::ExitCode::SUCCESS,
+                Ok(false) => glib::ExitCode::FAILURE,
+                Err(e) => {
+                    eprintln!("error: {:#}", e);
+                    CHECK_ERROR
+                }
+            });
+        }
+        Command::Open(args) => open::open(args),
+        Command::ReviewSchemes(args) => commands::review_schemes(args),
+        Command::Duplicates(args) => commands::duplicates(args),
+        Command::FlatpakHandlers(args) => commands::flatpak_handlers(args),
+        Command::Search(args) => commands::search(args),
+        Command::Lint(args) => lint::lint(args),
+        Command::Doctor(args) => doctor::doctor(args),
+        Command::Status(args) => status::status(args),
+        Command::GenerateMan(args) => commands::generate_man(args),
+        Command::Completions(args) => completions::completions(args),
+        Command::CompleteValues(args) => {
+            // Nothing to list tells the script to use clap's completions
+            if !completions::complete_values(args)? {
+                return Ok(glib::ExitCode::FAILURE);
+            }
+            Ok(())
+        }
+        Command::Batch(args) => batch::batch(args),
+        Command::Watch(args) => watch::watch(args),
+        Command::Presets(args) => commands::presets(args),
+        Command::ApplyPreset(args) => commands::apply_preset(args),
+        Command::Report(args) => commands::report(args),
+        Command::Export(args) => commands::export(args),
+        Command::Import(args) => commands::import(args),
+        Command::InstallVendor(args) => commands::install_vendor(args),
+    }?;
+    Ok(glib::ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_output_format() {
+        let is_json = |line: &str| match Cli::try_parse_from(line.split_whitespace()) {
+            Ok(Cli {
+                command: Some(Command::Search(args)),
+                ..
+            }) => Ok(args.output.is_json()),
+            Ok(_) => unreachable!(),
+            Err(e) => Err(e.kind()),
+        };
+        assert_eq!(is_json("xdg-chooser search foo"), Ok(false));
+        assert_eq!(is_json("xdg-chooser search --format json foo"), Ok(true));
+        assert_eq!(
+            is_json("xdg-chooser search --format yaml foo"),
+            Err(clap::error::ErrorKind::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_man_page_renders() {
+        let mut page = Vec::new();
+        clap_mangen::Man::new(Cli::command())
+            .render(&mut page)
+            .unwrap();
+        assert!(String::from_utf8(page).unwrap().contains("generate\\-man"));
+    }
+}
@@ -0,0 +1,157 @@
+use anyhow::{bail, Context, Result};
+use gio::prelude::*;
+
+use crate::config::MimeAppsConfig;
+use crate::desktop::discovery::AppRegistry;
+use crate::desktop::entry::AppEntry;
+use crate::desktop::mime_db::MimeDatabase;
+use crate::utils::exec::{launch_app_with_file, test_command};
+use crate::utils::mime::file_mime_type;
+
+use super::OpenArgs;
+
+/// Type whose default opens files no other handler claims
+const FALLBACK_MIME: &str = "application/octet-stream";
+
+/// Open a file or URI with the application the merged configuration picks,
+/// like xdg-open but with the same resolution the chooser shows
+pub fn open(args: OpenArgs) -> Result<()> {
+    let config = MimeAppsConfig::load()?;
+    let registry = AppRegistry::with_settings(&config.settings);
+
+    let (target, mime) = identify(&args.target)?;
+    let Some((handled, app)) = find_handler(&config, &registry, &mime) else {
+        bail!("No application opens {} ({})", args.target, mime);
+    };
+
+    if handled != mime {
+        tracing::debug!("Using the handler for {}, a parent of {}", handled, mime);
+    }
+
+    if args.dry_run {
+        let argv = test_command(app, Some(&target))?;
+        println!("{}\t{}\t{}", mime, app.id, shell_words::join(&argv));
+        return Ok(());
+    }
+
+    launch_app_with_file(app, &target, &config.settings.launch)
+}
+
+/// The argument to pass to the handler and the MIME type to look one up
+/// for: `x-scheme-handler/<scheme>` for URIs, the detected content type for
+/// local files (given as paths or `file://` URIs)
+fn identify(target: &str) -> Result<(String, String)> {
+    let file = gio::File::for_commandline_arg(target);
+    let mime = file_mime_type(&file)?;
+
+    if !file.has_uri_scheme("file") {
+        return Ok((target.to_string(), mime));
+    }
+    let path = file.path().context("File has no local path")?;
+    Ok((path.to_string_lossy().into_owned(), mime))
+}
+
+/// Every installed application that can open a MIME type, in the order
+/// [`find_handler`] tries them: for the type and then each type it is a
+/// subclass of, associations (the default first) and then apps declaring
+/// it, skipping removed associations. The default for unknown files comes
+/// last.
+pub fn candidates<'a>(
+    config: &MimeAppsConfig,
+    registry: &'a AppRegistry,
+    mime: &str,
+) -> Vec<&'a AppEntry> {
+    matching_candidates(config, registry, mime)
+        .into_iter()
+        .map(|(_, app)| app)
+        .collect()
+}
+
+/// The first installed application for a MIME type or, failing that, for the
+/// nearest type it is a subclass of: the first of its [`candidates`].
+/// Returns the type that matched with the app.
+pub fn find_handler<'a>(
+    config: &MimeAppsConfig,
+    registry: &'a AppRegistry,
+    mime: &str,
+) -> Option<(String, &'a AppEntry)> {
+    matching_candidates(config, registry, mime)
+        .into_iter()
+        .next()
+}
+
+/// [`candidates`], each with the type it was found for
+fn matching_candidates<'a>(
+    config: &MimeAppsConfig,
+    registry: &'a AppRegistry,
+    mime: &str,
+) -> Vec<(String, &'a AppEntry)> {
+    let mut candidates: Vec<(String, &AppEntry)> = Vec::new();
+    let mut push = |mime: &str, app: &'a AppEntry| {
+        if !candidates
+            .iter()
+            .any(|(_, candidate)| candidate.id == app.id)
+        {
+            candidates.push((mime.to_string(), app));
+        }
+    };
+
+    for mime in MimeDatabase::get().lineage(mime) {
+        let associated = config
+            .get_associations(&mime)
+            .into_iter()
+            .filter_map(|id| registry.get_app(id));
+        let installed = registry
+            .apps_for_mime(&mime)
+            .into_iter()
+            .filter(|app| !config.is_removed(&mime, &app.id));
+
+        for app in associated.chain(installed) {
+            push(&mime, app);
+        }
+    }
+
+    let fallback = config
+        .get_default(FALLBACK_MIME)
+        .and_then(|id| registry.get_app(id));
+    if let Some(app) = fallback {
+        push(FALLBACK_MIME, app);
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidates() {
+        let registry = AppRegistry::demo();
+        let config = MimeAppsConfig::demo();
+
+        let ids: Vec<&str> = candidates(&config, &registry, "application/pdf")
+            .into_iter()
+            .map(|app| &*app.id)
+            .collect();
+        // The default first, then the added association, without repeats
+        assert_eq!(
+            &ids[..2],
+            ["org.gnome.Evince.desktop", "org.kde.okular.desktop"]
+        );
+        assert_eq!(
+            ids.iter()
+                .filter(|id| **id == "org.gnome.Evince.desktop")
+                .count(),
+            1
+        );
+        // Removed from the type
+        assert!(!ids.contains(&"chromium.desktop"));
+        assert_eq!(
+            ids[0],
+            &*find_handler(&config, &registry, "application/pdf")
+                .unwrap()
+                .1
+                .id
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::config::MimeAppsConfig;
+use crate::desktop::categories::AppCategory;
+use crate::desktop::discovery::AppRegistry;
+use crate::desktop::entry::AppEntry;
+
+use super::StatusArgs;
+
+/// A category's line of `status`, and what `status --format json` prints for it
+#[derive(Debug, Serialize)]
+pub struct CategoryStatus<'a> {
+    pub category: AppCategory,
+    pub name: String,
+    /// Desktop file ID of the default, `None` when no installed
+    /// application is the default of any of its types
+    pub default: Option<&'a str>,
+    pub default_name: Option<&'a str>,
+    /// Installed applications offered for the category
+    pub apps: usize,
+}
+
+/// Print every category with its default and how many applications could
+/// serve it, as an aligned table
+pub fn status(args: StatusArgs) -> Result<()> {
+    let config = MimeAppsConfig::load()?;
+    let registry = AppRegistry::with_settings(&config.settings);
+    let statuses = category_statuses(&config, &registry);
+
+    if args.output.is_json() {
+        println!("{}", serde_json::to_string_pretty(&statuses)?);
+        return Ok(());
+    }
+
+    let rows: Vec<[String; 3]> = statuses
+        .iter()
+        .map(|status| {
+            let default = match (status.default, status.default_name) {
+                (Some(id), Some(name)) => format!("{} ({})", name, id),
+                _ => "none".to_string(),
+            };
+            [status.name.clone(), default, status.apps.to_string()]
+        })
+        .collect();
+    let header = ["CATEGORY", "DEFAULT", "APPS"].map(String::from);
+    let width = |column: usize| {
+        rows.iter()
+            .chain([&header])
+            .map(|row| row[column].chars().count())
+            .max()
+            .unwrap_or(0)
+    };
+    let (first, second) = (width(0), width(1));
+    for [category, default, apps] in [&header].into_iter().chain(&rows) {
+        println!("{:first$}  {:second$}  {}", category, default, apps);
+    }
+
+    Ok(())
+}
+
+/// Every category in the sidebar's order, hidden ones included, with the
+/// default the window shows for it: that of its first primary type whose
+/// default is installed
+pub fn category_statuses<'a>(
+    config: &'a MimeAppsConfig,
+    registry: &'a AppRegistry,
+) -> Vec<CategoryStatus<'a>> {
+    let settings = &config.settings;
+    settings
+        .ordered_categories(AppCategory::all())
+        .into_iter()
+        .map(|category| {
+            let primary = settings.primary_mime_types(&category);
+            let default: Option<&AppEntry> = primary
+                .iter()
+                .filter_map(|mime| config.get_default(mime))
+                .find_map(|id| registry.get_app(id));
+            // Like the window, without the apps removed from all its types
+            let apps = registry
+                .apps_for_app_category(&category, settings)
+                .into_iter()
+                .filter(|app| {
+                    primary.is_empty() || !primary.iter().all(|m| config.is_removed(m, &app.id))
+                })
+                .count();
+            CategoryStatus {
+                name: category.display_name().to_string(),
+                category,
+                default: default.map(|app| &*app.id),
+                default_name: default.map(|app| app.name.as_str()),
+                apps,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_category_statuses() {
+        let registry = AppRegistry::demo();
+        let config = MimeAppsConfig::demo();
+
+        let statuses = category_statuses(&config, &registry);
+        assert_eq!(statuses.len(), AppCategory::all().len());
+        let status = |category: AppCategory| {
+            statuses
+                .iter()
+                .find(|status| status.category == category)
+                .unwrap()
+        };
+
+        let browsers = status(AppCategory::WebBrowser);
+        assert_eq!(browsers.default, Some("firefox.desktop"));
+        assert_eq!(browsers.default_name, Some("Firefox"));
+        assert_eq!(browsers.apps, 2);
+        assert_eq!(status(AppCategory::Calculator).default, None);
+    }
+}
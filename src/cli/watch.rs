@@ -0,0 +1,116 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::config::changes::{diff_defaults, DefaultChange};
+use crate::config::MimeAppsConfig;
+use crate::utils::watch::LayerWatcher;
+
+use super::WatchArgs;
+
+/// A default change in one file, as printed with `--format json`
+#[derive(Serialize)]
+struct WatchEvent<'a> {
+    file: &'a Path,
+    #[serde(flatten)]
+    change: &'a DefaultChange,
+    /// Default that is now in effect after merging all layers
+    effective: Option<&'a str>,
+}
+
+/// Monitor every mimeapps.list layer and print default changes as they happen
+pub fn watch(args: WatchArgs) -> Result<()> {
+    let paths = MimeAppsConfig::layer_paths()?;
+
+    let snapshots: RefCell<HashMap<PathBuf, HashMap<String, String>>> = RefCell::new(
+        paths
+            .iter()
+            .map(|path| (path.clone(), MimeAppsConfig::read_file_defaults(path)))
+            .collect(),
+    );
+
+    eprintln!(
+        "Watching {} mimeapps.list locations, press Ctrl+C to stop",
+        paths.len()
+    );
+
+    if args.guard {
+        guard_pinned();
+    }
+
+    let _watcher = LayerWatcher::new(&paths, move |path| {
+        if args.guard {
+            guard_pinned();
+        }
+
+        let current = MimeAppsConfig::read_file_defaults(path);
+
+        let mut snapshots = snapshots.borrow_mut();
+        let previous = snapshots.entry(path.to_path_buf()).or_default();
+        let changes = diff_defaults(previous, &current);
+        *previous = current;
+
+        if changes.is_empty() {
+            return;
+        }
+
+        let config = MimeAppsConfig::load().ok();
+
+        for change in &changes {
+            let effective = config.as_ref().and_then(|c| c.get_default(&change.mime));
+
+            if args.output.is_json() {
+                let event = WatchEvent {
+                    file: path,
+                    change,
+                    effective,
+                };
+                match serde_json::to_string(&event) {
+                    Ok(line) => println!("{}", line),
+                    Err(e) => tracing::error!("Failed to serialize event: {}", e),
+                }
+            } else {
+                println!(
+                    "{}: {}: {} -> {} (effective: {})",
+                    path.display(),
+                    change.mime,
+                    change.old.as_deref().unwrap_or("(none)"),
+                    change.new.as_deref().unwrap_or("(none)"),
+                    effective.unwrap_or("(none)")
+                );
+            }
+        }
+    });
+
+    glib::MainLoop::new(None, false).run();
+
+    Ok(())
+}
+
+/// Restore pinned defaults that were changed and report each one. The
+/// restore itself changes the user's file, which is reported as usual.
+fn guard_pinned() {
+    let restored = MimeAppsConfig::load().and_then(|mut config| {
+        let restored = config.restore_pinned()?;
+        if !restored.is_empty() {
+            config.save()?;
+        }
+        Ok(restored)
+    });
+    match restored {
+        Ok(restored) => {
+            for change in restored {
+                eprintln!(
+                    "Restored pinned default for {}: {} -> {}",
+                    change.mime,
+                    change.old.as_deref().unwrap_or("(none)"),
+                    change.new.as_deref().unwrap_or("(none)")
+                );
+            }
+        }
+        Err(e) => tracing::error!("Failed to restore pinned defaults: {:#}", e),
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use serde::{Deserialize, Serialize};
+
+use super::document::{ADDED_ASSOCIATIONS, DEFAULT_APPLICATIONS, REMOVED_ASSOCIATIONS};
+use super::MimeAppsConfig;
+
+/// Where the exported Ansible tasks write, on the managed machine
+const ANSIBLE_PATH: &str = "~/.config/mimeapps.list";
+
+/// Version of the JSON export, raised when its layout changes
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Every default and association the user's mimeapps.list sets, as
+/// `export --format json` prints it for `import --from json` on another
+/// machine. Each section maps MIME types to their applications.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedDefaults {
+    pub version: u32,
+    #[serde(default)]
+    pub defaults: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub added_associations: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub removed_associations: BTreeMap<String, Vec<String>>,
+}
+
+impl ExportedDefaults {
+    pub fn new(config: &MimeAppsConfig) -> Self {
+        let section = |name: &str| -> BTreeMap<String, Vec<String>> {
+            config
+                .user_document()
+                .entries(name)
+                .filter(|(_, apps)| !apps.is_empty())
+                .map(|(mime, apps)| (mime.to_string(), apps.to_vec()))
+                .collect()
+        };
+        Self {
+            version: EXPORT_VERSION,
+            defaults: section(DEFAULT_APPLICATIONS),
+            added_associations: section(ADDED_ASSOCIATIONS),
+            removed_associations: section(REMOVED_ASSOCIATIONS),
+        }
+    }
+}
+
+/// The entries of the user's own mimeapps.list, per section and sorted by
+/// MIME type so exports diff cleanly. Later duplicates of a key win, and
+/// keys without applications are left out.
+fn user_entries(config: &MimeAppsConfig) -> Vec<(&'static str, BTreeMap<String, String>)> {
+    [
+        DEFAULT_APPLICATIONS,
+        ADDED_ASSOCIATIONS,
+        REMOVED_ASSOCIATIONS,
+    ]
+    .into_iter()
+    .map(|section| {
+        let entries = config
+            .user_document()
+            .entries(section)
+            .filter(|(_, apps)| !apps.is_empty())
+            .map(|(mime, apps)| (mime.to_string(), format!("{};", apps.join(";"))))
+            .collect();
+        (section, entries)
+    })
+    .filter(|(_, entries): &(_, BTreeMap<_, _>)| !entries.is_empty())
+    .collect()
+}
+
+/// The user's associations as a mimeapps.list fragment, for configuration
+/// management tools that manage whole files (such as a Puppet `file`
+/// resource)
+pub fn to_ini(config: &MimeAppsConfig) -> String {
+    let mut out = String::new();
+    for (section, entries) in user_entries(config) {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        let _ = writeln!(out, "[{}]", section);
+        for (mime, apps) in entries {
+            let _ = writeln!(out, "{}={}", mime, apps);
+        }
+    }
+    out
+}
+
+/// The user's defaults and added associations as a vendor mimeapps.list,
+/// for `$XDG_DATA_DIRS/applications/`. Being the lowest priority file, it
+/// only fills in what no user or system configuration sets.
+pub fn to_vendor(config: &MimeAppsConfig) -> String {
+    let mut out = String::from(
+        "# Vendor defaults generated by xdg-chooser install-vendor\n\
+         # Settings in ~/.config and /etc/xdg take precedence over this file\n",
+    );
+    for (section, entries) in user_entries(config) {
+        if section == REMOVED_ASSOCIATIONS {
+            continue;
+        }
+        let _ = writeln!(out, "\n[{}]", section);
+        for (mime, apps) in entries {
+            let _ = writeln!(out, "{}={}", mime, apps);
+        }
+    }
+    out
+}
+
+/// The user's associations as an Ansible task list, one
+/// `community.general.ini_file` task per entry, so other entries on the
+/// managed machines are kept
+pub fn to_ansible(config: &MimeAppsConfig) -> String {
+    let mut out = String::from("# Generated by xdg-chooser export --format ansible\n");
+    for (section, entries) in user_entries(config) {
+        for (mime, apps) in entries {
+            let _ = write!(
+                out,
+                "- name: {}\n  \
+                 community.general.ini_file:\n    \
+                 path: {}\n    \
+                 section: {}\n    \
+                 option: {}\n    \
+                 value: {}\n    \
+                 no_extra_spaces: true\n",
+                yaml_string(&format!("{} in [{}]", mime, section)),
+                yaml_string(ANSIBLE_PATH),
+                yaml_string(section),
+                yaml_string(&mime),
+                yaml_string(&apps),
+            );
+        }
+    }
+    out
+}
+
+/// Quote a string for YAML; JSON strings are valid YAML scalars
+fn yaml_string(value: &str) -> String {
+    serde_json::Value::from(value).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_formats() {
+        let mut config = MimeAppsConfig::demo();
+        config.set_default("text/plain", "code.desktop").unwrap();
+        config.add_association("image/png", "gimp.desktop").unwrap();
+
+        let ini = to_ini(&config);
+        assert!(ini.starts_with("[Default Applications]\n"));
+        assert!(ini.contains("\ntext/plain=code.desktop;\n"));
+        assert!(ini.contains("\n\n[Added Associations]\n"));
+        assert!(ini.contains("gimp.desktop;\n"));
+
+        let ansible = to_ansible(&config);
+        assert!(ansible.contains(
+            "- name: \"text/plain in [Default Applications]\"\n  \
+             community.general.ini_file:\n    \
+             path: \"~/.config/mimeapps.list\"\n    \
+             section: \"Default Applications\"\n    \
+             option: \"text/plain\"\n    \
+             value: \"code.desktop;\"\n"
+        ));
+    }
+
+    #[test]
+    fn test_json_export() {
+        let mut config = MimeAppsConfig::demo();
+        config.set_default("text/plain", "code.desktop").unwrap();
+
+        let exported = ExportedDefaults::new(&config);
+        assert_eq!(exported.version, EXPORT_VERSION);
+        assert_eq!(exported.defaults["text/plain"], vec!["code.desktop"]);
+        assert_eq!(
+            exported.removed_associations["application/pdf"],
+            vec!["chromium.desktop"]
+        );
+
+        let json = serde_json::to_string(&exported).unwrap();
+        let parsed: ExportedDefaults = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, exported);
+    }
+
+    #[test]
+    fn test_vendor_leaves_out_removed_associations() {
+        let mut config = MimeAppsConfig::demo();
+        config.set_default("text/plain", "code.desktop").unwrap();
+        config
+            .remove_association("image/png", "gimp.desktop")
+            .unwrap();
+
+        let vendor = to_vendor(&config);
+        assert!(vendor.starts_with("# Vendor defaults"));
+        assert!(vendor.contains("\n\n[Default Applications]\n"));
+        assert!(vendor.contains("\ntext/plain=code.desktop;\n"));
+        assert!(!vendor.contains("[Removed Associations]"));
+        assert!(to_ini(&config).contains("[Removed Associations]"));
+    }
+}
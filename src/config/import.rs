@@ -0,0 +1,314 @@
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use super::export::{ExportedDefaults, EXPORT_VERSION};
+use super::MimeAppsConfig;
+
+/// A rule from another handler manager: a command and the MIME types it
+/// should be the default for
+#[derive(Debug, PartialEq, Eq)]
+pub struct ImportedRule {
+    pub command: String,
+    pub mime_types: Vec<String>,
+    /// Patterns with no MIME type equivalent, such as regexes matching
+    /// particular sites
+    pub skipped: Vec<String>,
+}
+
+/// handlr's `handlr.toml`; its plain defaults already live in mimeapps.list,
+/// so only the regex handlers are of interest
+#[derive(Debug, Deserialize)]
+struct HandlrConfig {
+    #[serde(default)]
+    handlers: Vec<HandlrHandler>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HandlrHandler {
+    exec: String,
+    #[serde(default)]
+    regexes: Vec<String>,
+}
+
+/// Parse the regex handlers of handlr's `handlr.toml`
+pub fn parse_handlr(content: &str) -> Result<Vec<ImportedRule>> {
+    let config: HandlrConfig = toml::from_str(content).context("Invalid handlr.toml")?;
+    Ok(config
+        .handlers
+        .into_iter()
+        .map(|handler| rule(handler.exec, handler.regexes))
+        .collect())
+}
+
+/// Parse mimeo's `associations.txt`: a command on an unindented line,
+/// followed by indented lines of MIME types or regexes it handles
+pub fn parse_mimeo(content: &str) -> Vec<ImportedRule> {
+    let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+
+    for line in content.lines() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        if line.starts_with(char::is_whitespace) {
+            if let Some((_, patterns)) = groups.last_mut() {
+                patterns.push(line.trim().to_string());
+            }
+        } else {
+            groups.push((line.trim().to_string(), Vec::new()));
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(command, patterns)| rule(command, patterns))
+        .collect()
+}
+
+/// Parse a JSON export written by `export --format json`
+pub fn parse_export(content: &str) -> Result<ExportedDefaults> {
+    let exported: ExportedDefaults =
+        serde_json::from_str(content).context("Invalid xdg-chooser JSON export")?;
+    if exported.version > EXPORT_VERSION {
+        bail!(
+            "The export is version {}, newer than this xdg-chooser reads ({})",
+            exported.version,
+            EXPORT_VERSION
+        );
+    }
+    Ok(exported)
+}
+
+/// What applying an export changed, and what it couldn't apply
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ImportOutcome {
+    /// Tab-separated MIME type, application and what was set for it
+    pub applied: Vec<String>,
+    /// An explanation per entry that was left out
+    pub skipped: Vec<String>,
+}
+
+/// Apply a JSON export to the configuration. A default becomes the first
+/// of its applications `installed` accepts, and added associations are only
+/// made for installed applications; removed associations are applied as
+/// they are, as blocking a missing application does no harm. Entries
+/// already in place aren't reported.
+pub fn apply_export(
+    config: &mut MimeAppsConfig,
+    exported: &ExportedDefaults,
+    installed: impl Fn(&str) -> bool,
+) -> ImportOutcome {
+    let mut outcome = ImportOutcome::default();
+
+    for (mime, apps) in &exported.defaults {
+        let Some(app_id) = apps.iter().find(|id| installed(id)) else {
+            outcome.skipped.push(format!(
+                "Default for {}: {} not installed",
+                mime,
+                apps.join(", ")
+            ));
+            continue;
+        };
+        if config.get_default(mime) == Some(app_id.as_str()) {
+            continue;
+        }
+        match config.set_default(mime, app_id) {
+            Ok(()) => outcome
+                .applied
+                .push(format!("{}\t{}\tdefault", mime, app_id)),
+            Err(e) => outcome
+                .skipped
+                .push(format!("Default for {}: {:#}", mime, e)),
+        }
+    }
+
+    for (mime, apps) in &exported.added_associations {
+        for app_id in apps {
+            if !installed(app_id) {
+                outcome.skipped.push(format!(
+                    "Association of {} with {}: not installed",
+                    app_id, mime
+                ));
+                continue;
+            }
+            match config.add_association(mime, app_id) {
+                Ok(true) => outcome
+                    .applied
+                    .push(format!("{}\t{}\tassociated", mime, app_id)),
+                Ok(false) => {}
+                Err(e) => outcome
+                    .skipped
+                    .push(format!("Association of {} with {}: {:#}", app_id, mime, e)),
+            }
+        }
+    }
+
+    for (mime, apps) in &exported.removed_associations {
+        for app_id in apps {
+            match config.remove_association(mime, app_id) {
+                Ok(true) => outcome
+                    .applied
+                    .push(format!("{}\t{}\tremoved", mime, app_id)),
+                Ok(false) => {}
+                Err(e) => outcome.skipped.push(format!(
+                    "Removed association of {} with {}: {:#}",
+                    app_id, mime, e
+                )),
+            }
+        }
+    }
+
+    outcome
+}
+
+fn rule(command: String, patterns: Vec<String>) -> ImportedRule {
+    let mut mime_types = Vec::new();
+    let mut skipped = Vec::new();
+
+    for pattern in patterns {
+        match pattern_mime_types(&pattern) {
+            Some(mimes) => mime_types.extend(mimes),
+            None => skipped.push(pattern),
+        }
+    }
+
+    ImportedRule {
+        command,
+        mime_types,
+        skipped,
+    }
+}
+
+/// The MIME types a pattern stands for: itself if it is a MIME type, or the
+/// scheme handlers for a regex matching every URL of a scheme (such as
+/// `^https?://` or `^magnet:.*`)
+fn pattern_mime_types(pattern: &str) -> Option<Vec<String>> {
+    if MimeAppsConfig::validate_mime_type(pattern).is_ok() {
+        return Some(vec![pattern.to_string()]);
+    }
+
+    let rest = pattern.strip_prefix('^').unwrap_or(pattern);
+    let (scheme, after) = rest.split_once(':')?;
+    if !matches!(after, "" | ".*" | ".+" | "//" | "//.*" | "//.+") {
+        return None;
+    }
+
+    let schemes = match scheme {
+        "https?" => vec!["http", "https"],
+        scheme
+            if scheme.starts_with(|c: char| c.is_ascii_lowercase())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "+-.".contains(c)) =>
+        {
+            vec![scheme]
+        }
+        _ => return None,
+    };
+    Some(
+        schemes
+            .into_iter()
+            .map(|scheme| format!("x-scheme-handler/{}", scheme))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mimeo() {
+        let rules = parse_mimeo(
+            "# browsers\n\
+             firefox %U\n\
+             \x20 ^https?://\n\
+             \x20 text/html\n\
+             \x20 ^https://www\\.youtube\\.com/.*\n\
+             \n\
+             org.gnome.Evince.desktop\n\
+             \tapplication/pdf\n",
+        );
+
+        assert_eq!(
+            rules,
+            vec![
+                ImportedRule {
+                    command: "firefox %U".to_string(),
+                    mime_types: vec![
+                        "x-scheme-handler/http".to_string(),
+                        "x-scheme-handler/https".to_string(),
+                        "text/html".to_string(),
+                    ],
+                    skipped: vec!["^https://www\\.youtube\\.com/.*".to_string()],
+                },
+                ImportedRule {
+                    command: "org.gnome.Evince.desktop".to_string(),
+                    mime_types: vec!["application/pdf".to_string()],
+                    skipped: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_export() {
+        let exported = parse_export(
+            r#"{
+                "version": 1,
+                "defaults": {
+                    "text/plain": ["missing.desktop", "code.desktop"],
+                    "image/png": ["missing.desktop"],
+                    "application/pdf": ["org.gnome.Evince.desktop"]
+                },
+                "added_associations": {
+                    "image/png": ["gimp.desktop", "missing.desktop"]
+                },
+                "removed_associations": {
+                    "text/html": ["missing.desktop"]
+                }
+            }"#,
+        )
+        .unwrap();
+        let mut config = MimeAppsConfig::demo();
+
+        let outcome = apply_export(&mut config, &exported, |id| id != "missing.desktop");
+        assert_eq!(config.get_default("text/plain"), Some("code.desktop"));
+        assert!(config.is_removed("text/html", "missing.desktop"));
+        // Evince already opens PDFs
+        assert!(!outcome
+            .applied
+            .iter()
+            .any(|line| line.starts_with("application/pdf")));
+        assert_eq!(
+            outcome.skipped,
+            vec![
+                "Default for image/png: missing.desktop not installed",
+                "Association of missing.desktop with image/png: not installed",
+            ]
+        );
+
+        assert!(parse_export(r#"{"version": 2}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_handlr() {
+        let rules = parse_handlr(
+            r#"
+enable_selector = false
+
+[[handlers]]
+exec = "transmission-gtk %u"
+regexes = ["^magnet:.*"]
+
+[[handlers]]
+exec = "freetube %u"
+regexes = ['(https://)?(www\.)?youtu(be\.com|\.be)/(.*)']
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(rules[0].mime_types, vec!["x-scheme-handler/magnet"]);
+        assert!(rules[1].mime_types.is_empty());
+        assert_eq!(rules[1].skipped.len(), 1);
+    }
+}
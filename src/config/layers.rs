@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use super::document::{
+    MimeAppsDocument, ADDED_ASSOCIATIONS, DEFAULT_APPLICATIONS, REMOVED_ASSOCIATIONS,
+};
+use super::MimeAppsConfig;
+
+/// One mimeapps.list location and what it contributes to the merge
+#[derive(Debug)]
+pub struct Layer {
+    pub path: PathBuf,
+    /// Whether the file exists; missing layers contribute nothing
+    pub exists: bool,
+    pub entries: Vec<LayerEntry>,
+}
+
+/// A key of one layer's file
+#[derive(Debug, PartialEq, Eq)]
+pub struct LayerEntry {
+    pub section: &'static str,
+    pub mime: String,
+    pub apps: Vec<String>,
+    pub status: EntryStatus,
+}
+
+/// How an entry takes part in the merge
+#[derive(Debug, PartialEq, Eq)]
+pub enum EntryStatus {
+    /// The default that applies
+    Used,
+    /// A default hidden by the layer at this index
+    Overridden(usize),
+    /// Associations, which are combined from every layer
+    Merged,
+    /// Listed without any applications, so it is skipped
+    Empty,
+}
+
+/// Every mimeapps.list location in priority order (highest first), with
+/// the entries each contributes and which defaults win
+pub fn inspect_layers() -> Result<Vec<Layer>> {
+    let documents = MimeAppsConfig::layer_paths()?
+        .into_iter()
+        .map(|path| {
+            let doc = match MimeAppsConfig::read_document(&path) {
+                Ok(doc) => path.exists().then_some(doc),
+                Err(e) => {
+                    tracing::warn!("{:#}", e);
+                    None
+                }
+            };
+            (path, doc)
+        })
+        .collect();
+
+    Ok(resolve_layers(documents))
+}
+
+/// Work out the status of each entry from the parsed layers, highest
+/// priority first (`None` for files that don't exist)
+fn resolve_layers(documents: Vec<(PathBuf, Option<MimeAppsDocument>)>) -> Vec<Layer> {
+    // Layer whose default applies, per MIME type
+    let mut winners: HashMap<String, usize> = HashMap::new();
+
+    documents
+        .into_iter()
+        .enumerate()
+        .map(|(index, (path, doc))| {
+            let mut entries = Vec::new();
+
+            if let Some(doc) = &doc {
+                for section in [
+                    DEFAULT_APPLICATIONS,
+                    ADDED_ASSOCIATIONS,
+                    REMOVED_ASSOCIATIONS,
+                ] {
+                    // Later duplicates of a key replace earlier ones
+                    let mut keys: Vec<(&str, &[String])> = Vec::new();
+                    for (mime, apps) in doc.entries(section) {
+                        keys.retain(|(key, _)| *key != mime);
+                        keys.push((mime, apps));
+                    }
+
+                    for (mime, apps) in keys {
+                        let status = if apps.is_empty() {
+                            EntryStatus::Empty
+                        } else if section != DEFAULT_APPLICATIONS {
+                            EntryStatus::Merged
+                        } else {
+                            match winners.get(mime) {
+                                Some(&winner) => EntryStatus::Overridden(winner),
+                                None => {
+                                    winners.insert(mime.to_string(), index);
+                                    EntryStatus::Used
+                                }
+                            }
+                        };
+
+                        entries.push(LayerEntry {
+                            section,
+                            mime: mime.to_string(),
+                            apps: apps.to_vec(),
+                            status,
+                        });
+                    }
+                }
+            }
+
+            Layer {
+                path,
+                exists: doc.is_some(),
+                entries,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_layers() {
+        let layers = resolve_layers(vec![
+            (
+                PathBuf::from("/home/user/.config/gnome-mimeapps.list"),
+                Some(MimeAppsDocument::parse(
+                    "[Default Applications]\ntext/html=firefox.desktop\ntext/plain=\n",
+                )),
+            ),
+            (PathBuf::from("/home/user/.config/mimeapps.list"), None),
+            (
+                PathBuf::from("/etc/xdg/mimeapps.list"),
+                Some(MimeAppsDocument::parse(
+                    "[Default Applications]\n\
+                     text/html=chromium.desktop\n\
+                     image/png=eog.desktop\n\
+                     image/png=gimp.desktop\n\
+                     [Added Associations]\n\
+                     image/png=gimp.desktop\n",
+                )),
+            ),
+        ]);
+
+        let statuses: Vec<Vec<(&str, &EntryStatus)>> = layers
+            .iter()
+            .map(|layer| {
+                layer
+                    .entries
+                    .iter()
+                    .map(|entry| (entry.mime.as_str(), &entry.status))
+                    .collect()
+            })
+            .collect();
+
+        assert_eq!(
+            statuses,
+            vec![
+                vec![
+                    ("text/html", &EntryStatus::Used),
+                    ("text/plain", &EntryStatus::Empty),
+                ],
+                vec![],
+                vec![
+                    ("text/html", &EntryStatus::Overridden(0)),
+                    ("image/png", &EntryStatus::Used),
+                    ("image/png", &EntryStatus::Merged),
+                ],
+            ]
+        );
+        assert!(!layers[1].exists);
+        assert_eq!(layers[2].entries[1].apps, vec!["gimp.desktop"]);
+    }
+}
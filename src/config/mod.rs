@@ -1,3 +1,9 @@
-pub mod mimeapps;
+pub use xdg_chooser_core::config::{changes, diagnostics, document, mimeapps, settings};
+
+pub mod export;
+pub mod import;
+pub mod layers;
+pub mod presets;
+pub mod report;
 
 pub use mimeapps::MimeAppsConfig;
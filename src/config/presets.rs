@@ -0,0 +1,267 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::settings::Settings;
+use crate::desktop::categories::AppCategory;
+
+/// Where a preset was found, highest priority first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PresetSource {
+    /// `~/.config/xdg-chooser/presets.d`
+    User,
+    /// `/etc/xdg/xdg-chooser/presets.d`, for administrators
+    System,
+    /// `/usr/share/xdg-chooser/presets.d`, shipped by distributions
+    Vendor,
+}
+
+impl PresetSource {
+    /// Short name shown in listings
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::User => "User",
+            Self::System => "System",
+            Self::Vendor => "Vendor",
+        }
+    }
+}
+
+/// A named set of recommended defaults, read from a `presets.d/*.toml` file:
+///
+/// ```toml
+/// name = "Example Desktop"
+/// description = "Applications shipped with Example OS"
+///
+/// [categories]
+/// web-browser = "firefox.desktop"
+///
+/// [defaults]
+/// "application/pdf" = "org.gnome.Evince.desktop"
+/// ```
+///
+/// `categories` sets the app for every primary MIME type of a category;
+/// `defaults` sets single MIME types and wins over the categories.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Preset {
+    /// File name without `.toml`; a file with the same name in a higher
+    /// priority directory replaces this one
+    #[serde(skip_deserializing)]
+    pub id: String,
+    #[serde(skip_deserializing)]
+    pub source: Option<PresetSource>,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub categories: BTreeMap<String, String>,
+    #[serde(default)]
+    pub defaults: BTreeMap<String, String>,
+}
+
+impl Preset {
+    /// Parse a preset file's contents
+    pub fn parse(id: &str, content: &str) -> Result<Self> {
+        let mut preset: Preset = toml::from_str(content)?;
+        preset.id = id.to_string();
+
+        for category in preset.categories.keys() {
+            if AppCategory::from_id(category).is_none() {
+                bail!("Unknown category '{}'", category);
+            }
+        }
+
+        Ok(preset)
+    }
+
+    /// The MIME type → desktop file ID pairs this preset sets, sorted by MIME
+    /// type. Categories cover their primary types with the user's promotions
+    /// and demotions, like "Set as Default".
+    pub fn assignments(&self, settings: &Settings) -> Vec<(String, String)> {
+        let mut assignments: BTreeMap<String, String> = BTreeMap::new();
+
+        for (category, app_id) in &self.categories {
+            if let Some(category) = AppCategory::from_id(category) {
+                for mime in settings.primary_mime_types(&category) {
+                    assignments.insert(mime, app_id.clone());
+                }
+            }
+        }
+
+        for (mime, app_id) in &self.defaults {
+            assignments.insert(mime.clone(), app_id.clone());
+        }
+
+        assignments.into_iter().collect()
+    }
+}
+
+/// Preset directories with their source, highest priority first
+pub fn preset_dirs() -> Vec<(PathBuf, PresetSource)> {
+    let Ok(xdg_dirs) = xdg::BaseDirectories::new() else {
+        return Vec::new();
+    };
+
+    let mut dirs = vec![(xdg_dirs.get_config_home(), PresetSource::User)];
+    dirs.extend(
+        xdg_dirs
+            .get_config_dirs()
+            .into_iter()
+            .map(|dir| (dir, PresetSource::System)),
+    );
+    dirs.extend(
+        xdg_dirs
+            .get_data_dirs()
+            .into_iter()
+            .map(|dir| (dir, PresetSource::Vendor)),
+    );
+
+    dirs.into_iter()
+        .map(|(dir, source)| (dir.join("xdg-chooser/presets.d"), source))
+        .collect()
+}
+
+/// Load all available presets, sorted by name
+pub fn load_presets() -> Vec<Preset> {
+    load_from_dirs(&preset_dirs())
+}
+
+/// Load presets from directories in priority order. Invalid files are
+/// skipped with a warning so one broken drop-in doesn't hide the rest.
+fn load_from_dirs(dirs: &[(PathBuf, PresetSource)]) -> Vec<Preset> {
+    let mut presets: BTreeMap<String, Preset> = BTreeMap::new();
+
+    for (dir, source) in dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().is_none_or(|ext| ext != "toml") {
+                continue;
+            }
+            let Some(id) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            // Higher priority directories were read first
+            if presets.contains_key(id) {
+                continue;
+            }
+
+            match load_file(id, &path) {
+                Ok(mut preset) => {
+                    preset.source = Some(*source);
+                    presets.insert(id.to_string(), preset);
+                }
+                Err(e) => tracing::warn!("Skipping preset {}: {:#}", path.display(), e),
+            }
+        }
+    }
+
+    let mut presets: Vec<Preset> = presets.into_values().collect();
+    presets.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    presets
+}
+
+fn load_file(id: &str, path: &Path) -> Result<Preset> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Preset::parse(id, &content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::MimeTypeOverrides;
+
+    #[test]
+    fn test_assignments() {
+        let preset = Preset::parse(
+            "example",
+            r#"
+name = "Example"
+
+[categories]
+email-client = "thunderbird.desktop"
+
+[defaults]
+"x-scheme-handler/mailto" = "geary.desktop"
+"#,
+        )
+        .unwrap();
+
+        let mut settings = Settings::default();
+        let assignments = preset.assignments(&settings);
+        assert!(assignments.contains(&(
+            "message/rfc822".to_string(),
+            "thunderbird.desktop".to_string()
+        )));
+        assert!(assignments.contains(&(
+            "x-scheme-handler/mailto".to_string(),
+            "geary.desktop".to_string()
+        )));
+
+        // Categories follow the user's promoted and demoted types
+        settings.mime_types.insert(
+            "email-client".to_string(),
+            MimeTypeOverrides {
+                promote: vec!["application/mbox".to_string()],
+                demote: vec!["message/rfc822".to_string()],
+            },
+        );
+        let assignments = preset.assignments(&settings);
+        assert!(assignments.contains(&(
+            "application/mbox".to_string(),
+            "thunderbird.desktop".to_string()
+        )));
+        assert!(!assignments.iter().any(|(mime, _)| mime == "message/rfc822"));
+
+        assert!(
+            Preset::parse("bad", "name = \"Bad\"\n[categories]\nnope = \"a.desktop\"").is_err()
+        );
+    }
+
+    #[test]
+    fn test_json_includes_id_and_source() {
+        let mut preset = Preset::parse("example", "name = \"Example\"\n").unwrap();
+        preset.source = Some(PresetSource::Vendor);
+
+        let json = serde_json::to_value(&preset).unwrap();
+        assert_eq!(json["id"], "example");
+        assert_eq!(json["source"], "vendor");
+        assert_eq!(json["name"], "Example");
+    }
+
+    #[test]
+    fn test_higher_priority_directory_wins() {
+        let root = std::env::temp_dir().join(format!("xdg-chooser-presets-{}", std::process::id()));
+        let user = root.join("user");
+        let vendor = root.join("vendor");
+        fs::create_dir_all(&user).unwrap();
+        fs::create_dir_all(&vendor).unwrap();
+
+        fs::write(user.join("distro.toml"), "name = \"Mine\"\n").unwrap();
+        fs::write(vendor.join("distro.toml"), "name = \"Distro\"\n").unwrap();
+        fs::write(vendor.join("extra.toml"), "name = \"Extra\"\n").unwrap();
+        fs::write(vendor.join("broken.toml"), "name = \n").unwrap();
+
+        let presets = load_from_dirs(&[(user, PresetSource::User), (vendor, PresetSource::Vendor)]);
+        fs::remove_dir_all(&root).unwrap();
+
+        let found: Vec<_> = presets
+            .iter()
+            .map(|p| (p.id.as_str(), p.name.as_str(), p.source))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("extra", "Extra", Some(PresetSource::Vendor)),
+                ("distro", "Mine", Some(PresetSource::User)),
+            ]
+        );
+    }
+}
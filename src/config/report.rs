@@ -0,0 +1,319 @@
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use super::MimeAppsConfig;
+use crate::desktop::categories::AppCategory;
+use crate::desktop::discovery::AppRegistry;
+use crate::desktop::native::current_desktops;
+use crate::utils::icons::icon_data_uri;
+
+/// Snapshot of every category's defaults and any problems with them, for
+/// support tickets and documenting machines
+#[derive(Debug, Serialize)]
+pub struct Report {
+    /// Desktops from `XDG_CURRENT_DESKTOP`, which pick the desktop-specific
+    /// files
+    pub desktops: Vec<String>,
+    /// mimeapps.list files that exist, highest priority first
+    pub files: Vec<PathBuf>,
+    pub categories: Vec<CategoryReport>,
+    /// Invalid lines in the files, and defaults naming missing applications
+    pub problems: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CategoryReport {
+    pub category: AppCategory,
+    pub name: String,
+    pub icon: String,
+    /// One entry per primary MIME type
+    pub defaults: Vec<DefaultReport>,
+    /// Installed applications offered for the category, most fitting first
+    pub candidates: Vec<CandidateReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CandidateReport {
+    pub id: String,
+    pub name: String,
+    pub icon: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DefaultReport {
+    pub mime: String,
+    /// Desktop file ID, `None` when no default is set
+    pub app: Option<String>,
+    /// Name of the application, `None` when it isn't installed
+    pub app_name: Option<String>,
+    /// File the default comes from
+    pub source: Option<PathBuf>,
+}
+
+impl Report {
+    pub fn new(config: &MimeAppsConfig, registry: &AppRegistry) -> Self {
+        let files = if config.is_in_memory() {
+            Vec::new()
+        } else {
+            MimeAppsConfig::layer_paths()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|path| path.exists())
+                .collect()
+        };
+
+        let mut problems: Vec<String> = config
+            .diagnostics()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        let categories = AppCategory::all()
+            .into_iter()
+            .map(|category| {
+                let defaults = config
+                    .settings
+                    .primary_mime_types(&category)
+                    .into_iter()
+                    .map(|mime| {
+                        let app = config.get_default(&mime).map(str::to_string);
+                        let app_name = app
+                            .as_deref()
+                            .and_then(|id| registry.get_app(id))
+                            .map(|entry| entry.name.clone());
+                        if let (Some(id), None) = (&app, &app_name) {
+                            problems.push(format!(
+                                "The default for {} is {}, which is not installed",
+                                mime, id
+                            ));
+                        }
+
+                        DefaultReport {
+                            source: config.default_source(&mime).map(PathBuf::from),
+                            mime,
+                            app,
+                            app_name,
+                        }
+                    })
+                    .collect();
+
+                let candidates = registry
+                    .ranked_apps_for_app_category(&category, &config.settings)
+                    .into_iter()
+                    .map(|app| CandidateReport {
+                        id: app.id.to_string(),
+                        name: app.name.clone(),
+                        icon: app.icon.clone(),
+                    })
+                    .collect();
+
+                CategoryReport {
+                    name: category.display_name().to_string(),
+                    icon: category.icon_name().to_string(),
+                    category,
+                    defaults,
+                    candidates,
+                }
+            })
+            .collect();
+
+        Self {
+            desktops: current_desktops(),
+            files,
+            categories,
+            problems,
+        }
+    }
+
+    /// The report as a Markdown document with a table per category
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Default applications\n\n");
+
+        if !self.desktops.is_empty() {
+            let _ = writeln!(out, "Desktop: {}\n", self.desktops.join(", "));
+        }
+        if !self.files.is_empty() {
+            out.push_str("Configuration files, highest priority first:\n\n");
+            for path in &self.files {
+                let _ = writeln!(out, "- `{}`", path.display());
+            }
+            out.push('\n');
+        }
+
+        for category in &self.categories {
+            let _ = writeln!(out, "## {}\n", category.name);
+            out.push_str("| MIME type | Default | Source |\n");
+            out.push_str("| --- | --- | --- |\n");
+            for default in &category.defaults {
+                let app = match (&default.app, &default.app_name) {
+                    (Some(id), Some(name)) => format!("{} (`{}`)", name, id),
+                    (Some(id), None) => format!("`{}` (not installed)", id),
+                    (None, _) => "none".to_string(),
+                };
+                let source = default
+                    .source
+                    .as_ref()
+                    .map_or_else(String::new, |path| format!("`{}`", path.display()));
+                let _ = writeln!(out, "| `{}` | {} | {} |", default.mime, app, source);
+            }
+            out.push('\n');
+        }
+
+        out.push_str("## Problems\n\n");
+        if self.problems.is_empty() {
+            out.push_str("None found.\n");
+        }
+        for problem in &self.problems {
+            let _ = writeln!(out, "- {}", problem);
+        }
+
+        out
+    }
+
+    /// The report as a standalone HTML page, with the icons embedded so it
+    /// can be viewed on another machine
+    pub fn to_html(&self) -> String {
+        let mut out = String::from(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Default applications</title>\n<style>\n\
+             body { font-family: sans-serif; max-width: 60em; margin: 2em auto; padding: 0 1em; }\n\
+             table { border-collapse: collapse; width: 100%; }\n\
+             th, td { text-align: left; padding: 0.3em 0.6em; border-bottom: 1px solid #ccc; }\n\
+             img { width: 24px; height: 24px; vertical-align: middle; margin-right: 0.4em; }\n\
+             .missing { color: #b00; }\n\
+             </style>\n</head>\n<body>\n<h1>Default applications</h1>\n",
+        );
+
+        if !self.desktops.is_empty() {
+            let _ = writeln!(
+                out,
+                "<p>Desktop: {}</p>",
+                escape_html(&self.desktops.join(", "))
+            );
+        }
+        if !self.files.is_empty() {
+            out.push_str("<p>Configuration files, highest priority first:</p>\n<ul>\n");
+            for path in &self.files {
+                let _ = writeln!(
+                    out,
+                    "<li><code>{}</code></li>",
+                    escape_html(&path.display().to_string())
+                );
+            }
+            out.push_str("</ul>\n");
+        }
+
+        for category in &self.categories {
+            let _ = writeln!(
+                out,
+                "<h2>{}{}</h2>",
+                icon_html(Some(&category.icon)),
+                escape_html(&category.name)
+            );
+
+            out.push_str("<table>\n<tr><th>MIME type</th><th>Default</th><th>Source</th></tr>\n");
+            for default in &category.defaults {
+                let app = match (&default.app, &default.app_name) {
+                    (Some(id), Some(name)) => {
+                        format!("{} (<code>{}</code>)", escape_html(name), escape_html(id))
+                    }
+                    (Some(id), None) => format!(
+                        "<span class=\"missing\"><code>{}</code> (not installed)</span>",
+                        escape_html(id)
+                    ),
+                    (None, _) => "none".to_string(),
+                };
+                let source = default.source.as_ref().map_or_else(String::new, |path| {
+                    format!("<code>{}</code>", escape_html(&path.display().to_string()))
+                });
+                let _ = writeln!(
+                    out,
+                    "<tr><td><code>{}</code></td><td>{}</td><td>{}</td></tr>",
+                    escape_html(&default.mime),
+                    app,
+                    source
+                );
+            }
+            out.push_str("</table>\n");
+
+            if !category.candidates.is_empty() {
+                out.push_str("<p>Installed applications:</p>\n<ul>\n");
+                for candidate in &category.candidates {
+                    let _ = writeln!(
+                        out,
+                        "<li>{}{} (<code>{}</code>)</li>",
+                        icon_html(candidate.icon.as_deref()),
+                        escape_html(&candidate.name),
+                        escape_html(&candidate.id)
+                    );
+                }
+                out.push_str("</ul>\n");
+            }
+        }
+
+        out.push_str("<h2>Problems</h2>\n");
+        if self.problems.is_empty() {
+            out.push_str("<p>None found.</p>\n");
+        } else {
+            out.push_str("<ul>\n");
+            for problem in &self.problems {
+                let _ = writeln!(out, "<li>{}</li>", escape_html(problem));
+            }
+            out.push_str("</ul>\n");
+        }
+
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+/// An `<img>` with the icon inlined, or nothing if it can't be found
+fn icon_html(name: Option<&str>) -> String {
+    name.and_then(icon_data_uri)
+        .map(|uri| format!("<img src=\"{}\" alt=\"\">", uri))
+        .unwrap_or_default()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_formats() {
+        let registry = AppRegistry::demo();
+        let mut config = MimeAppsConfig::demo();
+        config.set_default("text/plain", "missing.desktop").unwrap();
+
+        let report = Report::new(&config, &registry);
+        assert_eq!(report.categories.len(), AppCategory::all().len());
+        assert!(report
+            .problems
+            .iter()
+            .any(|p| p.contains("missing.desktop")));
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("## Web Browser\n"));
+        assert!(markdown.contains("| `text/plain` | `missing.desktop` (not installed) |"));
+
+        let json: serde_json::Value = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["categories"][0]["category"], "web-browser");
+        assert!(report.categories[0]
+            .candidates
+            .iter()
+            .any(|c| c.id == "firefox.desktop"));
+
+        let html = report.to_html();
+        assert!(html.contains("Web Browser</h2>"));
+        assert!(html.contains("<code>missing.desktop</code> (not installed)"));
+        assert_eq!(escape_html("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+}
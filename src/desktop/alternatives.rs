@@ -0,0 +1,94 @@
+use std::collections::HashSet;
+
+use super::categories::AppCategory;
+use super::discovery::AppRegistry;
+use super::entry::AppEntry;
+use crate::config::MimeAppsConfig;
+
+/// A category with the installed applications that could serve it but
+/// aren't the default for anything they declare
+pub struct Alternatives<'a> {
+    pub category: AppCategory,
+    /// The category's main types with the user's promotions and demotions
+    pub mime_types: Vec<String>,
+    pub apps: Vec<&'a AppEntry>,
+}
+
+/// Every installed application that is the default for none of the types
+/// it declares, under each shown category whose main types it declares (in
+/// sidebar order). Apps the user removed from all of a category's types are
+/// left out of it.
+pub fn unused_alternatives<'a>(
+    registry: &'a AppRegistry,
+    config: &MimeAppsConfig,
+) -> Vec<Alternatives<'a>> {
+    let in_use: HashSet<&str> = config
+        .effective_defaults()
+        .iter()
+        .filter_map(|(mime, id)| {
+            registry
+                .get_app(id)
+                .filter(|app| app.supports_mime_type(mime))
+                .map(|app| &*app.id)
+        })
+        .collect();
+
+    let settings = &config.settings;
+    settings
+        .ordered_categories(registry.visible_categories(settings))
+        .into_iter()
+        .filter(|category| !settings.is_category_hidden(category))
+        .filter_map(|category| {
+            let mime_types = settings.primary_mime_types(&category);
+            let apps: Vec<&AppEntry> = registry
+                .apps_for_app_category(&category, settings)
+                .into_iter()
+                .filter(|app| !in_use.contains(&*app.id))
+                .filter(|app| {
+                    mime_types.iter().any(|mime| {
+                        app.supports_mime_type(mime) && !config.is_removed(mime, &app.id)
+                    })
+                })
+                .collect();
+            (!apps.is_empty()).then_some(Alternatives {
+                category,
+                mime_types,
+                apps,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unused_alternatives() {
+        let registry = AppRegistry::demo();
+        let config = MimeAppsConfig::demo();
+
+        let alternatives = unused_alternatives(&registry, &config);
+        let ids = |category: AppCategory| -> Vec<&str> {
+            alternatives
+                .iter()
+                .find(|alternatives| alternatives.category == category)
+                .map(|alternatives| alternatives.apps.iter().map(|app| &*app.id).collect())
+                .unwrap_or_default()
+        };
+
+        // Firefox opens links, Chromium nothing
+        assert_eq!(ids(AppCategory::WebBrowser), vec!["chromium.desktop"]);
+        assert_eq!(
+            ids(AppCategory::EmailClient),
+            vec!["org.gnome.Geary.desktop"]
+        );
+        let file_managers = ids(AppCategory::FileManager);
+        assert!(file_managers.contains(&"org.kde.dolphin.desktop"));
+        assert!(!file_managers.contains(&"org.gnome.Nautilus.desktop"));
+        // Chromium's PDF association was removed
+        assert!(!ids(AppCategory::DocumentViewer).contains(&"chromium.desktop"));
+        // No MIME types to set a default for
+        assert!(ids(AppCategory::Calculator).is_empty());
+    }
+}
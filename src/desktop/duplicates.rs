@@ -0,0 +1,102 @@
+use super::discovery::AppRegistry;
+use super::entry::AppEntry;
+use super::providers::is_flatpak;
+
+/// An application installed both as a native package and as a Flatpak,
+/// under two desktop file IDs
+#[derive(Debug)]
+pub struct FlatpakDuplicate<'a> {
+    pub native: &'a AppEntry,
+    pub flatpak: &'a AppEntry,
+}
+
+/// Every installed application that has a native and a Flatpak desktop
+/// file, by native app name
+pub fn flatpak_duplicates(registry: &AppRegistry) -> Vec<FlatpakDuplicate<'_>> {
+    let (flatpaks, natives): (Vec<&AppEntry>, Vec<&AppEntry>) = registry
+        .all_apps()
+        .into_iter()
+        .partition(|app| is_flatpak(app));
+    find_pairs(&natives, &flatpaks)
+}
+
+fn find_pairs<'a>(
+    natives: &[&'a AppEntry],
+    flatpaks: &[&'a AppEntry],
+) -> Vec<FlatpakDuplicate<'a>> {
+    natives
+        .iter()
+        .filter_map(|native| {
+            let flatpak = flatpaks.iter().find(|flatpak| same_app(native, flatpak))?;
+            Some(FlatpakDuplicate { native, flatpak })
+        })
+        .collect()
+}
+
+/// Whether a native and a Flatpak desktop entry look like the same app:
+/// the same name, the native ID matching the last part of the Flatpak's
+/// (`firefox` and `org.mozilla.firefox`), or the same StartupWMClass
+fn same_app(native: &AppEntry, flatpak: &AppEntry) -> bool {
+    let native_id = native.id.strip_suffix(".desktop").unwrap_or(&native.id);
+    let flatpak_id = flatpak.id.strip_suffix(".desktop").unwrap_or(&flatpak.id);
+    let flatpak_name = flatpak_id.rsplit('.').next().unwrap_or(flatpak_id);
+
+    native.name.eq_ignore_ascii_case(&flatpak.name)
+        || native_id.eq_ignore_ascii_case(flatpak_name)
+        || native
+            .startup_wm_class
+            .as_deref()
+            .zip(flatpak.startup_wm_class.as_deref())
+            .is_some_and(|(a, b)| a.eq_ignore_ascii_case(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn entry(path: &str, name: &str) -> AppEntry {
+        let content = format!(
+            "[Desktop Entry]\nType=Application\nName={}\nExec=app %u\n",
+            name
+        );
+        AppEntry::parse(&content, Path::new(path), &[]).unwrap()
+    }
+
+    #[test]
+    fn test_find_pairs() {
+        let firefox = entry(
+            "/usr/share/applications/firefox.desktop",
+            "Firefox Web Browser",
+        );
+        let gimp = entry(
+            "/usr/share/applications/gimp.desktop",
+            "GNU Image Manipulation Program",
+        );
+        let eog = entry(
+            "/usr/share/applications/org.gnome.eog.desktop",
+            "Image Viewer",
+        );
+        let flatpak_firefox = entry(
+            "/var/lib/flatpak/exports/share/applications/org.mozilla.firefox.desktop",
+            "Firefox",
+        );
+        let flatpak_gimp = entry(
+            "/var/lib/flatpak/exports/share/applications/org.gimp.GIMP.desktop",
+            "GNU Image Manipulation Program",
+        );
+
+        let pairs = find_pairs(&[&firefox, &gimp, &eog], &[&flatpak_firefox, &flatpak_gimp]);
+        let ids: Vec<(&str, &str)> = pairs
+            .iter()
+            .map(|pair| (&*pair.native.id, &*pair.flatpak.id))
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                ("firefox.desktop", "org.mozilla.firefox.desktop"),
+                ("gimp.desktop", "org.gimp.GIMP.desktop"),
+            ]
+        );
+    }
+}
@@ -1,3 +1,7 @@
-pub mod categories;
-pub mod discovery;
-pub mod entry;
+pub use xdg_chooser_core::desktop::{categories, discovery, entry, mime_db, native, providers};
+
+pub mod alternatives;
+pub mod duplicates;
+pub mod portal;
+pub mod running;
+pub mod scheme_review;
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use gio::prelude::*;
+use serde::Serialize;
+
+const PERMISSION_STORE_NAME: &str = "org.freedesktop.impl.portal.PermissionStore";
+const PERMISSION_STORE_PATH: &str = "/org/freedesktop/impl/portal/PermissionStore";
+
+/// Permission store table where the OpenURI portal keeps, per content type,
+/// the handler each sandboxed app last picked
+const USED_APPS_TABLE: &str = "desktop-used-apps";
+
+/// Times a handler has to be picked before the portal stops asking, used
+/// when the store doesn't record one
+const DEFAULT_THRESHOLD: u32 = 3;
+
+/// A handler choice the OpenURI portal recorded for a sandboxed app
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PortalChoice {
+    /// Flatpak app ID of the app opening files or URIs
+    pub requester: String,
+    /// MIME type or `x-scheme-handler/...` being opened
+    pub content_type: String,
+    /// Desktop file ID of the chosen handler (with `.desktop`)
+    pub handler: String,
+    /// Whether the portal opens the handler without asking, which happens
+    /// once it has been picked often enough
+    pub remembered: bool,
+}
+
+/// Every choice recorded by the OpenURI portal, from the permission store
+/// on the session bus, sorted by requester and content type
+pub fn portal_choices() -> Result<Vec<PortalChoice>> {
+    let bus = gio::bus_get_sync(gio::BusType::Session, gio::Cancellable::NONE)
+        .context("Failed to connect to the session bus")?;
+    let call = |method: &str, args: glib::Variant, reply: &str| {
+        bus.call_sync(
+            Some(PERMISSION_STORE_NAME),
+            PERMISSION_STORE_PATH,
+            PERMISSION_STORE_NAME,
+            method,
+            Some(&args),
+            Some(glib::VariantTy::new(reply).expect("valid reply type")),
+            gio::DBusCallFlags::NONE,
+            -1,
+            gio::Cancellable::NONE,
+        )
+        .with_context(|| format!("Permission store {} call failed", method))
+    };
+
+    let (content_types,): (Vec<String>,) = call("List", (USED_APPS_TABLE,).to_variant(), "(as)")?
+        .get()
+        .context("Unexpected reply from the permission store")?;
+
+    let mut choices = Vec::new();
+    for content_type in content_types {
+        let reply = call(
+            "Lookup",
+            (USED_APPS_TABLE, content_type.as_str()).to_variant(),
+            "(a{sas}v)",
+        )?;
+        let permissions: HashMap<String, Vec<String>> = reply
+            .child_value(0)
+            .get()
+            .context("Unexpected reply from the permission store")?;
+        choices.extend(parse_permissions(&content_type, &permissions));
+    }
+
+    choices.sort_by(|a, b| (&a.requester, &a.content_type).cmp(&(&b.requester, &b.content_type)));
+    Ok(choices)
+}
+
+/// Turn one content type's permissions (app ID → `[handler, count,
+/// threshold]`) into choices, skipping malformed entries
+fn parse_permissions(
+    content_type: &str,
+    permissions: &HashMap<String, Vec<String>>,
+) -> Vec<PortalChoice> {
+    permissions
+        .iter()
+        .filter_map(|(requester, values)| {
+            let handler = values.first().filter(|h| !h.is_empty())?;
+            let count: u32 = values.get(1).and_then(|c| c.parse().ok()).unwrap_or(0);
+            let threshold: u32 = values
+                .get(2)
+                .and_then(|t| t.parse().ok())
+                .unwrap_or(DEFAULT_THRESHOLD);
+
+            Some(PortalChoice {
+                requester: requester.clone(),
+                content_type: content_type.to_string(),
+                handler: if handler.ends_with(".desktop") {
+                    handler.clone()
+                } else {
+                    format!("{}.desktop", handler)
+                },
+                remembered: count >= threshold,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_permissions() {
+        let permissions: HashMap<String, Vec<String>> = [
+            (
+                "org.mozilla.firefox".to_string(),
+                vec!["org.gnome.Evince".to_string(), "3".into(), "3".into()],
+            ),
+            (
+                "org.telegram.desktop".to_string(),
+                vec!["org.kde.okular.desktop".to_string(), "1".into()],
+            ),
+            ("com.example.Broken".to_string(), vec![]),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut choices = parse_permissions("application/pdf", &permissions);
+        choices.sort_by(|a, b| a.requester.cmp(&b.requester));
+
+        assert_eq!(
+            choices,
+            vec![
+                PortalChoice {
+                    requester: "org.mozilla.firefox".to_string(),
+                    content_type: "application/pdf".to_string(),
+                    handler: "org.gnome.Evince.desktop".to_string(),
+                    remembered: true,
+                },
+                PortalChoice {
+                    requester: "org.telegram.desktop".to_string(),
+                    content_type: "application/pdf".to_string(),
+                    handler: "org.kde.okular.desktop".to_string(),
+                    remembered: false,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use anyhow::{Context, Result};
+use gio::prelude::*;
+
+use super::entry::AppEntry;
+
+const DBUS_NAME: &str = "org.freedesktop.DBus";
+const DBUS_PATH: &str = "/org/freedesktop/DBus";
+
+/// Interface of the Desktop Entry specification's D-Bus activation, which
+/// GApplication and KDE apps implement on the bus name of their app ID
+const APPLICATION_INTERFACE: &str = "org.freedesktop.Application";
+
+/// How long the window waits for the bus to list its names
+const LIST_NAMES_TIMEOUT_MS: i32 = 2000;
+
+/// The well-known names currently owned on the session bus, such as
+/// `org.gnome.Evince` or `org.mpris.MediaPlayer2.vlc`
+pub fn bus_names() -> Result<Vec<String>> {
+    let bus = gio::bus_get_sync(gio::BusType::Session, gio::Cancellable::NONE)
+        .context("Failed to connect to the session bus")?;
+    let reply = bus
+        .call_sync(
+            Some(DBUS_NAME),
+            DBUS_PATH,
+            DBUS_NAME,
+            "ListNames",
+            None,
+            Some(glib::VariantTy::new("(as)").expect("valid reply type")),
+            gio::DBusCallFlags::NONE,
+            -1,
+            gio::Cancellable::NONE,
+        )
+        .context("ListNames call failed")?;
+    well_known_names(&reply)
+}
+
+/// [`bus_names`] without blocking the main loop, passed to `callback` once
+/// the bus replies
+pub fn bus_names_async<F>(callback: F)
+where
+    F: FnOnce(Result<Vec<String>>) + 'static,
+{
+    gio::bus_get(gio::BusType::Session, gio::Cancellable::NONE, |bus| {
+        let bus = match bus {
+            Ok(bus) => bus,
+            Err(e) => {
+                callback(Err(e).context("Failed to connect to the session bus"));
+                return;
+            }
+        };
+        bus.call(
+            Some(DBUS_NAME),
+            DBUS_PATH,
+            DBUS_NAME,
+            "ListNames",
+            None,
+            Some(glib::VariantTy::new("(as)").expect("valid reply type")),
+            gio::DBusCallFlags::NONE,
+            LIST_NAMES_TIMEOUT_MS,
+            gio::Cancellable::NONE,
+            |reply| {
+                let reply = reply.context("ListNames call failed");
+                callback(reply.and_then(|reply| well_known_names(&reply)));
+            },
+        );
+    });
+}
+
+/// The names in a `ListNames` reply that aren't unique names
+fn well_known_names(reply: &glib::Variant) -> Result<Vec<String>> {
+    let (names,): (Vec<String>,) = reply
+        .get()
+        .context("Unexpected reply from the session bus")?;
+
+    // Unique names (":1.42") say nothing about which app owns them
+    Ok(names
+        .into_iter()
+        .filter(|name| !name.starts_with(':'))
+        .collect())
+}
+
+/// Whether an app looks to be running, going by the bus names it would
+/// own: its desktop file ID (D-Bus activatable and most Flatpak apps own
+/// it, or names under it), or its StartupWMClass as the last part of a name
+/// (as in media players' `org.mpris.MediaPlayer2.<name>`)
+pub fn is_running(app: &AppEntry, bus_names: &[String]) -> bool {
+    let app_id = app.id.strip_suffix(".desktop").unwrap_or(&app.id);
+
+    bus_names.iter().any(|name| {
+        let owned_by_id = name
+            .strip_prefix(app_id)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'));
+        let owned_by_class = app.startup_wm_class.as_deref().is_some_and(|class| {
+            name.rsplit('.')
+                .next()
+                .is_some_and(|last| last.eq_ignore_ascii_case(class))
+        });
+        owned_by_id || owned_by_class
+    })
+}
+
+/// If the app's ID is owned on the session bus, ask that instance to show
+/// its window (or open `file`, a path or URI) through
+/// `org.freedesktop.Application`, instead of starting a second process.
+/// Returns whether it was running.
+pub fn activate_running(
+    app: &AppEntry,
+    file: Option<&str>,
+    activation_token: Option<&str>,
+) -> Result<bool> {
+    let app_id = app.id.strip_suffix(".desktop").unwrap_or(&app.id);
+    if !bus_names()?.iter().any(|name| name == app_id) {
+        return Ok(false);
+    }
+
+    let platform_data = glib::VariantDict::new(None);
+    if let Some(token) = activation_token {
+        platform_data.insert("activation-token", token);
+        platform_data.insert("desktop-startup-id", token);
+    }
+    let platform_data = platform_data.end();
+    let (method, parameters) = match file {
+        Some(file) => {
+            let uri = gio::File::for_commandline_arg(file).uri().to_string();
+            ("Open", (vec![uri], platform_data).to_variant())
+        }
+        None => ("Activate", (platform_data,).to_variant()),
+    };
+
+    let bus = gio::bus_get_sync(gio::BusType::Session, gio::Cancellable::NONE)
+        .context("Failed to connect to the session bus")?;
+    bus.call_sync(
+        Some(app_id),
+        &object_path(app_id),
+        APPLICATION_INTERFACE,
+        method,
+        Some(&parameters),
+        None,
+        gio::DBusCallFlags::NONE,
+        -1,
+        gio::Cancellable::NONE,
+    )
+    .with_context(|| format!("{} call to {} failed", method, app_id))?;
+    Ok(true)
+}
+
+/// The object path an app exports `org.freedesktop.Application` at: its ID
+/// with dots turned into slashes and dashes into underscores
+fn object_path(app_id: &str) -> String {
+    format!("/{}", app_id.replace('.', "/").replace('-', "_"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn entry(id: &str, extra: &str) -> AppEntry {
+        let content = format!(
+            "[Desktop Entry]\nType=Application\nName=Test\nExec=test\n{}\n",
+            extra
+        );
+        AppEntry::parse(&content, Path::new(id), &[]).unwrap()
+    }
+
+    #[test]
+    fn test_is_running() {
+        let names = vec![
+            "org.gnome.Evince".to_string(),
+            "org.gnome.Nautilus.Tracker".to_string(),
+            "org.mpris.MediaPlayer2.vlc".to_string(),
+        ];
+
+        assert!(is_running(&entry("org.gnome.Evince.desktop", ""), &names));
+        assert!(is_running(&entry("org.gnome.Nautilus.desktop", ""), &names));
+        assert!(is_running(
+            &entry("vlc.desktop", "StartupWMClass=VLC"),
+            &names
+        ));
+        assert!(!is_running(&entry("org.gnome.Eog.desktop", ""), &names));
+        // A prefix of another app's ID isn't a match
+        assert!(!is_running(&entry("org.gnome.Evin.desktop", ""), &names));
+    }
+
+    #[test]
+    fn test_object_path() {
+        assert_eq!(object_path("org.gnome.Evince"), "/org/gnome/Evince");
+        assert_eq!(object_path("org.example.my-app"), "/org/example/my_app");
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use serde::Serialize;
+
+use super::discovery::{user_application_dir, AppRegistry};
+use super::entry::AppEntry;
+use crate::utils::exec::test_command;
+
+/// Desktop files changed this recently count as newly added handlers
+const RECENT: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+
+/// Programs that run whatever command line they are given
+const SHELLS: &[&str] = &[
+    "sh", "bash", "dash", "zsh", "ksh", "mksh", "fish", "csh", "tcsh", "busybox",
+];
+
+/// Something about a URL scheme handler worth a second look: links on web
+/// pages and in messages hand their URLs to it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SchemeRisk {
+    /// Its desktop file is in the user's own applications directory, which
+    /// any program the user runs can write to
+    UserInstalled,
+    /// Its desktop file was created or changed this many days ago
+    RecentlyChanged { days: u64 },
+    /// Its Exec line runs this shell, which interprets anything in the URL
+    RunsShell(String),
+}
+
+impl SchemeRisk {
+    pub fn description(&self) -> String {
+        match self {
+            Self::UserInstalled => "installed in your own applications directory".to_string(),
+            Self::RecentlyChanged { days: 0 } => "desktop file changed today".to_string(),
+            Self::RecentlyChanged { days: 1 } => "desktop file changed yesterday".to_string(),
+            Self::RecentlyChanged { days } => {
+                format!("desktop file changed {} days ago", days)
+            }
+            Self::RunsShell(shell) => format!("runs the {} shell", shell),
+        }
+    }
+}
+
+/// An application handling URL schemes, with what makes it stand out
+#[derive(Debug, Serialize)]
+pub struct SchemeHandlerReview {
+    pub app_id: String,
+    pub name: String,
+    pub path: PathBuf,
+    /// Schemes it declares, from MimeType or X-KDE-Protocols
+    pub schemes: Vec<String>,
+    /// Schemes it is the default handler of
+    pub default_for: Vec<String>,
+    pub risks: Vec<SchemeRisk>,
+}
+
+/// Every installed scheme handler with something unusual about it, default
+/// handlers first. `defaults` maps MIME types to their default app IDs.
+pub fn review_scheme_handlers(
+    registry: &AppRegistry,
+    defaults: &HashMap<String, String>,
+) -> Vec<SchemeHandlerReview> {
+    let user_dir = user_application_dir();
+    let now = SystemTime::now();
+
+    let mut reviews: Vec<SchemeHandlerReview> = registry
+        .all_apps()
+        .into_iter()
+        .filter_map(|app| {
+            let schemes = schemes(app);
+            if schemes.is_empty() {
+                return None;
+            }
+            let modified = app.path.metadata().and_then(|m| m.modified()).ok();
+            let risks = risks(app, user_dir.as_deref(), modified, now);
+            if risks.is_empty() {
+                return None;
+            }
+
+            let default_for = schemes
+                .iter()
+                .filter(|scheme| {
+                    defaults
+                        .get(&format!("x-scheme-handler/{}", scheme))
+                        .is_some_and(|id| **id == *app.id)
+                })
+                .cloned()
+                .collect();
+            Some(SchemeHandlerReview {
+                app_id: app.id.to_string(),
+                name: app.name.clone(),
+                path: app.path.clone(),
+                schemes,
+                default_for,
+                risks,
+            })
+        })
+        .collect();
+
+    // Stable sort keeps the name order of `all_apps`
+    reviews.sort_by_key(|review| review.default_for.is_empty());
+    reviews
+}
+
+/// The URL schemes an app declares it handles
+fn schemes(app: &AppEntry) -> Vec<String> {
+    let mut schemes: Vec<String> = app
+        .mime_types
+        .iter()
+        .filter_map(|mime| mime.strip_prefix("x-scheme-handler/"))
+        .map(str::to_string)
+        .collect();
+    for protocol in &app.protocols {
+        if !schemes.contains(protocol) {
+            schemes.push(protocol.clone());
+        }
+    }
+    schemes
+}
+
+/// What stands out about a handler, given its desktop file's modification
+/// time
+fn risks(
+    app: &AppEntry,
+    user_dir: Option<&Path>,
+    modified: Option<SystemTime>,
+    now: SystemTime,
+) -> Vec<SchemeRisk> {
+    let mut risks = Vec::new();
+
+    if user_dir.is_some_and(|dir| app.path.starts_with(dir)) {
+        risks.push(SchemeRisk::UserInstalled);
+    }
+
+    if let Some(age) = modified.and_then(|modified| now.duration_since(modified).ok()) {
+        if age < RECENT {
+            risks.push(SchemeRisk::RecentlyChanged {
+                days: age.as_secs() / (24 * 60 * 60),
+            });
+        }
+    }
+
+    if let Some(shell) = shell_program(app) {
+        risks.push(SchemeRisk::RunsShell(shell));
+    }
+
+    risks
+}
+
+/// The shell an app's Exec line runs, directly or through `env`
+fn shell_program(app: &AppEntry) -> Option<String> {
+    let argv = test_command(app, None).ok()?;
+    let mut args = argv.iter().map(|arg| {
+        Path::new(arg)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(arg)
+    });
+
+    let mut program = args.next()?;
+    if program == "env" {
+        // Skip env's options and variable assignments
+        program = args.find(|arg| !arg.starts_with('-') && !arg.contains('='))?;
+    }
+    SHELLS.contains(&program).then(|| program.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, exec: &str) -> AppEntry {
+        let content = format!(
+            "[Desktop Entry]\nType=Application\nName=Test\nExec={}\n\
+             MimeType=x-scheme-handler/https;text/html;\n\
+             X-KDE-Protocols=https,ftp\n",
+            exec
+        );
+        AppEntry::parse(&content, Path::new(path), &[]).unwrap()
+    }
+
+    #[test]
+    fn test_risks() {
+        let user_dir = Path::new("/home/user/.local/share/applications");
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(100 * 24 * 60 * 60);
+        let three_days_ago = now - Duration::from_secs(3 * 24 * 60 * 60 + 60);
+
+        let app = entry(
+            "/home/user/.local/share/applications/opener.desktop",
+            "/usr/bin/env FOO=1 bash -c \"curl %u | sh\"",
+        );
+        assert_eq!(schemes(&app), vec!["https", "ftp"]);
+        assert_eq!(
+            risks(&app, Some(user_dir), Some(three_days_ago), now),
+            vec![
+                SchemeRisk::UserInstalled,
+                SchemeRisk::RecentlyChanged { days: 3 },
+                SchemeRisk::RunsShell("bash".to_string()),
+            ]
+        );
+
+        let app = entry("/usr/share/applications/firefox.desktop", "firefox %u");
+        assert!(risks(&app, Some(user_dir), Some(SystemTime::UNIX_EPOCH), now).is_empty());
+    }
+}
@@ -1,17 +1,47 @@
 mod app;
+mod cli;
 mod config;
 mod desktop;
+mod tui;
 mod ui;
 mod utils;
 mod window;
 
+use clap::Parser;
 use gtk::glib;
 
 fn main() -> glib::ExitCode {
-    // Initialize logging
-    tracing_subscriber::fmt::init();
+    let cli = cli::Cli::parse();
+
+    // Initialize logging; the terminal interface owns the terminal, so it
+    // only logs to the file
+    utils::logging::init(cli.verbose, cli.log_file.as_deref(), !cli.tui);
+
+    // Command-line subcommands run without starting GTK
+    if let Some(command) = cli.command {
+        return match cli::run(command) {
+            Ok(status) => status,
+            Err(e) => {
+                eprintln!("error: {:#}", e);
+                glib::ExitCode::FAILURE
+            }
+        };
+    }
+
+    if cli.tui {
+        return match tui::run(&app::StartupOptions::from(&cli)) {
+            Ok(()) => glib::ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("error: {:#}", e);
+                glib::ExitCode::FAILURE
+            }
+        };
+    }
 
     // Create and run the application
     let app = app::GtkChooserApp::new();
+    if cli.gapplication_service {
+        app.wait_for_activation();
+    }
     app.run()
 }
@@ -0,0 +1,350 @@
+use anyhow::{Context, Result};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::app::StartupOptions;
+use crate::config::MimeAppsConfig;
+use crate::desktop::categories::AppCategory;
+use crate::desktop::discovery::AppRegistry;
+use crate::desktop::entry::AppEntry;
+
+const HELP: &str = "↑/↓ move  Home/End first/last  Tab switch pane  Enter set as default  q quit";
+
+/// Run the terminal interface until the user quits
+pub fn run(options: &StartupOptions) -> Result<()> {
+    let (config, registry) = options.load_data();
+    let mut app = TuiApp::new(config, registry, options.target_category());
+
+    let mut terminal = ratatui::try_init().context("Failed to set up the terminal")?;
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+/// Which list the arrow keys move in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Categories,
+    Apps,
+}
+
+/// Terminal counterpart of the main window: categories on the left, the
+/// current default and available applications of the selected one on the
+/// right
+struct TuiApp {
+    config: MimeAppsConfig,
+    registry: AppRegistry,
+    categories: Vec<AppCategory>,
+    category_state: ListState,
+    app_state: ListState,
+    focus: Focus,
+    /// Result of the last action, shown instead of the key help
+    status: Option<String>,
+    quit: bool,
+}
+
+impl TuiApp {
+    fn new(config: MimeAppsConfig, registry: AppRegistry, start: Option<AppCategory>) -> Self {
+        let categories: Vec<AppCategory> = config
+            .settings
+            .ordered_categories(registry.visible_categories(&config.settings))
+            .into_iter()
+            .filter(|category| !config.settings.is_category_hidden(category))
+            .collect();
+        let selected = start
+            .and_then(|start| categories.iter().position(|c| *c == start))
+            .unwrap_or(0);
+
+        Self {
+            config,
+            registry,
+            categories,
+            category_state: ListState::default().with_selected(Some(selected)),
+            app_state: ListState::default().with_selected(Some(0)),
+            focus: Focus::Categories,
+            status: None,
+            quit: false,
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle_key(key.code);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn category(&self) -> &AppCategory {
+        &self.categories[self.category_state.selected().unwrap_or(0)]
+    }
+
+    /// Applications offered for the selected category, minus those removed
+    /// from all of its types, in the window's order
+    fn apps(&self) -> Vec<&AppEntry> {
+        let category = self.category();
+        let primary = self.config.settings.primary_mime_types(category);
+        let mut apps = self
+            .registry
+            .ranked_apps_for_app_category(category, &self.config.settings);
+        if !primary.is_empty() {
+            apps.retain(|app| !primary.iter().all(|m| self.config.is_removed(m, &app.id)));
+        }
+        apps
+    }
+
+    fn handle_key(&mut self, key: KeyCode) {
+        self.status = None;
+
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Home | KeyCode::Char('g') => self.move_selection(isize::MIN),
+            KeyCode::End | KeyCode::Char('G') => self.move_selection(isize::MAX),
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Left | KeyCode::Right => {
+                self.focus = match self.focus {
+                    Focus::Categories => Focus::Apps,
+                    Focus::Apps => Focus::Categories,
+                };
+            }
+            KeyCode::Enter => match self.focus {
+                Focus::Categories => self.focus = Focus::Apps,
+                Focus::Apps => self.set_selected_default(),
+            },
+            _ => {}
+        }
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let (state, len) = match self.focus {
+            Focus::Categories => (&self.category_state, self.categories.len()),
+            Focus::Apps => (&self.app_state, self.apps().len()),
+        };
+        if len == 0 {
+            return;
+        }
+
+        let current = state.selected().unwrap_or(0) as isize;
+        let selected = current.saturating_add(delta).clamp(0, len as isize - 1) as usize;
+        match self.focus {
+            Focus::Categories => {
+                self.category_state.select(Some(selected));
+                self.app_state.select(Some(0));
+            }
+            Focus::Apps => self.app_state.select(Some(selected)),
+        }
+    }
+
+    /// Make the selected application the default for every primary type of
+    /// the selected category and save. Like the window, this includes the
+    /// related types the app declares when `set_related_types` is on.
+    fn set_selected_default(&mut self) {
+        if self.config.settings.locked {
+            self.status = Some("Default applications are locked".to_string());
+            return;
+        }
+
+        let Some(app) = self
+            .apps()
+            .get(self.app_state.selected().unwrap_or(0))
+            .copied()
+        else {
+            return;
+        };
+        let (app_id, app_name) = (app.id.clone(), app.name.clone());
+
+        let mut mimes = self.config.settings.primary_mime_types(self.category());
+        if mimes.is_empty() {
+            self.status = Some(format!(
+                "{} has no MIME types to set a default for",
+                self.category().display_name()
+            ));
+            return;
+        }
+        if self.config.settings.set_related_types {
+            let related: Vec<String> = self
+                .category()
+                .related_mime_types()
+                .into_iter()
+                .filter(|mime| !mimes.iter().any(|m| m == mime))
+                .filter(|mime| {
+                    self.registry
+                        .get_app(&app_id)
+                        .is_some_and(|app| app.supports_mime_type(mime))
+                })
+                .map(str::to_string)
+                .collect();
+            mimes.extend(related);
+        }
+
+        let result = mimes
+            .iter()
+            .try_for_each(|mime| self.config.set_default(mime, &app_id))
+            .and_then(|()| self.config.save());
+        self.status = Some(match result {
+            Ok(()) => format!(
+                "{} is now the default {}",
+                app_name,
+                self.category().display_name()
+            ),
+            Err(e) => {
+                tracing::error!("Failed to set default: {:#}", e);
+                format!("Failed to set default: {:#}", e)
+            }
+        });
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [sidebar, content] =
+            Layout::horizontal([Constraint::Length(24), Constraint::Min(0)]).areas(main);
+        let [current, available] =
+            Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(content);
+
+        let highlight = |focused: bool| {
+            if focused {
+                Style::new().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::new().add_modifier(Modifier::BOLD)
+            }
+        };
+
+        // Categories, marked by how many of their types have a default
+        let items: Vec<ListItem> = self
+            .categories
+            .iter()
+            .map(|category| {
+                let mimes = self.config.settings.primary_mime_types(category);
+                let configured = mimes
+                    .iter()
+                    .filter(|mime| self.config.get_default(mime).is_some())
+                    .count();
+                let mark = match configured {
+                    0 => ' ',
+                    n if n == mimes.len() => '✓',
+                    _ => '~',
+                };
+                ListItem::new(format!("{} {}", mark, category.display_name()))
+            })
+            .collect();
+        let title = if self.config.settings.locked {
+            "Categories (locked)"
+        } else {
+            "Categories"
+        };
+        let list = List::new(items)
+            .block(Block::bordered().title(title))
+            .highlight_style(highlight(self.focus == Focus::Categories));
+        frame.render_stateful_widget(list, sidebar, &mut self.category_state);
+
+        // Current default, from the first primary type that has one
+        let category = self.category();
+        let primary = self.config.settings.primary_mime_types(category);
+        let current_app = primary
+            .iter()
+            .filter_map(|mime| self.config.get_default(mime))
+            .find_map(|id| self.registry.get_app(id));
+        let current_text = match current_app {
+            Some(app) => format!("{} ({})", app.name, app.id),
+            None => "None".to_string(),
+        };
+        frame.render_widget(
+            Paragraph::new(current_text).block(Block::bordered().title("Current Default")),
+            current,
+        );
+
+        // Available applications: ● default for all types, ◐ for some
+        let items: Vec<ListItem> = self
+            .apps()
+            .iter()
+            .map(|app| {
+                let count = self.config.default_count(&primary, &app.id);
+                let mark = match count {
+                    0 => ' ',
+                    n if n == primary.len() => '●',
+                    _ => '◐',
+                };
+                ListItem::new(format!("{} {}  {}", mark, app.name, app.id))
+            })
+            .collect();
+        let empty = items.is_empty();
+        let list = List::new(items)
+            .block(Block::bordered().title("Available Applications"))
+            .highlight_style(highlight(self.focus == Focus::Apps));
+        if empty {
+            frame.render_widget(
+                Paragraph::new("No applications found for this category")
+                    .block(Block::bordered().title("Available Applications")),
+                available,
+            );
+        } else {
+            frame.render_stateful_widget(list, available, &mut self.app_state);
+        }
+
+        let status_text = self.status.as_deref().unwrap_or(HELP);
+        frame.render_widget(Line::from(status_text), status);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn demo_app(start: Option<AppCategory>) -> TuiApp {
+        TuiApp::new(MimeAppsConfig::demo(), AppRegistry::demo(), start)
+    }
+
+    #[test]
+    fn test_set_default_from_keys() {
+        let mut app = demo_app(Some(AppCategory::WebBrowser));
+        assert_eq!(app.category(), &AppCategory::WebBrowser);
+
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.focus, Focus::Apps);
+
+        let target = app
+            .apps()
+            .iter()
+            .position(|entry| &*entry.id != "firefox.desktop")
+            .unwrap();
+        for _ in 0..target {
+            app.handle_key(KeyCode::Down);
+        }
+        let target_id = app.apps()[target].id.clone();
+        app.handle_key(KeyCode::Enter);
+
+        for mime in AppCategory::WebBrowser.primary_mime_types() {
+            assert_eq!(app.config.get_default(mime), Some(&*target_id));
+        }
+
+        app.config.settings.locked = true;
+        app.handle_key(KeyCode::Up);
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(
+            app.status.as_deref(),
+            Some("Default applications are locked")
+        );
+    }
+
+    #[test]
+    fn test_home_and_end() {
+        let mut app = demo_app(None);
+        let last = app.categories.len() - 1;
+
+        app.handle_key(KeyCode::End);
+        assert_eq!(app.category_state.selected(), Some(last));
+        app.handle_key(KeyCode::Home);
+        assert_eq!(app.category_state.selected(), Some(0));
+    }
+}
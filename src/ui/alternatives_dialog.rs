@@ -0,0 +1,209 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Button, Label, ListBox, Orientation, ScrolledWindow, Window};
+
+use crate::config::MimeAppsConfig;
+use crate::desktop::alternatives::{unused_alternatives, Alternatives};
+use crate::desktop::discovery::AppRegistry;
+use crate::desktop::entry::AppEntry;
+use crate::ui::confirm_dialog::active_window;
+use crate::ui::saving::save_in_background;
+use crate::utils::icons::{app_icon, category_icon};
+
+/// What the sections of the dialog show and act on
+struct AlternativesView {
+    registry: Rc<AppRegistry>,
+    config: Rc<RefCell<MimeAppsConfig>>,
+    sections: GtkBox,
+    on_changed: Box<dyn Fn()>,
+}
+
+/// Show the installed applications that aren't the default for anything
+/// they open, under each category they could serve, with a button making
+/// one the category's default. `on_changed` runs after a default was set.
+pub fn show_alternatives_dialog<F>(
+    registry: Rc<AppRegistry>,
+    config: Rc<RefCell<MimeAppsConfig>>,
+    on_changed: F,
+) where
+    F: Fn() + 'static,
+{
+    let dialog = Window::builder()
+        .title("Alternatives")
+        .modal(true)
+        .default_width(600)
+        .default_height(560)
+        .build();
+    dialog.set_transient_for(active_window().as_ref());
+
+    let content = GtkBox::new(Orientation::Vertical, 12);
+    content.set_margin_start(18);
+    content.set_margin_end(18);
+    content.set_margin_top(18);
+    content.set_margin_bottom(18);
+
+    let intro = Label::new(Some(
+        "These installed applications could open files or links for you, \
+         but aren't the default for anything yet.",
+    ));
+    intro.set_wrap(true);
+    intro.set_xalign(0.0);
+    intro.add_css_class("dim-label");
+    content.append(&intro);
+
+    let sections = GtkBox::new(Orientation::Vertical, 18);
+    let scrolled = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .vexpand(true)
+        .child(&sections)
+        .build();
+    content.append(&scrolled);
+
+    let view = Rc::new(AlternativesView {
+        registry,
+        config,
+        sections,
+        on_changed: Box::new(on_changed),
+    });
+    fill_sections(&view);
+    // The rows only hold weak references, so the dialog keeps the view
+    dialog.connect_destroy(move |_| {
+        let _ = &view;
+    });
+
+    let close_btn = Button::with_label("Close");
+    close_btn.set_halign(gtk::Align::End);
+    let dialog_clone = dialog.clone();
+    close_btn.connect_clicked(move |_| dialog_clone.close());
+    content.append(&close_btn);
+
+    dialog.set_child(Some(&content));
+    dialog.present();
+}
+
+/// Replace the sections with one per category that has alternatives
+fn fill_sections(view: &Rc<AlternativesView>) {
+    let sections = &view.sections;
+    while let Some(child) = sections.first_child() {
+        sections.remove(&child);
+    }
+
+    let config = view.config.borrow();
+    let alternatives = unused_alternatives(&view.registry, &config);
+    if alternatives.is_empty() {
+        let empty = Label::new(Some("Every application is the default for something"));
+        empty.set_vexpand(true);
+        empty.add_css_class("dim-label");
+        sections.append(&empty);
+        return;
+    }
+
+    for category in &alternatives {
+        sections.append(&create_section(view, category, config.settings.locked));
+    }
+}
+
+/// The category's name and a row per alternative
+fn create_section(
+    view: &Rc<AlternativesView>,
+    alternatives: &Alternatives,
+    read_only: bool,
+) -> GtkBox {
+    let section = GtkBox::new(Orientation::Vertical, 8);
+
+    let header = GtkBox::new(Orientation::Horizontal, 8);
+    header.append(&category_icon(alternatives.category.icon_name(), 24));
+    let title = Label::new(Some(alternatives.category.display_name()));
+    title.add_css_class("heading");
+    header.append(&title);
+    section.append(&header);
+
+    let list = ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    for app in &alternatives.apps {
+        list.append(&create_app_row(view, alternatives, app, read_only));
+    }
+    section.append(&list);
+    section
+}
+
+fn create_app_row(
+    view: &Rc<AlternativesView>,
+    alternatives: &Alternatives,
+    app: &AppEntry,
+    read_only: bool,
+) -> GtkBox {
+    let hbox = GtkBox::new(Orientation::Horizontal, 12);
+    hbox.set_margin_start(12);
+    hbox.set_margin_end(12);
+    hbox.set_margin_top(6);
+    hbox.set_margin_bottom(6);
+    hbox.append(&app_icon(app.icon.as_deref(), 32));
+
+    let text_box = GtkBox::new(Orientation::Vertical, 2);
+    text_box.set_hexpand(true);
+    let name_label = Label::new(Some(&app.name));
+    name_label.set_halign(gtk::Align::Start);
+    text_box.append(&name_label);
+    if let Some(description) = app.comment.as_ref().or(app.generic_name.as_ref()) {
+        let description_label = Label::new(Some(description));
+        description_label.set_halign(gtk::Align::Start);
+        description_label.set_wrap(true);
+        description_label.add_css_class("dim-label");
+        description_label.add_css_class("caption");
+        text_box.append(&description_label);
+    }
+    hbox.append(&text_box);
+
+    if read_only {
+        return hbox;
+    }
+
+    let btn = Button::with_label("Set as Default");
+    btn.set_valign(gtk::Align::Center);
+    btn.set_tooltip_text(Some(&format!(
+        "Open {} with {}",
+        alternatives.mime_types.join(", "),
+        app.name
+    )));
+    let weak_view = Rc::downgrade(view);
+    let category = alternatives.category.clone();
+    let mimes = alternatives.mime_types.clone();
+    let app_id = app.id.clone();
+    btn.connect_clicked(move |_| {
+        let Some(view) = weak_view.upgrade() else {
+            return;
+        };
+
+        {
+            let mut config = view.config.borrow_mut();
+            // Like the category page, with the related types it declares
+            let related: Vec<&str> = if config.settings.set_related_types {
+                let app = view.registry.get_app(&app_id);
+                category
+                    .related_mime_types()
+                    .into_iter()
+                    .filter(|mime| app.is_some_and(|app| app.supports_mime_type(mime)))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            for mime in mimes.iter().map(String::as_str).chain(related) {
+                if let Err(e) = config.set_default(mime, &app_id) {
+                    tracing::error!("Failed to set default for {}: {:#}", mime, e);
+                    return;
+                }
+            }
+        }
+        tracing::info!("Set {} as default for {}", app_id, category.display_name());
+        save_in_background(&view.config, || {});
+        (view.on_changed)();
+        fill_sections(&view);
+    });
+    hbox.append(&btn);
+    hbox
+}
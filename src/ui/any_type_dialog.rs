@@ -0,0 +1,249 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gtk::prelude::*;
+use gtk::{
+    Box as GtkBox, Button, CheckButton, Image, Label, ListBox, Orientation, ScrolledWindow, Window,
+};
+
+use crate::config::MimeAppsConfig;
+use crate::desktop::discovery::AppRegistry;
+use crate::desktop::entry::AppEntry;
+use crate::ui::confirm_dialog::active_window;
+use crate::ui::mime_entry::mime_entry;
+use crate::ui::saving::save_in_background;
+use crate::utils::icons::app_icon;
+
+/// What the application list shows and acts on
+struct HandlerList {
+    registry: Rc<AppRegistry>,
+    config: Rc<RefCell<MimeAppsConfig>>,
+    list: ListBox,
+    current_label: Label,
+    show_all: CheckButton,
+    /// The type typed in last, once it was valid
+    mime: RefCell<Option<String>>,
+    on_changed: Box<dyn Fn()>,
+}
+
+/// Show a dialog for setting the default application of any MIME type or
+/// URL scheme, including the many no category lists. The type entry
+/// suggests those the MIME database or an installed app knows.
+/// `on_changed` runs after a default was set.
+pub fn show_any_type_dialog<F>(
+    registry: Rc<AppRegistry>,
+    config: Rc<RefCell<MimeAppsConfig>>,
+    on_changed: F,
+) where
+    F: Fn() + 'static,
+{
+    let dialog = Window::builder()
+        .title("Set Default for Any Type")
+        .modal(true)
+        .default_width(560)
+        .default_height(560)
+        .build();
+    dialog.set_transient_for(active_window().as_ref());
+
+    let content = GtkBox::new(Orientation::Vertical, 12);
+    content.set_margin_start(18);
+    content.set_margin_end(18);
+    content.set_margin_top(18);
+    content.set_margin_bottom(18);
+
+    let intro = Label::new(Some(
+        "Type a MIME type, such as application/x-sqlite3, or a URL scheme as \
+         x-scheme-handler/<scheme>, then pick the application that opens it.",
+    ));
+    intro.set_wrap(true);
+    intro.set_xalign(0.0);
+    intro.add_css_class("dim-label");
+    content.append(&intro);
+
+    let entry_box = GtkBox::new(Orientation::Horizontal, 6);
+    let type_entry = mime_entry(registry.known_mime_types());
+    entry_box.append(&type_entry);
+    let show_btn = Button::with_label("Show Applications");
+    entry_box.append(&show_btn);
+    content.append(&entry_box);
+
+    let error_label = Label::new(None);
+    error_label.set_wrap(true);
+    error_label.set_xalign(0.0);
+    error_label.add_css_class("error");
+    error_label.set_visible(false);
+    content.append(&error_label);
+
+    let current_label = Label::new(None);
+    current_label.set_xalign(0.0);
+    current_label.set_wrap(true);
+    current_label.add_css_class("heading");
+    content.append(&current_label);
+
+    let list = ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    let scrolled = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .vexpand(true)
+        .child(&list)
+        .build();
+    content.append(&scrolled);
+
+    let show_all = CheckButton::with_label("Include applications that don't declare the type");
+    content.append(&show_all);
+
+    let handlers = Rc::new(HandlerList {
+        registry,
+        config,
+        list,
+        current_label,
+        show_all: show_all.clone(),
+        mime: RefCell::new(None),
+        on_changed: Box::new(on_changed),
+    });
+
+    let show_type = {
+        let handlers = Rc::clone(&handlers);
+        let type_entry = type_entry.clone();
+        move || {
+            let text = type_entry.text();
+            let mime = text.trim();
+            if let Err(e) = MimeAppsConfig::validate_mime_type(mime) {
+                error_label.set_text(&format!("{:#}", e));
+                error_label.set_visible(true);
+                return;
+            }
+            error_label.set_visible(false);
+            handlers.mime.replace(Some(mime.to_string()));
+            fill_list(&handlers);
+        }
+    };
+    let show_clone = show_type.clone();
+    type_entry.connect_activate(move |_| show_clone());
+    show_btn.connect_clicked(move |_| show_type());
+
+    let handlers_clone = Rc::clone(&handlers);
+    show_all.connect_toggled(move |_| fill_list(&handlers_clone));
+
+    let close_btn = Button::with_label("Close");
+    close_btn.set_halign(gtk::Align::End);
+    let dialog_clone = dialog.clone();
+    close_btn.connect_clicked(move |_| dialog_clone.close());
+    content.append(&close_btn);
+
+    dialog.set_child(Some(&content));
+    dialog.present();
+}
+
+/// List the applications for the chosen type: those declaring it first,
+/// then, if asked for, every other one
+fn fill_list(handlers: &Rc<HandlerList>) {
+    let list = &handlers.list;
+    while let Some(child) = list.first_child() {
+        list.remove(&child);
+    }
+    let Some(mime) = handlers.mime.borrow().clone() else {
+        handlers.current_label.set_text("");
+        return;
+    };
+
+    let config = handlers.config.borrow();
+    let current = config.get_default(&mime).map(str::to_string);
+    let current_name = current.as_deref().map(|id| {
+        handlers
+            .registry
+            .get_app(id)
+            .map_or(id.to_string(), |app| app.name.clone())
+    });
+    handlers.current_label.set_text(&format!(
+        "Default for {}: {}",
+        mime,
+        current_name.as_deref().unwrap_or("none")
+    ));
+
+    let mut apps = handlers.registry.apps_for_mime(&mime);
+    if handlers.show_all.is_active() {
+        let declaring: Vec<&str> = apps.iter().map(|app| &*app.id).collect();
+        let others: Vec<&AppEntry> = handlers
+            .registry
+            .all_apps()
+            .into_iter()
+            .filter(|app| !declaring.contains(&&*app.id))
+            .collect();
+        apps.extend(others);
+    }
+
+    if apps.is_empty() {
+        let empty = Label::new(Some("No installed application declares this type"));
+        empty.set_margin_top(12);
+        empty.set_margin_bottom(12);
+        empty.add_css_class("dim-label");
+        list.append(&empty);
+        return;
+    }
+    let read_only = config.settings.locked;
+    for app in apps {
+        let is_current = current.as_deref() == Some(&*app.id);
+        list.append(&create_app_row(handlers, app, &mime, is_current, read_only));
+    }
+}
+
+/// A row with the app and a button making it the type's default
+fn create_app_row(
+    handlers: &Rc<HandlerList>,
+    app: &AppEntry,
+    mime: &str,
+    is_current: bool,
+    read_only: bool,
+) -> GtkBox {
+    let hbox = GtkBox::new(Orientation::Horizontal, 12);
+    hbox.set_margin_start(12);
+    hbox.set_margin_end(12);
+    hbox.set_margin_top(6);
+    hbox.set_margin_bottom(6);
+    hbox.append(&app_icon(app.icon.as_deref(), 32));
+
+    let text_box = GtkBox::new(Orientation::Vertical, 2);
+    text_box.set_hexpand(true);
+    let name_label = Label::new(Some(&app.name));
+    name_label.set_halign(gtk::Align::Start);
+    text_box.append(&name_label);
+    let id_label = Label::new(Some(&app.id));
+    id_label.set_halign(gtk::Align::Start);
+    id_label.add_css_class("dim-label");
+    id_label.add_css_class("caption");
+    text_box.append(&id_label);
+    hbox.append(&text_box);
+
+    if is_current {
+        let check = Image::from_icon_name("object-select-symbolic");
+        check.set_tooltip_text(Some("Current default"));
+        hbox.append(&check);
+        return hbox;
+    }
+    if read_only {
+        return hbox;
+    }
+
+    let btn = Button::with_label("Set as Default");
+    btn.set_valign(gtk::Align::Center);
+    let weak_handlers = Rc::downgrade(handlers);
+    let (mime, app_id) = (mime.to_string(), app.id.clone());
+    btn.connect_clicked(move |_| {
+        let Some(handlers) = weak_handlers.upgrade() else {
+            return;
+        };
+        if let Err(e) = handlers.config.borrow_mut().set_default(&mime, &app_id) {
+            tracing::error!("Failed to set default for {}: {:#}", mime, e);
+            return;
+        }
+        tracing::info!("Set {} as default for {}", app_id, mime);
+        save_in_background(&handlers.config, || {});
+        (handlers.on_changed)();
+        fill_list(&handlers);
+    });
+    hbox.append(&btn);
+    hbox
+}
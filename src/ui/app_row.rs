@@ -1,6 +1,9 @@
 use gtk::prelude::*;
-use gtk::{Box as GtkBox, Button, Image, Label, ListBoxRow, Orientation};
+use gtk::{
+    Box as GtkBox, Button, Image, Label, ListBoxRow, MenuButton, Orientation, Popover, ToggleButton,
+};
 
+use crate::desktop::discovery::MatchReason;
 use crate::desktop::entry::AppEntry;
 use crate::utils::icons::app_icon;
 
@@ -12,16 +15,115 @@ fn set_margins(widget: &impl WidgetExt, margin: i32) {
     widget.set_margin_bottom(margin);
 }
 
+/// Whether an application is the default for a category's MIME types
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefaultState {
+    /// Not the default for any of them
+    None,
+    /// The default for some but not all of them
+    Partial { count: usize, total: usize },
+    /// The default for all of them
+    Full,
+}
+
+impl DefaultState {
+    /// State for an app that is the default for `count` of `total` types
+    pub fn new(count: usize, total: usize) -> Self {
+        match count {
+            0 => Self::None,
+            count if count >= total => Self::Full,
+            count => Self::Partial { count, total },
+        }
+    }
+
+    /// Short description such as "Default for 2 of 6 types"
+    pub fn partial_text(count: usize, total: usize) -> String {
+        format!("Default for {} of {} types", count, total)
+    }
+}
+
+/// Small indicator for a partial default, pointing at the button that completes it
+pub fn partial_indicator(count: usize, total: usize) -> Label {
+    let label = Label::new(Some(&format!("{}/{}", count, total)));
+    label.add_css_class("warning");
+    label.add_css_class("caption");
+    label.set_tooltip_text(Some(&format!(
+        "{}; set as default to use it for all of them",
+        DefaultState::partial_text(count, total)
+    )));
+    label
+}
+
+/// Hidden dot marking a running app, shown by `set_running`
+pub fn running_indicator() -> Label {
+    let dot = Label::new(Some("\u{25cf}"));
+    dot.add_css_class("accent");
+    dot.add_css_class("caption");
+    dot.set_valign(gtk::Align::Center);
+    dot.set_tooltip_text(Some("Running"));
+    dot.set_visible(false);
+    dot
+}
+
+/// Hidden "Why is this listed?" button, filled in by `set_match_reasons`
+pub fn match_reasons_button() -> MenuButton {
+    let btn = MenuButton::new();
+    btn.set_icon_name("dialog-information-symbolic");
+    btn.set_tooltip_text(Some("Why is this listed?"));
+    btn.add_css_class("flat");
+    btn.set_valign(gtk::Align::Center);
+    btn.set_visible(false);
+    btn
+}
+
+/// Give a `match_reasons_button` a popover listing the reasons, showing it
+pub fn set_match_reasons(btn: &MenuButton, reasons: &[MatchReason]) {
+    if reasons.is_empty() {
+        return;
+    }
+
+    let content = GtkBox::new(Orientation::Vertical, 6);
+    set_margins(&content, 6);
+    let heading = Label::new(Some("Listed because it:"));
+    heading.set_halign(gtk::Align::Start);
+    heading.add_css_class("heading");
+    content.append(&heading);
+    for reason in reasons {
+        let label = Label::new(Some(&reason.description()));
+        label.set_halign(gtk::Align::Start);
+        label.set_selectable(true);
+        content.append(&label);
+    }
+
+    // Categories with many types would otherwise make a very tall popover
+    let scrolled = gtk::ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .propagate_natural_height(true)
+        .propagate_natural_width(true)
+        .max_content_height(320)
+        .child(&content)
+        .build();
+
+    let popover = Popover::new();
+    popover.set_child(Some(&scrolled));
+    btn.set_popover(Some(&popover));
+    btn.set_visible(true);
+}
+
 /// A row displaying an application with Set as Default button
 pub struct AppRow {
     pub widget: ListBoxRow,
     pub app_id: String,
     set_default_btn: Option<Button>,
     test_btn: Button,
+    test_file_btn: Button,
+    exclude_btn: Button,
+    reasons_btn: MenuButton,
+    running_dot: Label,
 }
 
 impl AppRow {
-    pub fn new(app: &AppEntry, is_current_default: bool) -> Self {
+    pub fn new(app: &AppEntry, state: DefaultState) -> Self {
         let hbox = GtkBox::new(Orientation::Horizontal, 12);
         set_margins(&hbox, 12);
 
@@ -33,10 +135,14 @@ impl AppRow {
         let text_box = GtkBox::new(Orientation::Vertical, 4);
         text_box.set_hexpand(true);
 
+        let name_box = GtkBox::new(Orientation::Horizontal, 6);
         let name_label = Label::new(Some(&app.name));
         name_label.set_halign(gtk::Align::Start);
         name_label.add_css_class("heading");
-        text_box.append(&name_label);
+        name_box.append(&name_label);
+        let running_dot = running_indicator();
+        name_box.append(&running_dot);
+        text_box.append(&name_box);
 
         if let Some(comment) = &app.comment {
             let comment_label = Label::new(Some(comment));
@@ -54,14 +160,21 @@ impl AppRow {
 
         hbox.append(&text_box);
 
+        // Match explanation, shown once reasons are set
+        let reasons_btn = match_reasons_button();
+        hbox.append(&reasons_btn);
+
         // Current default indicator or Set Default button
-        let set_default_btn = if is_current_default {
+        let set_default_btn = if state == DefaultState::Full {
             let check = Image::from_icon_name("emblem-ok-symbolic");
             check.add_css_class("success");
             check.set_tooltip_text(Some("Current default"));
             hbox.append(&check);
             None
         } else {
+            if let DefaultState::Partial { count, total } = state {
+                hbox.append(&partial_indicator(count, total));
+            }
             let btn = Button::with_label("Set as Default");
             btn.add_css_class("suggested-action");
             hbox.append(&btn);
@@ -74,17 +187,55 @@ impl AppRow {
         test_btn.add_css_class("flat");
         hbox.append(&test_btn);
 
+        // Test-with-file button, shown once a handler is connected
+        let test_file_btn = Button::from_icon_name("document-open-symbolic");
+        test_file_btn.set_tooltip_text(Some("Test with file…"));
+        test_file_btn.add_css_class("flat");
+        test_file_btn.set_visible(false);
+        hbox.append(&test_file_btn);
+
+        // Exclusion button, shown once a handler is connected
+        let exclude_btn = Button::from_icon_name("list-remove-symbolic");
+        exclude_btn.set_tooltip_text(Some("Not for this category"));
+        exclude_btn.add_css_class("flat");
+        exclude_btn.set_visible(false);
+        hbox.append(&exclude_btn);
+
         let row = ListBoxRow::new();
         row.set_child(Some(&hbox));
 
         Self {
             widget: row,
-            app_id: app.id.clone(),
+            app_id: app.id.to_string(),
             set_default_btn,
             test_btn,
+            test_file_btn,
+            exclude_btn,
+            reasons_btn,
+            running_dot,
         }
     }
 
+    /// Explain why the app is listed, from `AppRegistry::match_reasons`
+    pub fn set_match_reasons(&self, reasons: &[MatchReason]) {
+        set_match_reasons(&self.reasons_btn, reasons);
+    }
+
+    /// Mark the app as running
+    pub fn set_running(&self) {
+        self.running_dot.set_visible(true);
+    }
+
+    /// Hide the buttons, leaving only the default state visible
+    pub fn set_read_only(&self) {
+        if let Some(btn) = &self.set_default_btn {
+            btn.set_visible(false);
+        }
+        self.test_btn.set_visible(false);
+        self.test_file_btn.set_visible(false);
+        self.exclude_btn.set_visible(false);
+    }
+
     /// Connect a callback for the Set as Default button
     pub fn connect_set_default<F>(&self, callback: F)
     where
@@ -109,15 +260,41 @@ impl AppRow {
             callback(app_id.clone());
         });
     }
+
+    /// Connect a callback for the "Test with file…" button, showing it
+    pub fn connect_test_with_file<F>(&self, callback: F)
+    where
+        F: Fn(String) + Clone + 'static,
+    {
+        let app_id = self.app_id.clone();
+        self.test_file_btn.set_visible(true);
+        self.test_file_btn.connect_clicked(move |_| {
+            callback(app_id.clone());
+        });
+    }
+
+    /// Connect a callback for the "Not for this category" button, showing it
+    pub fn connect_exclude<F>(&self, callback: F)
+    where
+        F: Fn(String) + Clone + 'static,
+    {
+        let app_id = self.app_id.clone();
+        self.exclude_btn.set_visible(true);
+        self.exclude_btn.connect_clicked(move |_| {
+            callback(app_id.clone());
+        });
+    }
 }
 
 /// A compact row for the current default display
 pub struct CurrentDefaultRow {
     pub widget: GtkBox,
+    /// Only when there is a default to pin
+    pin_btn: Option<ToggleButton>,
 }
 
 impl CurrentDefaultRow {
-    pub fn new(app: Option<&AppEntry>) -> Self {
+    pub fn new(app: Option<&AppEntry>, state: DefaultState) -> Self {
         let hbox = GtkBox::new(Orientation::Horizontal, 12);
         set_margins(&hbox, 12);
         hbox.add_css_class("card");
@@ -144,10 +321,23 @@ impl CurrentDefaultRow {
 
             hbox.append(&text_box);
 
-            let check = Image::from_icon_name("emblem-ok-symbolic");
-            check.add_css_class("success");
-            check.set_pixel_size(24);
-            hbox.append(&check);
+            if let DefaultState::Partial { count, total } = state {
+                // Only some of the category's types open with this app
+                let partial = GtkBox::new(Orientation::Horizontal, 6);
+                partial.set_valign(gtk::Align::Center);
+                let warning = Image::from_icon_name("dialog-warning-symbolic");
+                warning.add_css_class("warning");
+                partial.append(&warning);
+                let label = Label::new(Some(&DefaultState::partial_text(count, total)));
+                label.add_css_class("warning");
+                partial.append(&label);
+                hbox.append(&partial);
+            } else {
+                let check = Image::from_icon_name("emblem-ok-symbolic");
+                check.add_css_class("success");
+                check.set_pixel_size(24);
+                hbox.append(&check);
+            }
         } else {
             let label = Label::new(Some("No default application set"));
             label.add_css_class("dim-label");
@@ -155,6 +345,36 @@ impl CurrentDefaultRow {
             hbox.append(&label);
         }
 
-        Self { widget: hbox }
+        // Pin button, shown once a handler is connected
+        let pin_btn = app.map(|_| {
+            let btn = ToggleButton::new();
+            btn.set_icon_name("view-pin-symbolic");
+            btn.set_tooltip_text(Some(
+                "Pin: restore these defaults if another program changes them",
+            ));
+            btn.set_valign(gtk::Align::Center);
+            btn.add_css_class("flat");
+            btn.set_visible(false);
+            hbox.append(&btn);
+            btn
+        });
+
+        Self {
+            widget: hbox,
+            pin_btn,
+        }
+    }
+
+    /// Show the pin button in the given state and connect a callback for
+    /// when it is toggled
+    pub fn connect_pin<F>(&self, pinned: bool, callback: F)
+    where
+        F: Fn(bool) + 'static,
+    {
+        if let Some(btn) = &self.pin_btn {
+            btn.set_active(pinned);
+            btn.set_visible(true);
+            btn.connect_toggled(move |btn| callback(btn.is_active()));
+        }
     }
 }
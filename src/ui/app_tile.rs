@@ -0,0 +1,191 @@
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Button, FlowBoxChild, Image, Label, MenuButton, Orientation};
+
+use crate::desktop::discovery::MatchReason;
+use crate::desktop::entry::AppEntry;
+use crate::ui::app_row::{
+    match_reasons_button, partial_indicator, running_indicator, set_match_reasons, DefaultState,
+};
+use crate::utils::icons::app_icon;
+
+/// Helper to set all margins at once
+fn set_margins(widget: &impl WidgetExt, margin: i32) {
+    widget.set_margin_start(margin);
+    widget.set_margin_end(margin);
+    widget.set_margin_top(margin);
+    widget.set_margin_bottom(margin);
+}
+
+/// A grid tile displaying an application with a large icon, for the grid view
+pub struct AppTile {
+    pub widget: FlowBoxChild,
+    pub app_id: String,
+    set_default_btn: Option<Button>,
+    test_btn: Button,
+    test_file_btn: Button,
+    exclude_btn: Button,
+    reasons_btn: MenuButton,
+    running_dot: Label,
+}
+
+impl AppTile {
+    pub fn new(app: &AppEntry, state: DefaultState) -> Self {
+        let vbox = GtkBox::new(Orientation::Vertical, 8);
+        set_margins(&vbox, 12);
+        vbox.add_css_class("card");
+
+        // App icon
+        let icon = app_icon(app.icon.as_deref(), 64);
+        icon.set_margin_top(8);
+        vbox.append(&icon);
+
+        // Name, wrapped over at most two lines
+        let name_label = Label::new(Some(&app.name));
+        name_label.add_css_class("heading");
+        name_label.set_wrap(true);
+        name_label.set_wrap_mode(gtk::pango::WrapMode::WordChar);
+        name_label.set_lines(2);
+        name_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+        name_label.set_justify(gtk::Justification::Center);
+        name_label.set_max_width_chars(14);
+        name_label.set_vexpand(true);
+        name_label.set_valign(gtk::Align::Start);
+        vbox.append(&name_label);
+
+        if let Some(description) = app.comment.as_ref().or(app.generic_name.as_ref()) {
+            vbox.set_tooltip_text(Some(description));
+        }
+
+        let buttons = GtkBox::new(Orientation::Horizontal, 4);
+        buttons.set_halign(gtk::Align::Center);
+        buttons.set_margin_bottom(4);
+
+        let running_dot = running_indicator();
+        buttons.append(&running_dot);
+
+        // Current default indicator or Set Default button
+        let set_default_btn = if state == DefaultState::Full {
+            let check = Image::from_icon_name("emblem-ok-symbolic");
+            check.add_css_class("success");
+            check.set_tooltip_text(Some("Current default"));
+            buttons.append(&check);
+            None
+        } else {
+            if let DefaultState::Partial { count, total } = state {
+                buttons.append(&partial_indicator(count, total));
+            }
+            let btn = Button::with_label("Set as Default");
+            btn.add_css_class("suggested-action");
+            buttons.append(&btn);
+            Some(btn)
+        };
+
+        // Test button
+        let test_btn = Button::from_icon_name("media-playback-start-symbolic");
+        test_btn.set_tooltip_text(Some("Launch this application"));
+        test_btn.add_css_class("flat");
+        buttons.append(&test_btn);
+
+        // Test-with-file button, shown once a handler is connected
+        let test_file_btn = Button::from_icon_name("document-open-symbolic");
+        test_file_btn.set_tooltip_text(Some("Test with file…"));
+        test_file_btn.add_css_class("flat");
+        test_file_btn.set_visible(false);
+        buttons.append(&test_file_btn);
+
+        // Exclusion button, shown once a handler is connected
+        let exclude_btn = Button::from_icon_name("list-remove-symbolic");
+        exclude_btn.set_tooltip_text(Some("Not for this category"));
+        exclude_btn.add_css_class("flat");
+        exclude_btn.set_visible(false);
+        buttons.append(&exclude_btn);
+
+        // Match explanation, shown once reasons are set
+        let reasons_btn = match_reasons_button();
+        buttons.append(&reasons_btn);
+
+        vbox.append(&buttons);
+
+        let tile = FlowBoxChild::new();
+        tile.set_child(Some(&vbox));
+
+        Self {
+            widget: tile,
+            app_id: app.id.to_string(),
+            set_default_btn,
+            test_btn,
+            test_file_btn,
+            exclude_btn,
+            reasons_btn,
+            running_dot,
+        }
+    }
+
+    /// Explain why the app is listed, from `AppRegistry::match_reasons`
+    pub fn set_match_reasons(&self, reasons: &[MatchReason]) {
+        set_match_reasons(&self.reasons_btn, reasons);
+    }
+
+    /// Mark the app as running
+    pub fn set_running(&self) {
+        self.running_dot.set_visible(true);
+    }
+
+    /// Hide the buttons, leaving only the default state visible
+    pub fn set_read_only(&self) {
+        if let Some(btn) = &self.set_default_btn {
+            btn.set_visible(false);
+        }
+        self.test_btn.set_visible(false);
+        self.test_file_btn.set_visible(false);
+        self.exclude_btn.set_visible(false);
+    }
+
+    /// Connect a callback for the Set as Default button
+    pub fn connect_set_default<F>(&self, callback: F)
+    where
+        F: Fn(String) + Clone + 'static,
+    {
+        if let Some(btn) = &self.set_default_btn {
+            let app_id = self.app_id.clone();
+            btn.connect_clicked(move |_| {
+                callback(app_id.clone());
+            });
+        }
+    }
+
+    /// Connect a callback for the Test button
+    pub fn connect_test<F>(&self, callback: F)
+    where
+        F: Fn(String) + Clone + 'static,
+    {
+        let app_id = self.app_id.clone();
+        self.test_btn.connect_clicked(move |_| {
+            callback(app_id.clone());
+        });
+    }
+
+    /// Connect a callback for the "Test with file…" button, showing it
+    pub fn connect_test_with_file<F>(&self, callback: F)
+    where
+        F: Fn(String) + Clone + 'static,
+    {
+        let app_id = self.app_id.clone();
+        self.test_file_btn.set_visible(true);
+        self.test_file_btn.connect_clicked(move |_| {
+            callback(app_id.clone());
+        });
+    }
+
+    /// Connect a callback for the "Not for this category" button, showing it
+    pub fn connect_exclude<F>(&self, callback: F)
+    where
+        F: Fn(String) + Clone + 'static,
+    {
+        let app_id = self.app_id.clone();
+        self.exclude_btn.set_visible(true);
+        self.exclude_btn.connect_clicked(move |_| {
+            callback(app_id.clone());
+        });
+    }
+}
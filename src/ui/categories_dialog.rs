@@ -0,0 +1,256 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gtk::prelude::*;
+use gtk::{
+    AlertDialog, Box as GtkBox, Button, CheckButton, Label, ListBox, Orientation, ScrolledWindow,
+    Window,
+};
+
+use crate::config::MimeAppsConfig;
+use crate::desktop::categories::{register_custom_categories, AppCategory, CustomCategory};
+use crate::desktop::discovery::AppRegistry;
+use crate::ui::category_editor::show_category_editor;
+use crate::ui::confirm_dialog::active_window;
+use crate::utils::icons::category_icon;
+
+/// What the rows of the dialog need
+struct CategoriesList {
+    config: Rc<RefCell<MimeAppsConfig>>,
+    registry: Rc<AppRegistry>,
+    list: ListBox,
+    checks: RefCell<Vec<CheckButton>>,
+    on_changed: Box<dyn Fn()>,
+    on_edited: Box<dyn Fn() -> Vec<AppCategory>>,
+}
+
+/// Show a checkbox per category for choosing which ones the sidebar lists,
+/// with buttons to add, edit and delete user-defined categories. Each
+/// change is saved right away. `on_changed` runs after a category was
+/// shown or hidden; `on_edited` after one was added, edited or deleted,
+/// returning the categories to list from then on.
+pub fn show_categories_dialog<F, G>(
+    config: Rc<RefCell<MimeAppsConfig>>,
+    registry: Rc<AppRegistry>,
+    categories: &[AppCategory],
+    on_changed: F,
+    on_edited: G,
+) where
+    F: Fn() + 'static,
+    G: Fn() -> Vec<AppCategory> + 'static,
+{
+    let dialog = Window::builder()
+        .title("Categories")
+        .modal(true)
+        .default_width(400)
+        .default_height(560)
+        .build();
+    dialog.set_transient_for(active_window().as_ref());
+
+    let content = GtkBox::new(Orientation::Vertical, 12);
+    content.set_margin_start(18);
+    content.set_margin_end(18);
+    content.set_margin_top(18);
+    content.set_margin_bottom(18);
+
+    let intro = Label::new(Some(
+        "Choose the categories shown in the sidebar. Hidden categories keep \
+         their defaults. Categories you add list the applications that \
+         declare any of their file types.",
+    ));
+    intro.set_wrap(true);
+    intro.set_xalign(0.0);
+    intro.add_css_class("dim-label");
+    content.append(&intro);
+
+    let list = ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    let categories_list = Rc::new(CategoriesList {
+        config,
+        registry,
+        list: list.clone(),
+        checks: RefCell::new(Vec::new()),
+        on_changed: Box::new(on_changed),
+        on_edited: Box::new(on_edited),
+    });
+    fill_list(&categories_list, categories);
+
+    let scrolled = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .vexpand(true)
+        .child(&list)
+        .build();
+    content.append(&scrolled);
+
+    let button_box = GtkBox::new(Orientation::Horizontal, 6);
+    let new_btn = Button::with_label("New Category…");
+    new_btn.set_hexpand(true);
+    new_btn.set_halign(gtk::Align::Start);
+    new_btn.connect_clicked(move |_| open_editor(&categories_list, None));
+    button_box.append(&new_btn);
+
+    let close_btn = Button::with_label("Close");
+    let dialog_clone = dialog.clone();
+    close_btn.connect_clicked(move |_| dialog_clone.close());
+    button_box.append(&close_btn);
+    content.append(&button_box);
+
+    dialog.set_child(Some(&content));
+    dialog.present();
+}
+
+/// Replace the rows with one per category
+fn fill_list(categories_list: &Rc<CategoriesList>, categories: &[AppCategory]) {
+    let list = &categories_list.list;
+    while let Some(child) = list.first_child() {
+        list.remove(&child);
+    }
+    categories_list.checks.borrow_mut().clear();
+
+    for category in categories {
+        list.append(&create_row(categories_list, category));
+    }
+}
+
+/// Let the window update, then list the categories it lists now
+fn refill_list(categories_list: &Rc<CategoriesList>) {
+    let categories = (categories_list.on_edited)();
+    fill_list(categories_list, &categories);
+}
+
+/// Show the editor for a new category, or a user-defined one, refilling
+/// the list once it is saved
+fn open_editor(categories_list: &Rc<CategoriesList>, category: Option<&CustomCategory>) {
+    let on_saved = {
+        let categories_list = Rc::clone(categories_list);
+        move || refill_list(&categories_list)
+    };
+    show_category_editor(
+        Rc::clone(&categories_list.config),
+        &categories_list.registry,
+        category,
+        on_saved,
+    );
+}
+
+fn create_row(categories_list: &Rc<CategoriesList>, category: &AppCategory) -> GtkBox {
+    let row_box = GtkBox::new(Orientation::Horizontal, 12);
+    row_box.set_margin_start(12);
+    row_box.set_margin_end(12);
+    row_box.set_margin_top(6);
+    row_box.set_margin_bottom(6);
+
+    row_box.append(&category_icon(category.icon_name(), 24));
+
+    let check = CheckButton::with_label(category.display_name());
+    check.set_active(
+        !categories_list
+            .config
+            .borrow()
+            .settings
+            .is_category_hidden(category),
+    );
+    check.set_hexpand(true);
+    row_box.append(&check);
+
+    if let AppCategory::Custom(custom) = category {
+        row_box.append(&create_edit_button(categories_list, custom));
+        row_box.append(&create_delete_button(categories_list, custom));
+    }
+
+    let category = category.clone();
+    let weak_list = Rc::downgrade(categories_list);
+    check.connect_toggled(move |check| {
+        let Some(categories_list) = weak_list.upgrade() else {
+            return;
+        };
+        // Keep at least one category, or the window would be empty
+        if !check.is_active()
+            && !categories_list
+                .checks
+                .borrow()
+                .iter()
+                .any(|c| c.is_active())
+        {
+            check.set_active(true);
+            return;
+        }
+
+        {
+            let mut config = categories_list.config.borrow_mut();
+            config
+                .settings
+                .set_category_hidden(&category, !check.is_active());
+            if let Err(e) = config.save_settings() {
+                tracing::error!("Failed to save settings: {:#}", e);
+            }
+        }
+        (categories_list.on_changed)();
+    });
+
+    categories_list.checks.borrow_mut().push(check);
+    row_box
+}
+
+fn create_edit_button(categories_list: &Rc<CategoriesList>, category: &CustomCategory) -> Button {
+    let btn = Button::from_icon_name("document-edit-symbolic");
+    btn.set_tooltip_text(Some("Edit"));
+    btn.add_css_class("flat");
+
+    let weak_list = Rc::downgrade(categories_list);
+    let category = category.clone();
+    btn.connect_clicked(move |_| {
+        if let Some(categories_list) = weak_list.upgrade() {
+            open_editor(&categories_list, Some(&category));
+        }
+    });
+    btn
+}
+
+/// Button deleting the category after asking. The defaults set through it
+/// stay, as other categories may share its types.
+fn create_delete_button(categories_list: &Rc<CategoriesList>, category: &CustomCategory) -> Button {
+    let btn = Button::from_icon_name("user-trash-symbolic");
+    btn.set_tooltip_text(Some("Delete"));
+    btn.add_css_class("flat");
+
+    let weak_list = Rc::downgrade(categories_list);
+    let category = category.clone();
+    btn.connect_clicked(move |btn| {
+        let Some(categories_list) = weak_list.upgrade() else {
+            return;
+        };
+        let dialog = AlertDialog::builder()
+            .modal(true)
+            .message(format!("Delete “{}”?", category.name))
+            .detail("Default applications set for its file types are kept.")
+            .buttons(["Cancel", "Delete"])
+            .cancel_button(0)
+            .default_button(0)
+            .build();
+
+        let id = category.id.clone();
+        dialog.choose(
+            btn.root().and_downcast::<Window>().as_ref(),
+            gio::Cancellable::NONE,
+            move |result| {
+                if result != Ok(1) {
+                    return;
+                }
+                {
+                    let mut config = categories_list.config.borrow_mut();
+                    config.settings.remove_custom_category(&id);
+                    if let Err(e) = config.save_settings() {
+                        tracing::error!("Failed to save settings: {:#}", e);
+                    }
+                    register_custom_categories(&config.settings.custom_categories);
+                }
+                tracing::info!("Deleted category {}", id);
+                refill_list(&categories_list);
+            },
+        );
+    });
+    btn
+}
@@ -0,0 +1,298 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use anyhow::{bail, Result};
+use gtk::prelude::*;
+use gtk::{
+    Box as GtkBox, Button, Entry, FlowBox, Image, Label, ListBox, Orientation, ScrolledWindow,
+    Window,
+};
+
+use crate::config::MimeAppsConfig;
+use crate::desktop::categories::{
+    custom_id, register_custom_categories, AppCategory, CustomCategory, CUSTOM_DEFAULT_ICON,
+};
+use crate::desktop::discovery::AppRegistry;
+use crate::ui::confirm_dialog::active_window;
+use crate::ui::mime_entry::mime_entry;
+use crate::utils::icons::category_icon;
+
+/// Show a dialog for defining a category, or changing the user-defined
+/// `editing` one. Saving stores it in the settings and registers it, then
+/// `on_saved` runs.
+pub fn show_category_editor<F>(
+    config: Rc<RefCell<MimeAppsConfig>>,
+    registry: &AppRegistry,
+    editing: Option<&CustomCategory>,
+    on_saved: F,
+) where
+    F: Fn() + 'static,
+{
+    let dialog = Window::builder()
+        .title(if editing.is_some() {
+            "Edit Category"
+        } else {
+            "New Category"
+        })
+        .modal(true)
+        .default_width(480)
+        .default_height(560)
+        .build();
+    dialog.set_transient_for(active_window().as_ref());
+
+    let content = GtkBox::new(Orientation::Vertical, 12);
+    content.set_margin_start(18);
+    content.set_margin_end(18);
+    content.set_margin_top(18);
+    content.set_margin_bottom(18);
+
+    let intro = Label::new(Some(
+        "Group the file types you want to set a default for together. Set as \
+         Default changes all of them, the first one deciding what is shown \
+         as current.",
+    ));
+    intro.set_wrap(true);
+    intro.set_xalign(0.0);
+    intro.add_css_class("dim-label");
+    content.append(&intro);
+
+    let name_entry = Entry::builder().placeholder_text("Name").build();
+    content.append(&heading("Name"));
+    content.append(&name_entry);
+
+    content.append(&heading("Icon"));
+    let (icon_box, icon_entry) = create_icon_picker();
+    content.append(&icon_box);
+
+    content.append(&heading("File Types"));
+    let mime_list = ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    let mime_types: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(Vec::new()));
+    let scrolled = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .vexpand(true)
+        .child(&mime_list)
+        .build();
+    content.append(&scrolled);
+
+    let error_label = Label::new(None);
+    error_label.set_wrap(true);
+    error_label.set_xalign(0.0);
+    error_label.add_css_class("error");
+    error_label.set_visible(false);
+
+    let add_box = GtkBox::new(Orientation::Horizontal, 6);
+    let type_entry = mime_entry(registry.known_mime_types());
+    add_box.append(&type_entry);
+    let add_btn = Button::with_label("Add");
+    add_box.append(&add_btn);
+    content.append(&add_box);
+    content.append(&error_label);
+
+    let add_type = {
+        let mime_list = mime_list.clone();
+        let mime_types = Rc::clone(&mime_types);
+        let error_label = error_label.clone();
+        Rc::new(move |mime: &str| {
+            let mime = mime.trim();
+            if let Err(e) = MimeAppsConfig::validate_mime_type(mime) {
+                show_error(&error_label, &e);
+                return false;
+            }
+            error_label.set_visible(false);
+            if !mime_types.borrow().iter().any(|m| m == mime) {
+                mime_types.borrow_mut().push(mime.to_string());
+                append_mime_row(&mime_list, &mime_types, mime);
+            }
+            true
+        })
+    };
+
+    let add_from_entry = {
+        let type_entry = type_entry.clone();
+        let add_type = Rc::clone(&add_type);
+        move || {
+            if add_type(&type_entry.text()) {
+                type_entry.set_text("");
+            }
+        }
+    };
+    let add_clone = add_from_entry.clone();
+    type_entry.connect_activate(move |_| add_clone());
+    add_btn.connect_clicked(move |_| add_from_entry());
+
+    if let Some(category) = editing {
+        name_entry.set_text(&category.name);
+        icon_entry.set_text(&category.icon);
+        for mime in &category.mime_types {
+            add_type(mime);
+        }
+    }
+
+    let button_box = GtkBox::new(Orientation::Horizontal, 6);
+    button_box.set_halign(gtk::Align::End);
+    let cancel_btn = Button::with_label("Cancel");
+    let dialog_clone = dialog.clone();
+    cancel_btn.connect_clicked(move |_| dialog_clone.close());
+    button_box.append(&cancel_btn);
+
+    let save_btn = Button::with_label("Save");
+    save_btn.add_css_class("suggested-action");
+    let editing_id = editing.map(|category| category.id.clone());
+    let dialog_clone = dialog.clone();
+    save_btn.connect_clicked(move |_| {
+        let mut category = CustomCategory::new(
+            &name_entry.text(),
+            &icon_entry.text(),
+            mime_types.borrow().clone(),
+        );
+        // Keep the ID when renaming, so its settings stay attached
+        if let Some(id) = &editing_id {
+            category.id = id.clone();
+        }
+        if let Err(e) = validate(&category, editing_id.is_some()) {
+            show_error(&error_label, &e);
+            return;
+        }
+
+        {
+            let mut config = config.borrow_mut();
+            config.settings.set_custom_category(category.clone());
+            if let Err(e) = config.save_settings() {
+                tracing::error!("Failed to save settings: {:#}", e);
+            }
+            register_custom_categories(&config.settings.custom_categories);
+        }
+        tracing::info!("Saved category {}", category.name);
+        dialog_clone.close();
+        on_saved();
+    });
+    button_box.append(&save_btn);
+    content.append(&button_box);
+
+    dialog.set_child(Some(&content));
+    dialog.present();
+}
+
+fn heading(text: &str) -> Label {
+    let label = Label::new(Some(text));
+    label.set_halign(gtk::Align::Start);
+    label.add_css_class("heading");
+    label
+}
+
+fn show_error(label: &Label, error: &anyhow::Error) {
+    label.set_text(&format!("{:#}", error));
+    label.set_visible(true);
+}
+
+/// An entry for an icon name with a preview, and buttons for the icons of
+/// the other categories
+fn create_icon_picker() -> (GtkBox, Entry) {
+    let vbox = GtkBox::new(Orientation::Vertical, 6);
+
+    let hbox = GtkBox::new(Orientation::Horizontal, 6);
+    let preview = Image::from_icon_name(CUSTOM_DEFAULT_ICON);
+    preview.set_pixel_size(32);
+    hbox.append(&preview);
+    let entry = Entry::builder()
+        .placeholder_text(CUSTOM_DEFAULT_ICON)
+        .hexpand(true)
+        .build();
+    let preview_clone = preview.clone();
+    entry.connect_changed(move |entry| {
+        let text = entry.text();
+        let name = text.trim();
+        preview_clone.set_icon_name(Some(if name.is_empty() {
+            CUSTOM_DEFAULT_ICON
+        } else {
+            name
+        }));
+    });
+    hbox.append(&entry);
+    vbox.append(&hbox);
+
+    let icons = FlowBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .max_children_per_line(12)
+        .build();
+    let mut names: Vec<String> = Vec::new();
+    for category in AppCategory::all() {
+        let name = category.icon_name().to_string();
+        if names.contains(&name) {
+            continue;
+        }
+        let btn = Button::new();
+        btn.set_child(Some(&category_icon(&name, 24)));
+        btn.set_tooltip_text(Some(&name));
+        btn.add_css_class("flat");
+        let entry = entry.clone();
+        let icon = name.clone();
+        btn.connect_clicked(move |_| entry.set_text(&icon));
+        icons.insert(&btn, -1);
+        names.push(name);
+    }
+    vbox.append(&icons);
+
+    (vbox, entry)
+}
+
+/// A row for a type in the category, with a button to take it out again
+fn append_mime_row(list: &ListBox, mime_types: &Rc<RefCell<Vec<String>>>, mime: &str) {
+    let hbox = GtkBox::new(Orientation::Horizontal, 12);
+    hbox.set_margin_start(12);
+    hbox.set_margin_end(6);
+    hbox.set_margin_top(4);
+    hbox.set_margin_bottom(4);
+
+    let label = Label::new(Some(mime));
+    label.set_halign(gtk::Align::Start);
+    label.set_hexpand(true);
+    label.set_selectable(true);
+    hbox.append(&label);
+
+    let remove_btn = Button::from_icon_name("list-remove-symbolic");
+    remove_btn.set_tooltip_text(Some("Remove"));
+    remove_btn.add_css_class("flat");
+    hbox.append(&remove_btn);
+    list.append(&hbox);
+
+    let list = list.clone();
+    let mime_types = Rc::clone(mime_types);
+    let mime = mime.to_string();
+    remove_btn.connect_clicked(move |btn| {
+        mime_types.borrow_mut().retain(|m| *m != mime);
+        if let Some(row) = btn.ancestor(gtk::ListBoxRow::static_type()) {
+            list.remove(&row);
+        }
+    });
+}
+
+/// Check a category before saving it. Names must be unique, as pages are
+/// found by them; a new category's ID mustn't be taken either.
+fn validate(category: &CustomCategory, editing: bool) -> Result<()> {
+    if category.name.is_empty() || category.id == custom_id("") {
+        bail!("Give the category a name with at least one letter or digit");
+    }
+    if category.mime_types.is_empty() {
+        bail!("Add at least one file type");
+    }
+
+    for other in AppCategory::all() {
+        if other.id() == category.id {
+            if editing {
+                continue;
+            }
+            bail!("A category like \"{}\" already exists", category.name);
+        }
+        if other.display_name().eq_ignore_ascii_case(&category.name) {
+            bail!(
+                "A category named \"{}\" already exists",
+                other.display_name()
+            );
+        }
+    }
+    Ok(())
+}
@@ -1,15 +1,31 @@
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 
 use gtk::prelude::*;
-use gtk::{Box as GtkBox, Expander, Label, ListBox, Orientation, ScrolledWindow};
+use gtk::{
+    Box as GtkBox, Button, Expander, FileDialog, FileFilter, FlowBox, Label, ListBox, Orientation,
+    ScrolledWindow,
+};
 
+use crate::config::settings::{Settings, ViewMode};
 use crate::config::MimeAppsConfig;
 use crate::desktop::categories::AppCategory;
 use crate::desktop::discovery::AppRegistry;
 use crate::desktop::entry::AppEntry;
-use crate::ui::app_row::{AppRow, CurrentDefaultRow};
-use crate::utils::icons::category_icon;
+use crate::desktop::mime_db::MimeDatabase;
+use crate::desktop::running::is_running;
+use crate::ui::app_row::{AppRow, CurrentDefaultRow, DefaultState};
+use crate::ui::app_tile::AppTile;
+use crate::ui::confirm_dialog::{
+    active_window, confirm_default_change, offer_remove_overrides, PendingChange,
+};
+use crate::ui::exec_preview::show_exec_preview;
+use crate::ui::launching::launch_app;
+use crate::ui::saving::save_in_background;
+use crate::ui::search::AppFilter;
+use crate::utils::icons::{category_icon, mime_icon};
+use crate::utils::mime::mime_description;
 
 /// Helper to set all margins at once
 fn set_margins(widget: &impl WidgetExt, margin: i32) {
@@ -19,10 +35,75 @@ fn set_margins(widget: &impl WidgetExt, margin: i32) {
     widget.set_margin_bottom(margin);
 }
 
+/// Number of applications shown before the rest collapse into "Show all"
+const COLLAPSED_APP_COUNT: usize = 8;
+
+/// Callbacks for the buttons of every entry in an app list, each given the
+/// app's desktop file ID
+#[derive(Clone)]
+struct AppActions {
+    set_default: Rc<dyn Fn(String)>,
+    test: Rc<dyn Fn(String)>,
+    /// Only for categories with MIME types to filter the file chooser by
+    test_with_file: Option<Rc<dyn Fn(String)>>,
+    /// Only for categories with MIME types to remove the app from
+    exclude: Option<Rc<dyn Fn(String)>>,
+}
+
+/// How the app lists of a page are shown
+struct AppListContext<'a> {
+    /// For explaining why each app is listed
+    category: &'a AppCategory,
+    settings: &'a Settings,
+    view_mode: ViewMode,
+    read_only: bool,
+    /// Names owned on the session bus, for marking running apps
+    bus_names: &'a [String],
+}
+
+/// Adapt a shared callback to the `Fn + Clone` the rows and tiles expect
+fn forward(callback: &Rc<dyn Fn(String)>) -> impl Fn(String) + Clone + 'static {
+    let callback = Rc::clone(callback);
+    move |app_id| callback(app_id)
+}
+
+/// The rows of a page's Individual MIME Type Settings, for pointing at the
+/// type of a file opened with the chooser
+#[derive(Clone, Default)]
+pub struct MimeRows {
+    expander: Option<Expander>,
+    rows: HashMap<String, GtkBox>,
+}
+
+impl MimeRows {
+    /// Expand the settings and focus the row of the first of `mimes` listed
+    /// there, so a MIME type and the types it is a subclass of can be
+    /// passed nearest first
+    pub fn reveal(&self, mimes: &[String]) {
+        let Some(expander) = &self.expander else {
+            return;
+        };
+        let Some(row) = mimes.iter().find_map(|mime| self.rows.get(mime)) else {
+            return;
+        };
+
+        expander.set_expanded(true);
+        row.add_css_class("accent");
+        row.set_focusable(true);
+        // Focusing scrolls the row into view, once it has been laid out
+        let row = row.clone();
+        glib::idle_add_local_once(move || {
+            row.grab_focus();
+        });
+    }
+}
+
 /// Page displaying a category with its default and available applications
 pub struct CategoryPage {
     pub widget: ScrolledWindow,
     category: AppCategory,
+    filter: AppFilter,
+    mime_rows: MimeRows,
 }
 
 impl CategoryPage {
@@ -30,6 +111,7 @@ impl CategoryPage {
         category: AppCategory,
         registry: Rc<AppRegistry>,
         config: Rc<RefCell<MimeAppsConfig>>,
+        bus_names: &[String],
         on_default_changed: F,
     ) -> Self
     where
@@ -51,25 +133,49 @@ impl CategoryPage {
 
         // Current default section
         let current_app = Self::get_current_default(&category, &registry, &config.borrow());
-        let current_section = Self::create_current_default_section(current_app);
+        let current_state = current_app.map_or(DefaultState::None, |app| {
+            let config = config.borrow();
+            let primary = config.settings.primary_mime_types(&category);
+            DefaultState::new(config.default_count(&primary, &app.id), primary.len())
+        });
+        let current_section = Self::create_current_default_section(
+            &category,
+            current_app,
+            current_state,
+            Rc::clone(&config),
+        );
         content.append(&current_section);
 
         // Available applications
+        let filter = AppFilter::default();
         let available_section = Self::create_available_apps_section(
             &category,
-            &registry,
             &config.borrow(),
+            bus_names,
             Rc::clone(&registry),
             Rc::clone(&config),
             Rc::clone(&on_default_changed),
+            &filter,
         );
         content.append(&available_section);
 
         // MIME type expander (for categories with multiple MIME types)
-        let primary_mimes = category.primary_mime_types();
-        if primary_mimes.len() > 1 {
-            let expander = Self::create_mime_expander(&category, &registry, &config.borrow());
-            content.append(&expander);
+        let mime_count = {
+            let settings = &config.borrow().settings;
+            settings.primary_mime_types(&category).len()
+                + settings.extended_mime_types(&category).len()
+        };
+        let mut mime_rows = MimeRows::default();
+        if mime_count > 1 {
+            mime_rows = Self::create_mime_expander(
+                &category,
+                &registry,
+                Rc::clone(&config),
+                Rc::clone(&on_default_changed),
+            );
+            if let Some(expander) = &mime_rows.expander {
+                content.append(expander);
+            }
         }
 
         scrolled.set_child(Some(&content));
@@ -77,6 +183,8 @@ impl CategoryPage {
         Self {
             widget: scrolled,
             category,
+            filter,
+            mime_rows,
         }
     }
 
@@ -101,8 +209,8 @@ impl CategoryPage {
         config: &MimeAppsConfig,
     ) -> Option<&'a AppEntry> {
         // Try primary MIME types first
-        for mime in category.primary_mime_types() {
-            if let Some(app_id) = config.get_default(mime) {
+        for mime in config.settings.primary_mime_types(category) {
+            if let Some(app_id) = config.get_default(&mime) {
                 if let Some(app) = registry.get_app(app_id) {
                     return Some(app);
                 }
@@ -113,7 +221,12 @@ impl CategoryPage {
         None
     }
 
-    fn create_current_default_section(current_app: Option<&AppEntry>) -> GtkBox {
+    fn create_current_default_section(
+        category: &AppCategory,
+        current_app: Option<&AppEntry>,
+        state: DefaultState,
+        config: Rc<RefCell<MimeAppsConfig>>,
+    ) -> GtkBox {
         let section = GtkBox::new(Orientation::Vertical, 8);
 
         let label = Label::new(Some("Current Default"));
@@ -121,19 +234,37 @@ impl CategoryPage {
         label.set_halign(gtk::Align::Start);
         section.append(&label);
 
-        let row = CurrentDefaultRow::new(current_app);
+        let row = CurrentDefaultRow::new(current_app, state);
         section.append(&row.widget);
 
+        // Pin the category's current defaults against changes by other programs
+        let (primary_mimes, locked, pinned) = {
+            let config = config.borrow();
+            let primary_mimes = config.settings.primary_mime_types(category);
+            let pinned = config.is_pinned(&primary_mimes);
+            (primary_mimes, config.settings.locked, pinned)
+        };
+        if !primary_mimes.is_empty() && !locked {
+            row.connect_pin(pinned, move |pinned| {
+                let mut config = config.borrow_mut();
+                config.set_pinned(&primary_mimes, pinned);
+                if let Err(e) = config.save_settings() {
+                    tracing::error!("Failed to save settings: {:#}", e);
+                }
+            });
+        }
+
         section
     }
 
     fn create_available_apps_section(
         category: &AppCategory,
-        registry: &AppRegistry,
         config: &MimeAppsConfig,
+        bus_names: &[String],
         registry_rc: Rc<AppRegistry>,
         config_rc: Rc<RefCell<MimeAppsConfig>>,
         on_default_changed: Rc<dyn Fn()>,
+        filter: &AppFilter,
     ) -> GtkBox {
         let section = GtkBox::new(Orientation::Vertical, 8);
 
@@ -142,92 +273,408 @@ impl CategoryPage {
         label.set_halign(gtk::Align::Start);
         section.append(&label);
 
-        let list = ListBox::builder()
-            .selection_mode(gtk::SelectionMode::None)
-            .css_classes(["boxed-list"])
-            .build();
-
-        // Get apps for this category
-        let apps = registry.apps_for_app_category(category);
-
-        // Determine current default
-        let current_default = category
-            .primary_mime_types()
-            .first()
-            .and_then(|m| config.get_default(m));
+        // Get apps for this category, minus those excluded from all of its types
+        let primary_mimes = config.settings.primary_mime_types(category);
+        let mut apps = registry_rc.apps_for_app_category(category, &config.settings);
+        if !primary_mimes.is_empty() {
+            apps.retain(|app| !primary_mimes.iter().all(|m| config.is_removed(m, &app.id)));
+        }
 
         if apps.is_empty() {
             let empty_label = Label::new(Some("No applications found for this category"));
             empty_label.add_css_class("dim-label");
             set_margins(&empty_label, 24);
             section.append(&empty_label);
+            return section;
+        }
+
+        // Exclude handler: block the app for every type of this category
+        let mimes = primary_mimes.clone();
+        let config_clone = Rc::clone(&config_rc);
+        let on_changed = Rc::clone(&on_default_changed);
+        let on_exclude = move |app_id: String| {
+            let mut config = config_clone.borrow_mut();
+            for mime in &mimes {
+                if let Err(e) = config.remove_association(mime, &app_id) {
+                    tracing::error!("Failed to remove association for {}: {}", mime, e);
+                    return;
+                }
+            }
+            drop(config);
+            save_in_background(&config_clone, || {});
+            on_changed();
+        };
+
+        // Test handler, opening something the category's apps should handle
+        let category_clone = category.clone();
+        let registry_clone = Rc::clone(&registry_rc);
+        let config_clone = Rc::clone(&config_rc);
+        let on_test = move |app_id: String| {
+            if let Some(app) = registry_clone.get_app(&app_id) {
+                let settings = &config_clone.borrow().settings;
+                let payload = settings.test_payload(&category_clone);
+                test_launch(app, payload.as_deref(), settings);
+            }
+        };
+
+        // Test-with-file handler, asking for a file the category handles
+        let category_clone = category.clone();
+        let registry_clone = Rc::clone(&registry_rc);
+        let config_clone = Rc::clone(&config_rc);
+        let on_test_with_file = move |app_id: String| {
+            let registry = Rc::clone(&registry_clone);
+            let config = Rc::clone(&config_clone);
+            let mimes = {
+                let settings = &config.borrow().settings;
+                let mut mimes = settings.primary_mime_types(&category_clone);
+                mimes.extend(settings.extended_mime_types(&category_clone));
+                mimes
+            };
+            choose_test_file(&category_clone, &mimes, move |file| {
+                if let Some(app) = registry.get_app(&app_id) {
+                    test_launch(app, Some(&file), &config.borrow().settings);
+                }
+            });
+        };
+
+        // Set default handler, confirming first when several types would change
+        let category_clone = category.clone();
+        let mimes = primary_mimes.clone();
+        let registry_clone = Rc::clone(&registry_rc);
+        let on_set_default = move |app_id: String| {
+            let changes: Vec<PendingChange> = {
+                let config = config_rc.borrow();
+                let app = registry_clone.get_app(&app_id);
+                let related = category_clone
+                    .related_mime_types()
+                    .into_iter()
+                    .filter(|mime| {
+                        config.settings.set_related_types
+                            && !mimes.iter().any(|m| m == mime)
+                            && app.is_some_and(|app| app.supports_mime_type(mime))
+                    });
+                mimes
+                    .iter()
+                    .map(String::as_str)
+                    .chain(related)
+                    .filter(|mime| config.get_default(mime) != Some(app_id.as_str()))
+                    .map(|mime| PendingChange {
+                        mime: mime.to_string(),
+                        description: mime_description(mime),
+                        current: config.get_default(mime).map(|id| {
+                            registry_clone
+                                .get_app(id)
+                                .map_or_else(|| id.to_string(), |app| app.name.clone())
+                        }),
+                        proposed: None,
+                    })
+                    .collect()
+            };
+
+            let config = Rc::clone(&config_rc);
+            let on_changed = Rc::clone(&on_default_changed);
+            let target = app_id.clone();
+            let apply = move |mimes: Vec<String>| {
+                let mut config_ref = config.borrow_mut();
+                for mime in &mimes {
+                    if let Err(e) = config_ref.set_default(mime, &target) {
+                        tracing::error!("Failed to set default for {}: {}", mime, e);
+                        return;
+                    }
+                }
+                drop(config_ref);
+                on_changed();
+
+                // Overrides are only up to date once the save is done
+                let config_clone = Rc::clone(&config);
+                let on_changed = Rc::clone(&on_changed);
+                save_in_background(&config, move || {
+                    offer_remove_overrides(config_clone, &mimes, move || on_changed());
+                });
+            };
+
+            if changes.len() > 1 {
+                let app_name = registry_clone
+                    .get_app(&app_id)
+                    .map_or(app_id.clone(), |app| app.name.clone());
+                let heading = format!("Make {} the default for:", app_name);
+                confirm_default_change(&heading, changes, apply);
+            } else {
+                apply(changes.into_iter().map(|change| change.mime).collect());
+            }
+        };
+
+        let has_mime_types = !primary_mimes.is_empty();
+        let actions = AppActions {
+            set_default: Rc::new(on_set_default),
+            test: Rc::new(on_test),
+            test_with_file: has_mime_types
+                .then(|| Rc::new(on_test_with_file) as Rc<dyn Fn(String)>),
+            exclude: has_mime_types.then(|| Rc::new(on_exclude) as Rc<dyn Fn(String)>),
+        };
+        let context = AppListContext {
+            category,
+            settings: &config.settings,
+            view_mode: config.settings.view_mode,
+            read_only: config.settings.locked,
+            bus_names,
+        };
+
+        // How much of the category each app is already the default for
+        let apps: Vec<(&AppEntry, DefaultState)> = apps
+            .into_iter()
+            .map(|app| {
+                let count = config.default_count(&primary_mimes, &app.id);
+                (app, DefaultState::new(count, primary_mimes.len()))
+            })
+            .collect();
+
+        // Apps declaring one of the category's types come first. Those only
+        // found through a desktop category (any "Office" app for document
+        // viewers) are listed apart, so odd suggestions are explained.
+        let (apps, related): (Vec<_>, Vec<_>) = if has_mime_types {
+            apps.into_iter().partition(|(app, _)| {
+                primary_mimes
+                    .iter()
+                    .any(|mime| app.supports_mime_type(mime))
+            })
+        } else {
+            (apps, Vec::new())
+        };
+
+        // Show the most relevant apps (and always the current defaults) up
+        // front, the rest behind an expander
+        let (shown, hidden): (Vec<_>, Vec<_>) = if apps.len() > COLLAPSED_APP_COUNT {
+            let ranked = registry_rc.ranked_apps_for_app_category(category, &config.settings);
+            let top: Vec<&str> = ranked
+                .iter()
+                .filter(|app| apps.iter().any(|(a, _)| a.id == app.id))
+                .take(COLLAPSED_APP_COUNT)
+                .map(|app| &*app.id)
+                .collect();
+            apps.into_iter()
+                .partition(|(app, state)| top.contains(&&*app.id) || *state != DefaultState::None)
         } else {
-            for app in apps {
-                let is_current = current_default == Some(&app.id);
-                let row = AppRow::new(app, is_current);
-
-                // Connect set default handler
-                let category_clone = category.clone();
-                let config_clone = Rc::clone(&config_rc);
-                let on_changed = Rc::clone(&on_default_changed);
-                row.connect_set_default(move |app_id| {
-                    let mut config = config_clone.borrow_mut();
-                    for mime in category_clone.primary_mime_types() {
-                        if let Err(e) = config.set_default(mime, &app_id) {
-                            tracing::error!("Failed to set default for {}: {}", mime, e);
-                            return;
+            (apps, Vec::new())
+        };
+
+        if !shown.is_empty() {
+            section.append(&Self::create_app_list(&shown, &context, &actions, filter));
+        }
+
+        if !hidden.is_empty() {
+            let expander =
+                Expander::new(Some(&format!("Show all ({})", shown.len() + hidden.len())));
+            expander.set_child(Some(&Self::create_app_list(
+                &hidden, &context, &actions, filter,
+            )));
+            filter.add_expander(&expander);
+            section.append(&expander);
+        }
+
+        if !related.is_empty() {
+            section.append(&Self::create_related_apps_expander(
+                &related,
+                shown.is_empty(),
+                &context,
+                &actions,
+                filter,
+            ));
+        }
+
+        section
+    }
+
+    /// Expander with the apps matched only by desktop category. It starts
+    /// open when there is nothing else to show or one of them is a default.
+    fn create_related_apps_expander(
+        apps: &[(&AppEntry, DefaultState)],
+        only_apps: bool,
+        context: &AppListContext,
+        actions: &AppActions,
+        filter: &AppFilter,
+    ) -> Expander {
+        let expander = Expander::new(Some(&format!("Related Applications ({})", apps.len())));
+        let has_default = apps.iter().any(|(_, state)| *state != DefaultState::None);
+        expander.set_expanded(only_apps || has_default);
+
+        let content = GtkBox::new(Orientation::Vertical, 8);
+        let explanation = Label::new(Some(
+            "These are in a related desktop category but don't declare that \
+             they open this category's file types, so they may not work well.",
+        ));
+        explanation.set_wrap(true);
+        explanation.set_xalign(0.0);
+        explanation.add_css_class("dim-label");
+        content.append(&explanation);
+        content.append(&Self::create_app_list(apps, context, actions, filter));
+
+        expander.set_child(Some(&content));
+        filter.add_expander(&expander);
+        expander
+    }
+
+    /// Build the list or grid of application entries. Read-only entries
+    /// show the default state without any buttons.
+    fn create_app_list(
+        apps: &[(&AppEntry, DefaultState)],
+        context: &AppListContext,
+        actions: &AppActions,
+        filter: &AppFilter,
+    ) -> gtk::Widget {
+        match context.view_mode {
+            ViewMode::List => {
+                let list = ListBox::builder()
+                    .selection_mode(gtk::SelectionMode::None)
+                    .css_classes(["boxed-list"])
+                    .build();
+
+                for (app, state) in apps {
+                    let row = AppRow::new(app, *state);
+                    row.set_match_reasons(&AppRegistry::match_reasons(
+                        app,
+                        context.category,
+                        context.settings,
+                    ));
+                    if is_running(app, context.bus_names) {
+                        row.set_running();
+                    }
+                    if context.read_only {
+                        row.set_read_only();
+                    } else {
+                        row.connect_set_default(forward(&actions.set_default));
+                        row.connect_test(forward(&actions.test));
+                        if let Some(on_test_with_file) = &actions.test_with_file {
+                            row.connect_test_with_file(forward(on_test_with_file));
+                        }
+                        if let (Some(on_exclude), DefaultState::None) = (&actions.exclude, state) {
+                            row.connect_exclude(forward(on_exclude));
                         }
                     }
-                    if let Err(e) = config.save() {
-                        tracing::error!("Failed to save config: {}", e);
+                    filter.add_entry(&row.widget, app);
+                    list.append(&row.widget);
+                }
+                list.upcast()
+            }
+            ViewMode::Grid => {
+                let grid = FlowBox::builder()
+                    .selection_mode(gtk::SelectionMode::None)
+                    .homogeneous(true)
+                    .min_children_per_line(2)
+                    .max_children_per_line(6)
+                    .row_spacing(12)
+                    .column_spacing(12)
+                    .build();
+
+                for (app, state) in apps {
+                    let tile = AppTile::new(app, *state);
+                    tile.set_match_reasons(&AppRegistry::match_reasons(
+                        app,
+                        context.category,
+                        context.settings,
+                    ));
+                    if is_running(app, context.bus_names) {
+                        tile.set_running();
                     }
-                    drop(config);
-                    on_changed();
-                });
-
-                // Connect test handler
-                let registry_clone = Rc::clone(&registry_rc);
-                row.connect_test(move |app_id| {
-                    if let Some(app) = registry_clone.get_app(&app_id) {
-                        if let Err(e) = crate::utils::exec::launch_app(app) {
-                            tracing::error!("Failed to launch app: {}", e);
+                    if context.read_only {
+                        tile.set_read_only();
+                    } else {
+                        tile.connect_set_default(forward(&actions.set_default));
+                        tile.connect_test(forward(&actions.test));
+                        if let Some(on_test_with_file) = &actions.test_with_file {
+                            tile.connect_test_with_file(forward(on_test_with_file));
+                        }
+                        if let (Some(on_exclude), DefaultState::None) = (&actions.exclude, state) {
+                            tile.connect_exclude(forward(on_exclude));
                         }
                     }
-                });
-
-                list.append(&row.widget);
+                    filter.add_entry(&tile.widget, app);
+                    grid.insert(&tile.widget, -1);
+                }
+                grid.upcast()
             }
-            section.append(&list);
         }
-
-        section
     }
 
     fn create_mime_expander(
         category: &AppCategory,
         registry: &AppRegistry,
-        config: &MimeAppsConfig,
-    ) -> Expander {
+        config_rc: Rc<RefCell<MimeAppsConfig>>,
+        on_changed: Rc<dyn Fn()>,
+    ) -> MimeRows {
         let expander = Expander::new(Some("Individual MIME Type Settings"));
         expander.set_margin_top(16);
 
         let content = GtkBox::new(Orientation::Vertical, 8);
         set_margins(&content, 8);
 
-        // List all associated MIME types with their current defaults
-        for mime in category.primary_mime_types() {
-            let mime_row = Self::create_mime_row(mime, registry, config);
-            content.append(&mime_row);
-        }
+        let mut rows = HashMap::new();
+        let config = config_rc.borrow();
+        let (primary, extended) = (
+            config.settings.primary_mime_types(category),
+            config.settings.extended_mime_types(category),
+        );
 
-        // Extended MIME types
-        for mime in category.extended_mime_types() {
-            let mime_row = Self::create_mime_row(mime, registry, config);
-            content.append(&mime_row);
+        // List all associated MIME types with their current defaults. Each
+        // can be moved between the types Set as Default covers and the rest.
+        for (mimes, is_primary) in [(primary, true), (extended, false)] {
+            for mime in mimes {
+                let mime_row = Self::create_mime_row(&mime, registry, &config);
+                if !config.settings.locked {
+                    let button = Self::create_move_button(
+                        category,
+                        &mime,
+                        is_primary,
+                        Rc::clone(&config_rc),
+                        Rc::clone(&on_changed),
+                    );
+                    mime_row.append(&button);
+                }
+                content.append(&mime_row);
+                rows.insert(mime, mime_row);
+            }
         }
 
         expander.set_child(Some(&content));
-        expander
+        MimeRows {
+            expander: Some(expander),
+            rows,
+        }
+    }
+
+    /// Button moving a MIME type out of the category's primary types, or
+    /// into them, and saving that in the settings
+    fn create_move_button(
+        category: &AppCategory,
+        mime: &str,
+        is_primary: bool,
+        config: Rc<RefCell<MimeAppsConfig>>,
+        on_changed: Rc<dyn Fn()>,
+    ) -> Button {
+        let (icon, tooltip) = if is_primary {
+            ("list-remove-symbolic", "Don't change with Set as Default")
+        } else {
+            ("list-add-symbolic", "Also change with Set as Default")
+        };
+
+        let button = Button::from_icon_name(icon);
+        button.set_tooltip_text(Some(tooltip));
+        button.set_valign(gtk::Align::Center);
+        button.add_css_class("flat");
+
+        let category = category.clone();
+        let mime = mime.to_string();
+        button.connect_clicked(move |_| {
+            let mut config = config.borrow_mut();
+            config.settings.set_primary(&category, &mime, !is_primary);
+            if let Err(e) = config.save_settings() {
+                tracing::error!("Failed to save settings: {:#}", e);
+            }
+            drop(config);
+            on_changed();
+        });
+
+        button
     }
 
     fn create_mime_row(mime: &str, registry: &AppRegistry, config: &MimeAppsConfig) -> GtkBox {
@@ -235,11 +682,42 @@ impl CategoryPage {
         row.set_margin_top(4);
         row.set_margin_bottom(4);
 
-        let mime_label = Label::new(Some(mime));
+        let icon = mime_icon(mime, 24);
+        row.append(&icon);
+
+        let text_box = GtkBox::new(Orientation::Vertical, 2);
+        text_box.set_hexpand(true);
+
+        // Most users recognize extensions rather than types
+        let extensions: Vec<String> = MimeDatabase::get()
+            .extensions(mime)
+            .iter()
+            .take(3)
+            .map(|ext| format!(".{}", ext))
+            .collect();
+        let mime_text = if extensions.is_empty() {
+            mime.to_string()
+        } else {
+            format!("{} ({})", mime, extensions.join(", "))
+        };
+
+        let mime_label = Label::new(Some(&mime_text));
         mime_label.set_halign(gtk::Align::Start);
-        mime_label.set_hexpand(true);
         mime_label.add_css_class("monospace");
-        row.append(&mime_label);
+
+        // Friendly name first, raw type underneath
+        if let Some(description) = mime_description(mime) {
+            let description_label = Label::new(Some(&description));
+            description_label.set_halign(gtk::Align::Start);
+            text_box.append(&description_label);
+
+            mime_label.add_css_class("dim-label");
+            mime_label.add_css_class("caption");
+            row.set_tooltip_text(Some(&format!("{} ({})", description, mime)));
+        }
+
+        text_box.append(&mime_label);
+        row.append(&text_box);
 
         let current = config
             .get_default(mime)
@@ -258,4 +736,65 @@ impl CategoryPage {
     pub fn category(&self) -> &AppCategory {
         &self.category
     }
+
+    /// Filter for the search in the header bar
+    pub fn filter(&self) -> &AppFilter {
+        &self.filter
+    }
+
+    /// Rows of the individual MIME type settings
+    pub fn mime_rows(&self) -> &MimeRows {
+        &self.mime_rows
+    }
+}
+
+/// Launch an app for testing, opening `file` if given. In advanced mode the
+/// command line is shown for editing first.
+fn test_launch(app: &AppEntry, file: Option<&str>, settings: &Settings) {
+    if settings.advanced {
+        show_exec_preview(app, file, settings.launch);
+        return;
+    }
+
+    if let Err(e) = launch_app(app, file, &settings.launch) {
+        tracing::error!("Failed to launch app: {}", e);
+    }
+}
+
+/// Ask for a file of one of the category's MIME types and pass its path to
+/// `on_chosen`, or its URI when it has no local path
+fn choose_test_file<F>(category: &AppCategory, mimes: &[String], on_chosen: F)
+where
+    F: FnOnce(String) + 'static,
+{
+    let filter = FileFilter::new();
+    filter.set_name(Some(category.display_name()));
+    for mime in mimes {
+        filter.add_mime_type(mime);
+    }
+    let filters = gio::ListStore::new::<FileFilter>();
+    filters.append(&filter);
+
+    let dialog = FileDialog::builder()
+        .title("Test with File")
+        .modal(true)
+        .filters(&filters)
+        .default_filter(&filter)
+        .build();
+
+    dialog.open(
+        active_window().as_ref(),
+        gio::Cancellable::NONE,
+        move |result| match result {
+            Ok(file) => {
+                let target = file.path().map_or_else(
+                    || file.uri().to_string(),
+                    |path| path.to_string_lossy().into_owned(),
+                );
+                on_chosen(target);
+            }
+            Err(e) if e.matches(gtk::DialogError::Dismissed) => {}
+            Err(e) => tracing::error!("Failed to choose a file: {}", e),
+        },
+    );
 }
@@ -0,0 +1,274 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gtk::prelude::*;
+use gtk::{
+    Box as GtkBox, Button, CheckButton, Label, ListBox, ListBoxRow, Orientation, ScrolledWindow,
+    Window,
+};
+
+use crate::config::MimeAppsConfig;
+
+/// A MIME type whose default would change
+pub struct PendingChange {
+    pub mime: String,
+    /// Friendly name of the type, if known
+    pub description: Option<String>,
+    /// Name of the application currently set as default, if any
+    pub current: Option<String>,
+    /// Name of the application it would change to, when the changes don't
+    /// all set the same one
+    pub proposed: Option<String>,
+}
+
+/// Find the window to attach dialogs to
+pub fn active_window() -> Option<Window> {
+    gio::Application::default()
+        .and_downcast::<gtk::Application>()
+        .and_then(|app| app.active_window())
+}
+
+/// Show a modal dialog listing the MIME types a "Set as Default" would
+/// change under `heading`, each with a checkbox to leave it out.
+/// `on_confirm` receives the MIME types that are still ticked when the user
+/// applies.
+pub fn confirm_default_change<F>(heading: &str, changes: Vec<PendingChange>, on_confirm: F)
+where
+    F: Fn(Vec<String>) + 'static,
+{
+    let dialog = Window::builder()
+        .title("Set Default Application")
+        .modal(true)
+        .default_width(480)
+        .resizable(false)
+        .build();
+    dialog.set_transient_for(active_window().as_ref());
+
+    let content = GtkBox::new(Orientation::Vertical, 12);
+    content.set_margin_start(18);
+    content.set_margin_end(18);
+    content.set_margin_top(18);
+    content.set_margin_bottom(18);
+
+    let heading = Label::new(Some(heading));
+    heading.add_css_class("heading");
+    heading.set_halign(gtk::Align::Start);
+    heading.set_wrap(true);
+    content.append(&heading);
+
+    let list = ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+
+    let apply_btn = Button::with_label("Set as Default");
+    apply_btn.add_css_class("suggested-action");
+
+    let selected: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(
+        changes.iter().map(|c| c.mime.clone()).collect(),
+    ));
+
+    let mut checks = Vec::new();
+    for change in &changes {
+        let check = CheckButton::new();
+        check.set_active(true);
+
+        let text_box = GtkBox::new(Orientation::Vertical, 2);
+        let title = match &change.description {
+            Some(description) => format!("{} ({})", description, change.mime),
+            None => change.mime.clone(),
+        };
+        let title_label = Label::new(Some(&title));
+        title_label.set_halign(gtk::Align::Start);
+        title_label.set_wrap(true);
+        text_box.append(&title_label);
+
+        let current = change.current.as_deref().unwrap_or("none");
+        let current_label = Label::new(Some(&match &change.proposed {
+            Some(proposed) => format!("Currently: {} → {}", current, proposed),
+            None => format!("Currently: {}", current),
+        }));
+        current_label.set_halign(gtk::Align::Start);
+        current_label.add_css_class("dim-label");
+        current_label.add_css_class("caption");
+        text_box.append(&current_label);
+
+        let row_box = GtkBox::new(Orientation::Horizontal, 12);
+        row_box.set_margin_start(12);
+        row_box.set_margin_end(12);
+        row_box.set_margin_top(8);
+        row_box.set_margin_bottom(8);
+        row_box.append(&check);
+        row_box.append(&text_box);
+
+        let row = ListBoxRow::new();
+        row.set_child(Some(&row_box));
+        checks.push(check.clone());
+
+        let mime = change.mime.clone();
+        let selected = Rc::clone(&selected);
+        let apply_btn = apply_btn.clone();
+        check.connect_toggled(move |check| {
+            let mut selected = selected.borrow_mut();
+            selected.retain(|m| m != &mime);
+            if check.is_active() {
+                selected.push(mime.clone());
+            }
+            apply_btn.set_sensitive(!selected.is_empty());
+        });
+
+        list.append(&row);
+    }
+
+    // Clicking anywhere on a row toggles its checkbox
+    list.connect_row_activated(move |_, row| {
+        if let Some(check) = checks.get(row.index() as usize) {
+            check.set_active(!check.is_active());
+        }
+    });
+    // Presets can touch dozens of types; scroll rather than grow off screen
+    let scrolled = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .max_content_height(400)
+        .propagate_natural_height(true)
+        .child(&list)
+        .build();
+    content.append(&scrolled);
+
+    let buttons = GtkBox::new(Orientation::Horizontal, 8);
+    buttons.set_halign(gtk::Align::End);
+    buttons.set_margin_top(6);
+
+    let cancel_btn = Button::with_label("Cancel");
+    let dialog_clone = dialog.clone();
+    cancel_btn.connect_clicked(move |_| dialog_clone.close());
+    buttons.append(&cancel_btn);
+
+    let dialog_clone = dialog.clone();
+    apply_btn.connect_clicked(move |_| {
+        // Keep the original listing order
+        let selected = selected.borrow();
+        let mimes = changes
+            .iter()
+            .filter(|c| selected.contains(&c.mime))
+            .map(|c| c.mime.clone())
+            .collect();
+        dialog_clone.close();
+        on_confirm(mimes);
+    });
+    buttons.append(&apply_btn);
+
+    content.append(&buttons);
+    dialog.set_child(Some(&content));
+    dialog.present();
+}
+
+/// After defaults were saved, warn about any of `mimes` that a higher
+/// priority file (such as `~/.config/gnome-mimeapps.list`) overrides, so the
+/// change has no effect, and offer to remove the overriding entries.
+/// `on_removed` runs once they are gone.
+pub fn offer_remove_overrides<F>(
+    config: Rc<RefCell<MimeAppsConfig>>,
+    mimes: &[String],
+    on_removed: F,
+) where
+    F: Fn() + 'static,
+{
+    let conflicts: Vec<(String, String)> = {
+        let config = config.borrow();
+        mimes
+            .iter()
+            .filter_map(|mime| {
+                let found = config.overriding_default(mime)?;
+                Some((
+                    mime.clone(),
+                    format!("{} sets {}", found.path.display(), found.apps.join(", ")),
+                ))
+            })
+            .collect()
+    };
+    if conflicts.is_empty() {
+        return;
+    }
+
+    let dialog = Window::builder()
+        .title("Default Overridden")
+        .modal(true)
+        .default_width(480)
+        .resizable(false)
+        .build();
+    dialog.set_transient_for(active_window().as_ref());
+
+    let content = GtkBox::new(Orientation::Vertical, 12);
+    content.set_margin_start(18);
+    content.set_margin_end(18);
+    content.set_margin_top(18);
+    content.set_margin_bottom(18);
+
+    let heading = Label::new(Some(
+        "Another file takes priority for these types, so the new default won't take effect:",
+    ));
+    heading.add_css_class("heading");
+    heading.set_halign(gtk::Align::Start);
+    heading.set_wrap(true);
+    content.append(&heading);
+
+    let list = ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    for (mime, detail) in &conflicts {
+        let text_box = GtkBox::new(Orientation::Vertical, 2);
+        text_box.set_margin_start(12);
+        text_box.set_margin_end(12);
+        text_box.set_margin_top(8);
+        text_box.set_margin_bottom(8);
+
+        let mime_label = Label::new(Some(mime));
+        mime_label.set_halign(gtk::Align::Start);
+        text_box.append(&mime_label);
+
+        let detail_label = Label::new(Some(detail));
+        detail_label.set_halign(gtk::Align::Start);
+        detail_label.set_wrap(true);
+        detail_label.add_css_class("dim-label");
+        detail_label.add_css_class("caption");
+        text_box.append(&detail_label);
+
+        list.append(&text_box);
+    }
+    content.append(&list);
+
+    let buttons = GtkBox::new(Orientation::Horizontal, 8);
+    buttons.set_halign(gtk::Align::End);
+    buttons.set_margin_top(6);
+
+    let keep_btn = Button::with_label("Keep");
+    let dialog_clone = dialog.clone();
+    keep_btn.connect_clicked(move |_| dialog_clone.close());
+    buttons.append(&keep_btn);
+
+    let remove_btn = Button::with_label("Remove Overriding Entries");
+    remove_btn.add_css_class("destructive-action");
+    let dialog_clone = dialog.clone();
+    remove_btn.connect_clicked(move |_| {
+        dialog_clone.close();
+        let mut config_ref = config.borrow_mut();
+        for (mime, _) in &conflicts {
+            if let Err(e) = config_ref.remove_override(mime) {
+                tracing::error!(
+                    "Failed to remove the overriding default for {}: {:#}",
+                    mime,
+                    e
+                );
+            }
+        }
+        drop(config_ref);
+        on_removed();
+    });
+    buttons.append(&remove_btn);
+
+    content.append(&buttons);
+    dialog.set_child(Some(&content));
+    dialog.present();
+}
@@ -0,0 +1,169 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Button, Label, ListBox, Orientation, ScrolledWindow, Window};
+
+use crate::config::MimeAppsConfig;
+use crate::desktop::discovery::AppRegistry;
+use crate::desktop::duplicates::{flatpak_duplicates, FlatpakDuplicate};
+use crate::desktop::entry::AppEntry;
+use crate::ui::confirm_dialog::active_window;
+use crate::ui::saving::save_in_background;
+use crate::utils::icons::app_icon;
+
+/// Show the applications installed both natively and as a Flatpak, each
+/// with buttons that point every default and association at one copy.
+/// `on_changed` runs after associations were rewritten.
+pub fn show_duplicates_dialog<F>(
+    registry: Rc<AppRegistry>,
+    config: Rc<RefCell<MimeAppsConfig>>,
+    on_changed: F,
+) where
+    F: Fn() + 'static,
+{
+    let on_changed: Rc<dyn Fn()> = Rc::new(on_changed);
+    let read_only = config.borrow().settings.locked;
+
+    let dialog = Window::builder()
+        .title("Duplicate Applications")
+        .modal(true)
+        .default_width(600)
+        .default_height(520)
+        .build();
+    dialog.set_transient_for(active_window().as_ref());
+
+    let content = GtkBox::new(Orientation::Vertical, 12);
+    content.set_margin_start(18);
+    content.set_margin_end(18);
+    content.set_margin_top(18);
+    content.set_margin_bottom(18);
+
+    let intro = Label::new(Some(
+        "These applications are installed both from your distribution and as \
+         a Flatpak. Pick the copy that should open everything the other one \
+         is set up for.",
+    ));
+    intro.set_wrap(true);
+    intro.set_xalign(0.0);
+    intro.add_css_class("dim-label");
+    content.append(&intro);
+
+    let duplicates = flatpak_duplicates(&registry);
+    if duplicates.is_empty() {
+        let empty = Label::new(Some("No application is installed twice"));
+        empty.set_vexpand(true);
+        empty.add_css_class("dim-label");
+        content.append(&empty);
+    } else {
+        let list = ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        for duplicate in &duplicates {
+            list.append(&create_duplicate_row(
+                duplicate,
+                read_only,
+                &config,
+                &on_changed,
+            ));
+        }
+
+        let scrolled = ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vexpand(true)
+            .child(&list)
+            .build();
+        content.append(&scrolled);
+    }
+
+    let close_btn = Button::with_label("Close");
+    close_btn.set_halign(gtk::Align::End);
+    let dialog_clone = dialog.clone();
+    close_btn.connect_clicked(move |_| dialog_clone.close());
+    content.append(&close_btn);
+
+    dialog.set_child(Some(&content));
+    dialog.present();
+}
+
+/// Both copies with their origins, and a button to prefer each
+fn create_duplicate_row(
+    duplicate: &FlatpakDuplicate,
+    read_only: bool,
+    config: &Rc<RefCell<MimeAppsConfig>>,
+    on_changed: &Rc<dyn Fn()>,
+) -> GtkBox {
+    let vbox = GtkBox::new(Orientation::Vertical, 8);
+    vbox.set_margin_start(12);
+    vbox.set_margin_end(12);
+    vbox.set_margin_top(8);
+    vbox.set_margin_bottom(8);
+
+    for (app, origin, other) in [
+        (duplicate.native, "Native package", duplicate.flatpak),
+        (duplicate.flatpak, "Flatpak", duplicate.native),
+    ] {
+        let hbox = GtkBox::new(Orientation::Horizontal, 12);
+        hbox.append(&app_icon(app.icon.as_deref(), 32));
+
+        let text_box = GtkBox::new(Orientation::Vertical, 2);
+        text_box.set_hexpand(true);
+        let name_label = Label::new(Some(&format!("{} ({})", app.name, origin)));
+        name_label.set_halign(gtk::Align::Start);
+        name_label.add_css_class("heading");
+        text_box.append(&name_label);
+        let path_label = Label::new(Some(&app.path.display().to_string()));
+        path_label.set_halign(gtk::Align::Start);
+        path_label.set_selectable(true);
+        path_label.set_wrap(true);
+        path_label.add_css_class("dim-label");
+        text_box.append(&path_label);
+        hbox.append(&text_box);
+
+        if !read_only {
+            hbox.append(&create_prefer_button(app, other, config, on_changed));
+        }
+        vbox.append(&hbox);
+    }
+
+    vbox
+}
+
+/// "Use Everywhere" button rewriting entries naming `other` to `app`
+fn create_prefer_button(
+    app: &AppEntry,
+    other: &AppEntry,
+    config: &Rc<RefCell<MimeAppsConfig>>,
+    on_changed: &Rc<dyn Fn()>,
+) -> Button {
+    let btn = Button::with_label("Use Everywhere");
+    btn.set_valign(gtk::Align::Center);
+    btn.set_tooltip_text(Some(&format!(
+        "Replace {} with {} in every default and association",
+        other.id, app.id
+    )));
+
+    let (from, to) = (other.id.clone(), app.id.clone());
+    let config = Rc::clone(config);
+    let on_changed = Rc::clone(on_changed);
+    btn.connect_clicked(move |_| {
+        let mut config_ref = config.borrow_mut();
+        let mimes = match config_ref.replace_app(&from, &to) {
+            Ok(mimes) => mimes,
+            Err(e) => {
+                tracing::error!("Failed to replace {}: {:#}", from, e);
+                return;
+            }
+        };
+        if mimes.is_empty() {
+            tracing::info!("Nothing refers to {}", from);
+            return;
+        }
+        tracing::info!("Replaced {} with {} for {}", from, to, mimes.join(", "));
+        drop(config_ref);
+        save_in_background(&config, || {});
+        on_changed();
+    });
+    btn
+}
@@ -0,0 +1,110 @@
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Button, Entry, Label, Orientation, Window};
+
+use crate::config::settings::LaunchSettings;
+use crate::desktop::entry::AppEntry;
+use crate::ui::confirm_dialog::active_window;
+use crate::ui::launching::activation_token;
+use crate::utils::exec::{spawn_detached, test_command};
+
+/// Show the command line a test launch would spawn, after field-code
+/// processing, in an editable entry. Edits only apply to this launch.
+pub fn show_exec_preview(app: &AppEntry, file: Option<&str>, launch: LaunchSettings) {
+    let argv = match test_command(app, file) {
+        Ok(argv) => argv,
+        Err(e) => {
+            tracing::error!("Failed to build command for {}: {:#}", app.id, e);
+            Vec::new()
+        }
+    };
+
+    let dialog = Window::builder()
+        .title("Test Launch")
+        .modal(true)
+        .default_width(560)
+        .resizable(false)
+        .build();
+    dialog.set_transient_for(active_window().as_ref());
+
+    let content = GtkBox::new(Orientation::Vertical, 12);
+    content.set_margin_start(18);
+    content.set_margin_end(18);
+    content.set_margin_top(18);
+    content.set_margin_bottom(18);
+
+    let heading = Label::new(Some(&format!("Command for {}:", app.name)));
+    heading.add_css_class("heading");
+    heading.set_halign(gtk::Align::Start);
+    content.append(&heading);
+
+    let entry = Entry::new();
+    entry.set_text(&shell_words::join(&argv));
+    entry.add_css_class("monospace");
+    content.append(&entry);
+
+    let source_label = Label::new(Some(&format!(
+        "From {}: Exec={}",
+        app.path.display(),
+        app.exec.as_deref().unwrap_or("")
+    )));
+    source_label.set_halign(gtk::Align::Start);
+    source_label.set_wrap(true);
+    source_label.set_selectable(true);
+    source_label.add_css_class("dim-label");
+    source_label.add_css_class("caption");
+    content.append(&source_label);
+
+    if launch.clean_environment {
+        let env_label = Label::new(Some("Runs with a clean environment"));
+        env_label.set_halign(gtk::Align::Start);
+        env_label.add_css_class("dim-label");
+        env_label.add_css_class("caption");
+        content.append(&env_label);
+    }
+
+    let error_label = Label::new(None);
+    error_label.set_halign(gtk::Align::Start);
+    error_label.set_wrap(true);
+    error_label.add_css_class("error");
+    error_label.set_visible(false);
+    content.append(&error_label);
+
+    let buttons = GtkBox::new(Orientation::Horizontal, 8);
+    buttons.set_halign(gtk::Align::End);
+    buttons.set_margin_top(6);
+
+    let cancel_btn = Button::with_label("Cancel");
+    let dialog_clone = dialog.clone();
+    cancel_btn.connect_clicked(move |_| dialog_clone.close());
+    buttons.append(&cancel_btn);
+
+    let launch_btn = Button::with_label("Launch");
+    launch_btn.add_css_class("suggested-action");
+    buttons.append(&launch_btn);
+
+    // Keep the dialog open with the error so the command can be fixed
+    let run = {
+        let dialog = dialog.clone();
+        let entry = entry.clone();
+        let app = app.clone();
+        move || {
+            let result = shell_words::split(&entry.text())
+                .map_err(anyhow::Error::from)
+                .and_then(|argv| spawn_detached(&argv, &launch, activation_token(&app).as_deref()));
+            match result {
+                Ok(()) => dialog.close(),
+                Err(e) => {
+                    error_label.set_text(&format!("{:#}", e));
+                    error_label.set_visible(true);
+                }
+            }
+        }
+    };
+    let run_clone = run.clone();
+    launch_btn.connect_clicked(move |_| run_clone());
+    entry.connect_activate(move |_| run());
+
+    content.append(&buttons);
+    dialog.set_child(Some(&content));
+    dialog.present();
+}
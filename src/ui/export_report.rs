@@ -0,0 +1,73 @@
+use gtk::prelude::*;
+use gtk::{FileDialog, FileFilter};
+
+use crate::config::report::Report;
+use crate::config::MimeAppsConfig;
+use crate::desktop::discovery::AppRegistry;
+use crate::ui::confirm_dialog::active_window;
+use crate::utils::atomic::write_atomic;
+
+/// Ask where to save a report of the current defaults and write it there,
+/// as JSON for a `.json` file name, HTML for `.html` and Markdown otherwise
+pub fn export_report(config: &MimeAppsConfig, registry: &AppRegistry) {
+    // Taken now, so the report matches what the window showed
+    let report = Report::new(config, registry);
+
+    let markdown = FileFilter::new();
+    markdown.set_name(Some("Markdown"));
+    markdown.add_suffix("md");
+    let json = FileFilter::new();
+    json.set_name(Some("JSON"));
+    json.add_suffix("json");
+    let html = FileFilter::new();
+    html.set_name(Some("HTML"));
+    html.add_suffix("html");
+    let filters = gio::ListStore::new::<FileFilter>();
+    filters.append(&markdown);
+    filters.append(&json);
+    filters.append(&html);
+
+    let dialog = FileDialog::builder()
+        .title("Export Report")
+        .modal(true)
+        .initial_name("default-applications.md")
+        .filters(&filters)
+        .build();
+
+    dialog.save(
+        active_window().as_ref(),
+        gio::Cancellable::NONE,
+        move |result| {
+            let file = match result {
+                Ok(file) => file,
+                Err(e) if e.matches(gtk::DialogError::Dismissed) => return,
+                Err(e) => {
+                    tracing::error!("Failed to choose where to save the report: {}", e);
+                    return;
+                }
+            };
+            let Some(path) = file.path() else {
+                tracing::error!("Reports can only be saved to local files");
+                return;
+            };
+
+            let extension = path.extension().and_then(|ext| ext.to_str());
+            let content = match extension {
+                Some("json") => match serde_json::to_string_pretty(&report) {
+                    Ok(json) => json + "\n",
+                    Err(e) => {
+                        tracing::error!("Failed to write the report as JSON: {}", e);
+                        return;
+                    }
+                },
+                Some("html" | "htm") => report.to_html(),
+                _ => report.to_markdown(),
+            };
+
+            match write_atomic(&path, &content) {
+                Ok(()) => tracing::info!("Saved report to {}", path.display()),
+                Err(e) => tracing::error!("Failed to save report: {:#}", e),
+            }
+        },
+    );
+}
@@ -0,0 +1,90 @@
+use gtk::gio;
+use gtk::prelude::*;
+
+use crate::config::changes::DefaultChange;
+use crate::desktop::discovery::AppRegistry;
+use crate::utils::mime::mime_description;
+
+/// Notification IDs, so a new batch of changes replaces the previous one
+const CHANGED_ID: &str = "external-change";
+const RESTORED_ID: &str = "pinned-restored";
+
+/// Changes listed in the notification body before summarizing the rest
+const MAX_LISTED: usize = 3;
+
+/// Tell the user that another program changed default applications, with
+/// the old and new apps and, unless `can_revert` is false, a Revert button
+/// that activates `app.revert-defaults`.
+pub fn notify_external_changes(
+    app: &gio::Application,
+    changes: &[DefaultChange],
+    registry: &AppRegistry,
+    can_revert: bool,
+) {
+    let title = match changes.len() {
+        1 => "Default application changed by another program".to_string(),
+        n => format!("{} default applications changed by another program", n),
+    };
+
+    let notification = gio::Notification::new(&title);
+    notification.set_body(Some(&describe_changes(changes, registry)));
+
+    if can_revert {
+        // An empty app ID means there was no default before
+        let previous: Vec<(String, String)> = changes
+            .iter()
+            .map(|change| (change.mime.clone(), change.old.clone().unwrap_or_default()))
+            .collect();
+        notification.add_button_with_target_value(
+            "Revert",
+            "app.revert-defaults",
+            Some(&previous.to_variant()),
+        );
+    }
+
+    app.send_notification(Some(CHANGED_ID), &notification);
+}
+
+/// Tell the user that pinned defaults were changed by another program and
+/// have been put back
+pub fn notify_pinned_restored(
+    app: &gio::Application,
+    restored: &[DefaultChange],
+    registry: &AppRegistry,
+) {
+    let title = match restored.len() {
+        1 => "Restored a pinned default application".to_string(),
+        n => format!("Restored {} pinned default applications", n),
+    };
+
+    let notification = gio::Notification::new(&title);
+    notification.set_body(Some(&describe_changes(restored, registry)));
+    app.send_notification(Some(RESTORED_ID), &notification);
+}
+
+/// One "type: old → new" line per change, summarizing past the first few
+fn describe_changes(changes: &[DefaultChange], registry: &AppRegistry) -> String {
+    let app_name = |id: Option<&str>| match id {
+        Some(id) => registry
+            .get_app(id)
+            .map_or_else(|| id.to_string(), |app| app.name.clone()),
+        None => "none".to_string(),
+    };
+
+    let mut lines: Vec<String> = changes
+        .iter()
+        .take(MAX_LISTED)
+        .map(|change| {
+            format!(
+                "{}: {} → {}",
+                mime_description(&change.mime).unwrap_or_else(|| change.mime.clone()),
+                app_name(change.old.as_deref()),
+                app_name(change.new.as_deref())
+            )
+        })
+        .collect();
+    if changes.len() > MAX_LISTED {
+        lines.push(format!("and {} more", changes.len() - MAX_LISTED));
+    }
+    lines.join("\n")
+}
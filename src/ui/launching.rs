@@ -0,0 +1,44 @@
+use anyhow::Result;
+use gtk::gdk;
+use gtk::prelude::*;
+
+use crate::config::settings::LaunchSettings;
+use crate::desktop::entry::AppEntry;
+use crate::desktop::running::activate_running;
+use crate::utils::exec::{spawn_detached, test_command};
+
+/// Launch an app, opening `file` if given, as a test or from the Open With
+/// dialog. An app declaring SingleMainWindow that is already running is
+/// asked to show its window instead, as a second process would only hand
+/// over to it and exit.
+pub fn launch_app(app: &AppEntry, file: Option<&str>, launch: &LaunchSettings) -> Result<()> {
+    let token = activation_token(app);
+
+    if app.single_main_window {
+        match activate_running(app, file, token.as_deref()) {
+            Ok(true) => {
+                tracing::info!("Activated the running instance of {}", app.id);
+                return Ok(());
+            }
+            Ok(false) => {}
+            Err(e) => tracing::warn!("Failed to activate {}: {:#}", app.id, e),
+        }
+    }
+
+    spawn_detached(&test_command(app, file)?, launch, token.as_deref())
+}
+
+/// A startup notification ID (X11) or activation token (Wayland) for
+/// launching an app that declares StartupNotify, so the desktop shows it
+/// starting and lets its window take the focus
+pub fn activation_token(app: &AppEntry) -> Option<String> {
+    if !app.startup_notify {
+        return None;
+    }
+    let display = gdk::Display::default()?;
+    let info = gio::DesktopAppInfo::from_filename(&app.path)?;
+    display
+        .app_launch_context()
+        .startup_notify_id(Some(&info), &[])
+        .map(String::from)
+}
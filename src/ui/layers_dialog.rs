@@ -0,0 +1,152 @@
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Button, Expander, Label, ListBox, Orientation, ScrolledWindow, Window};
+
+use crate::config::layers::{inspect_layers, EntryStatus, Layer, LayerEntry};
+use crate::config::MimeAppsConfig;
+use crate::ui::confirm_dialog::active_window;
+
+/// Show every mimeapps.list location in priority order, each expanding to
+/// the entries it contributes and whether its defaults win. Read from disk
+/// when opened, so it also reflects changes not yet picked up.
+pub fn show_layers_dialog() {
+    let dialog = Window::builder()
+        .title("Configuration Layers")
+        .modal(true)
+        .default_width(640)
+        .default_height(560)
+        .build();
+    dialog.set_transient_for(active_window().as_ref());
+
+    let content = GtkBox::new(Orientation::Vertical, 12);
+    content.set_margin_start(18);
+    content.set_margin_end(18);
+    content.set_margin_top(18);
+    content.set_margin_bottom(18);
+
+    let intro = Label::new(Some(
+        "Files are merged from the top down: the first default found for a \
+         type applies, associations are combined from all files.",
+    ));
+    intro.set_wrap(true);
+    intro.set_xalign(0.0);
+    intro.add_css_class("dim-label");
+    content.append(&intro);
+
+    let layers_box = GtkBox::new(Orientation::Vertical, 6);
+    match inspect_layers() {
+        Ok(layers) => {
+            for layer in &layers {
+                layers_box.append(&create_layer_expander(layer, &layers));
+            }
+        }
+        Err(e) => {
+            tracing::error!("Failed to inspect configuration layers: {:#}", e);
+            let label = Label::new(Some(&format!("{:#}", e)));
+            label.set_wrap(true);
+            layers_box.append(&label);
+        }
+    }
+
+    let scrolled = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .vexpand(true)
+        .child(&layers_box)
+        .build();
+    content.append(&scrolled);
+
+    let close_btn = Button::with_label("Close");
+    close_btn.set_halign(gtk::Align::End);
+    let dialog_clone = dialog.clone();
+    close_btn.connect_clicked(move |_| dialog_clone.close());
+    content.append(&close_btn);
+
+    dialog.set_child(Some(&content));
+    dialog.present();
+}
+
+/// Expander titled with the file's path and a summary, listing its entries
+fn create_layer_expander(layer: &Layer, layers: &[Layer]) -> Expander {
+    let used = layer
+        .entries
+        .iter()
+        .filter(|entry| entry.status == EntryStatus::Used)
+        .count();
+    let summary = if !layer.exists {
+        "not present".to_string()
+    } else if layer.entries.is_empty() {
+        "empty".to_string()
+    } else {
+        format!("{} entries, {} defaults used", layer.entries.len(), used)
+    };
+
+    let summary = if MimeAppsConfig::is_drop_in(&layer.path) {
+        format!("drop-in, never written; {}", summary)
+    } else {
+        summary
+    };
+
+    let expander = Expander::new(Some(&format!("{} ({})", layer.path.display(), summary)));
+    expander.set_sensitive(!layer.entries.is_empty());
+
+    let list = ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    for entry in &layer.entries {
+        list.append(&create_entry_row(entry, layers));
+    }
+    list.set_margin_top(6);
+    expander.set_child(Some(&list));
+    expander
+}
+
+/// A row with the section, MIME type, applications and merge status
+fn create_entry_row(entry: &LayerEntry, layers: &[Layer]) -> GtkBox {
+    let hbox = GtkBox::new(Orientation::Horizontal, 12);
+    hbox.set_margin_start(12);
+    hbox.set_margin_end(12);
+    hbox.set_margin_top(6);
+    hbox.set_margin_bottom(6);
+
+    let text_box = GtkBox::new(Orientation::Vertical, 2);
+    text_box.set_hexpand(true);
+
+    let mime_label = Label::new(Some(&entry.mime));
+    mime_label.set_halign(gtk::Align::Start);
+    mime_label.set_selectable(true);
+    text_box.append(&mime_label);
+
+    let apps = if entry.apps.is_empty() {
+        "(no applications)".to_string()
+    } else {
+        entry.apps.join(", ")
+    };
+    let apps_label = Label::new(Some(&format!("{}: {}", entry.section, apps)));
+    apps_label.set_halign(gtk::Align::Start);
+    apps_label.set_wrap(true);
+    apps_label.add_css_class("dim-label");
+    text_box.append(&apps_label);
+    hbox.append(&text_box);
+
+    let (status, class) = match entry.status {
+        EntryStatus::Used => ("used".to_string(), Some("success")),
+        EntryStatus::Overridden(winner) => {
+            let winner = layers
+                .get(winner)
+                .map_or_else(String::new, |layer| layer.path.display().to_string());
+            (format!("overridden by {}", winner), Some("warning"))
+        }
+        EntryStatus::Merged => ("merged".to_string(), None),
+        EntryStatus::Empty => ("ignored".to_string(), Some("dim-label")),
+    };
+    let status_label = Label::new(Some(&status));
+    status_label.set_wrap(true);
+    status_label.set_max_width_chars(30);
+    status_label.set_xalign(1.0);
+    if let Some(class) = class {
+        status_label.add_css_class(class);
+    }
+    hbox.append(&status_label);
+
+    hbox
+}
@@ -0,0 +1,99 @@
+use std::cell::Cell;
+use std::time::{Duration, UNIX_EPOCH};
+
+use gtk::prelude::*;
+use gtk::{glib, Revealer, RevealerTransitionType, ScrolledWindow, TextBuffer, TextView};
+use tracing::Level;
+
+use crate::utils::logging::records_since;
+
+/// How often new log records are picked up
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Collapsible view of recent log messages at the bottom of the window, so
+/// discovery and launch problems can be seen without a terminal
+pub struct LogPanel {
+    pub widget: Revealer,
+}
+
+impl LogPanel {
+    pub fn new() -> Self {
+        let buffer = TextBuffer::new(None);
+        buffer.create_tag(Some("warn"), &[("foreground", &"#c64600")]);
+        buffer.create_tag(Some("error"), &[("foreground", &"#c01c28")]);
+        let end_mark = buffer.create_mark(None, &buffer.end_iter(), false);
+
+        let view = TextView::builder()
+            .buffer(&buffer)
+            .editable(false)
+            .cursor_visible(false)
+            .monospace(true)
+            .wrap_mode(gtk::WrapMode::WordChar)
+            .left_margin(8)
+            .right_margin(8)
+            .top_margin(4)
+            .bottom_margin(4)
+            .build();
+
+        let scrolled = ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .min_content_height(160)
+            .child(&view)
+            .build();
+
+        let revealer = Revealer::builder()
+            .transition_type(RevealerTransitionType::SlideUp)
+            .child(&scrolled)
+            .build();
+
+        // Records can be logged from any thread, so poll rather than push
+        let seen = Cell::new(0);
+        let view = view.downgrade();
+        glib::timeout_add_local(POLL_INTERVAL, move || {
+            let Some(view) = view.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+
+            let (records, total) = records_since(seen.get());
+            seen.set(total);
+            if records.is_empty() {
+                return glib::ControlFlow::Continue;
+            }
+
+            let buffer = view.buffer();
+            for record in records {
+                let secs = record
+                    .time
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs() as i64);
+                let time = glib::DateTime::from_unix_local(secs)
+                    .and_then(|t| t.format("%H:%M:%S"))
+                    .map(|t| t.to_string())
+                    .unwrap_or_default();
+                let line = format!("{} {:<5} {}\n", time, record.level, record.message);
+
+                let tag = match record.level {
+                    Level::ERROR => Some("error"),
+                    Level::WARN => Some("warn"),
+                    _ => None,
+                };
+                let mut end = buffer.end_iter();
+                match tag {
+                    Some(tag) => buffer.insert_with_tags_by_name(&mut end, &line, &[tag]),
+                    None => buffer.insert(&mut end, &line),
+                }
+            }
+            view.scroll_mark_onscreen(&end_mark);
+
+            glib::ControlFlow::Continue
+        });
+
+        Self { widget: revealer }
+    }
+}
+
+impl Default for LogPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
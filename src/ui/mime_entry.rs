@@ -0,0 +1,163 @@
+use std::rc::Rc;
+
+use gtk::prelude::*;
+use gtk::{gdk, glib};
+use gtk::{Entry, Label, ListBox, Popover};
+
+/// Suggestions shown at once
+const MAX_SUGGESTIONS: usize = 8;
+
+/// Up to `limit` of `candidates` containing `query` (ignoring case), those
+/// starting with it first
+pub fn suggest<'a>(candidates: &'a [String], query: &str, limit: usize) -> Vec<&'a str> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches: Vec<&str> = candidates
+        .iter()
+        .map(String::as_str)
+        .filter(|candidate| candidate.to_lowercase().contains(&query))
+        .collect();
+    // Stable sort keeps the order of `candidates` within each group
+    matches.sort_by_key(|candidate| !candidate.to_lowercase().starts_with(&query));
+    matches.truncate(limit);
+    matches
+}
+
+/// An entry for typing a MIME type, suggesting `candidates` (usually the
+/// registry's `known_mime_types`) that match what was typed in a list below
+/// it. Down moves into the list; picking a suggestion fills it in and
+/// activates the entry.
+pub fn mime_entry(candidates: Vec<String>) -> Entry {
+    let entry = Entry::builder()
+        .placeholder_text("type/subtype")
+        .hexpand(true)
+        .build();
+
+    let list = ListBox::builder()
+        .selection_mode(gtk::SelectionMode::Browse)
+        .build();
+    let popover = Popover::builder()
+        .child(&list)
+        .autohide(false)
+        .has_arrow(false)
+        .position(gtk::PositionType::Bottom)
+        .build();
+    popover.set_parent(&entry);
+
+    let candidates = Rc::new(candidates);
+    let list_clone = list.clone();
+    let popover_clone = popover.clone();
+    entry.connect_changed(move |entry| {
+        let text = entry.text();
+        let suggestions = suggest(&candidates, &text, MAX_SUGGESTIONS);
+
+        while let Some(child) = list_clone.first_child() {
+            list_clone.remove(&child);
+        }
+        // Nothing to offer once the only match was typed out in full
+        if suggestions.is_empty() || suggestions == [text.as_str()] {
+            popover_clone.popdown();
+            return;
+        }
+        for suggestion in suggestions {
+            let label = Label::new(Some(suggestion));
+            label.set_halign(gtk::Align::Start);
+            list_clone.append(&label);
+        }
+        popover_clone.popup();
+    });
+
+    let entry_clone = entry.clone();
+    let popover_clone = popover.clone();
+    list.connect_row_activated(move |_, row| {
+        let Some(label) = row.child().and_downcast::<Label>() else {
+            return;
+        };
+        popover_clone.popdown();
+        entry_clone.set_text(&label.text());
+        entry_clone.set_position(-1);
+        entry_clone.grab_focus();
+        entry_clone.emit_activate();
+    });
+
+    let key = gtk::EventControllerKey::new();
+    let list_clone = list.clone();
+    let popover_clone = popover.clone();
+    key.connect_key_pressed(move |_, keyval, _, _| {
+        if !popover_clone.is_visible() {
+            return glib::Propagation::Proceed;
+        }
+        match keyval {
+            gdk::Key::Down | gdk::Key::KP_Down => {
+                if let Some(first) = list_clone.row_at_index(0) {
+                    list_clone.select_row(Some(&first));
+                    first.grab_focus();
+                }
+                glib::Propagation::Stop
+            }
+            gdk::Key::Escape => {
+                popover_clone.popdown();
+                glib::Propagation::Stop
+            }
+            _ => glib::Propagation::Proceed,
+        }
+    });
+    // Before the entry's text field gets to move the focus
+    key.set_propagation_phase(gtk::PropagationPhase::Capture);
+    entry.add_controller(key);
+
+    // Escape in the list goes back to the entry
+    let key = gtk::EventControllerKey::new();
+    let entry_clone = entry.clone();
+    let popover_clone = popover.clone();
+    key.connect_key_pressed(move |_, keyval, _, _| {
+        if keyval == gdk::Key::Escape {
+            popover_clone.popdown();
+            entry_clone.grab_focus();
+            return glib::Propagation::Stop;
+        }
+        glib::Propagation::Proceed
+    });
+    list.add_controller(key);
+
+    let popover_clone = popover.clone();
+    entry.connect_unmap(move |_| popover_clone.popdown());
+    entry.connect_destroy(move |_| popover.unparent());
+
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggest() {
+        let candidates: Vec<String> = [
+            "application/epub+zip",
+            "application/x-mobipocket-ebook",
+            "image/png",
+            "text/x-python",
+        ]
+        .map(str::to_string)
+        .to_vec();
+
+        assert_eq!(
+            suggest(&candidates, "EPUB", 8),
+            vec!["application/epub+zip"]
+        );
+        assert_eq!(
+            suggest(&candidates, "app", 8),
+            vec!["application/epub+zip", "application/x-mobipocket-ebook"]
+        );
+        // Prefix matches come before matches further in
+        assert_eq!(
+            suggest(&candidates, "i", 2),
+            vec!["image/png", "application/epub+zip"]
+        );
+        assert!(suggest(&candidates, "  ", 8).is_empty());
+    }
+}
@@ -1,3 +1,24 @@
+pub mod alternatives_dialog;
+pub mod any_type_dialog;
 pub mod app_row;
+pub mod app_tile;
+pub mod categories_dialog;
+pub mod category_editor;
 pub mod category_page;
+pub mod confirm_dialog;
+pub mod duplicates_dialog;
+pub mod exec_preview;
+pub mod export_report;
+pub mod external_changes;
+pub mod launching;
+pub mod layers_dialog;
+pub mod log_panel;
+pub mod mime_entry;
+pub mod open_with_dialog;
+pub mod presets_dialog;
+pub mod raw_editor;
+pub mod saving;
+pub mod scheme_review_dialog;
+pub mod search;
 pub mod sidebar;
+pub mod skel;
@@ -0,0 +1,218 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gtk::prelude::*;
+use gtk::{gio, glib};
+use gtk::{
+    Box as GtkBox, Button, CheckButton, Label, ListBox, Orientation, ScrolledWindow, Window,
+};
+
+use crate::cli::open::candidates;
+use crate::config::MimeAppsConfig;
+use crate::desktop::discovery::AppRegistry;
+use crate::desktop::entry::AppEntry;
+use crate::ui::launching::launch_app;
+use crate::ui::saving::save_in_background;
+use crate::utils::icons::app_icon;
+use crate::utils::mime::{file_mime_type, mime_description};
+
+/// Create the compact dialog of `--choose`: the applications that can open
+/// `file`, the one that would open it now selected, and a check button for
+/// keeping the choice as the default of its type. Open launches the chosen
+/// app and closes the dialog. The caller presents it.
+pub fn create_open_with_dialog(
+    app: &gtk::Application,
+    file: &gio::File,
+    config: MimeAppsConfig,
+    registry: AppRegistry,
+) -> Window {
+    let dialog = Window::builder()
+        .application(app)
+        .title("Open With")
+        .default_width(420)
+        .default_height(480)
+        .build();
+
+    let content = GtkBox::new(Orientation::Vertical, 12);
+    content.set_margin_start(18);
+    content.set_margin_end(18);
+    content.set_margin_top(18);
+    content.set_margin_bottom(18);
+
+    let name = file
+        .basename()
+        .filter(|_| file.has_uri_scheme("file"))
+        .map_or_else(
+            || file.uri().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+    let heading = Label::new(Some(&format!("Open “{}” with:", name)));
+    heading.set_wrap(true);
+    heading.set_xalign(0.0);
+    heading.add_css_class("heading");
+    content.append(&heading);
+
+    let mime = match file_mime_type(file) {
+        Ok(mime) => mime,
+        Err(e) => {
+            tracing::error!("Can't open {}: {:#}", file.parse_name(), e);
+            let error = Label::new(Some(&format!("{:#}", e)));
+            error.set_wrap(true);
+            error.set_xalign(0.0);
+            error.add_css_class("error");
+            content.append(&error);
+            content.append(&close_button(&dialog));
+            dialog.set_child(Some(&content));
+            return dialog;
+        }
+    };
+    let description = mime_description(&mime);
+    let type_label = Label::new(Some(&match &description {
+        Some(description) => format!("{} ({})", description, mime),
+        None => mime.clone(),
+    }));
+    type_label.set_wrap(true);
+    type_label.set_xalign(0.0);
+    type_label.add_css_class("dim-label");
+    content.append(&type_label);
+
+    let apps: Vec<AppEntry> = candidates(&config, &registry, &mime)
+        .into_iter()
+        .cloned()
+        .collect();
+    if apps.is_empty() {
+        let empty = Label::new(Some("No installed application opens this type"));
+        empty.set_vexpand(true);
+        empty.add_css_class("dim-label");
+        content.append(&empty);
+        content.append(&close_button(&dialog));
+        dialog.set_child(Some(&content));
+        return dialog;
+    }
+
+    let list = ListBox::builder()
+        .selection_mode(gtk::SelectionMode::Browse)
+        .css_classes(["boxed-list"])
+        .build();
+    for app in &apps {
+        list.append(&create_app_row(app));
+    }
+    list.select_row(list.row_at_index(0).as_ref());
+    let scrolled = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .vexpand(true)
+        .child(&list)
+        .build();
+    content.append(&scrolled);
+
+    let remember = CheckButton::with_label(&format!(
+        "Always use for {}",
+        description.as_deref().unwrap_or(&mime)
+    ));
+    if config.settings.locked {
+        remember.set_sensitive(false);
+        remember.set_tooltip_text(Some("Defaults are locked"));
+    }
+    content.append(&remember);
+
+    let button_box = GtkBox::new(Orientation::Horizontal, 6);
+    button_box.set_halign(gtk::Align::End);
+    let cancel_btn = Button::with_label("Cancel");
+    let dialog_clone = dialog.clone();
+    cancel_btn.connect_clicked(move |_| dialog_clone.close());
+    button_box.append(&cancel_btn);
+    let open_btn = Button::with_label("Open");
+    open_btn.add_css_class("suggested-action");
+    button_box.append(&open_btn);
+    content.append(&button_box);
+
+    // The handler gets a local path where there is one, like `open`
+    let target = file
+        .path()
+        .filter(|_| file.has_uri_scheme("file"))
+        .map_or_else(
+            || file.uri().to_string(),
+            |path| path.to_string_lossy().into_owned(),
+        );
+    let config = Rc::new(RefCell::new(config));
+    let open_selected = {
+        let dialog = dialog.clone();
+        let list = list.clone();
+        move || {
+            let Some(app) = list
+                .selected_row()
+                .and_then(|row| apps.get(row.index() as usize))
+            else {
+                return;
+            };
+
+            if remember.is_active() {
+                if let Err(e) = config.borrow_mut().set_default(&mime, &app.id) {
+                    tracing::error!("Failed to set default for {}: {:#}", mime, e);
+                } else {
+                    tracing::info!("Set {} as default for {}", app.id, mime);
+                    save_in_background(&config, || {});
+                }
+            }
+
+            let launch = config.borrow().settings.launch;
+            if let Err(e) = launch_app(app, Some(&target), &launch) {
+                tracing::error!("Failed to launch {}: {:#}", app.id, e);
+            }
+            dialog.close();
+        }
+    };
+    let open_clone = open_selected.clone();
+    list.connect_row_activated(move |_, _| open_clone());
+    open_btn.connect_clicked(move |_| open_selected());
+
+    // Escape cancels
+    let key = gtk::EventControllerKey::new();
+    let dialog_clone = dialog.clone();
+    key.connect_key_pressed(move |_, keyval, _, _| {
+        if keyval == gtk::gdk::Key::Escape {
+            dialog_clone.close();
+            return glib::Propagation::Stop;
+        }
+        glib::Propagation::Proceed
+    });
+    dialog.add_controller(key);
+
+    dialog.set_child(Some(&content));
+    dialog.set_default_widget(Some(&open_btn));
+    dialog
+}
+
+fn close_button(dialog: &Window) -> Button {
+    let close_btn = Button::with_label("Close");
+    close_btn.set_halign(gtk::Align::End);
+    let dialog_clone = dialog.clone();
+    close_btn.connect_clicked(move |_| dialog_clone.close());
+    close_btn
+}
+
+/// The app's icon and name, with its description below
+fn create_app_row(app: &AppEntry) -> GtkBox {
+    let hbox = GtkBox::new(Orientation::Horizontal, 12);
+    hbox.set_margin_start(12);
+    hbox.set_margin_end(12);
+    hbox.set_margin_top(6);
+    hbox.set_margin_bottom(6);
+    hbox.append(&app_icon(app.icon.as_deref(), 32));
+
+    let text_box = GtkBox::new(Orientation::Vertical, 2);
+    text_box.set_hexpand(true);
+    let name_label = Label::new(Some(&app.name));
+    name_label.set_halign(gtk::Align::Start);
+    text_box.append(&name_label);
+    if let Some(description) = app.comment.as_ref().or(app.generic_name.as_ref()) {
+        let description_label = Label::new(Some(description));
+        description_label.set_halign(gtk::Align::Start);
+        description_label.set_wrap(true);
+        description_label.add_css_class("dim-label");
+        description_label.add_css_class("caption");
+        text_box.append(&description_label);
+    }
+    hbox.append(&text_box);
+    hbox
+}
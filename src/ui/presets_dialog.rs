@@ -0,0 +1,193 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Button, Label, ListBox, ListBoxRow, Orientation, ScrolledWindow, Window};
+
+use crate::config::presets::{load_presets, Preset};
+use crate::config::MimeAppsConfig;
+use crate::desktop::discovery::AppRegistry;
+use crate::ui::confirm_dialog::{
+    active_window, confirm_default_change, offer_remove_overrides, PendingChange,
+};
+use crate::ui::saving::save_in_background;
+use crate::utils::mime::mime_description;
+
+/// Show the presets from the presets.d directories, each with an Apply button
+/// that confirms the defaults it would change. `on_applied` runs after a
+/// preset has been applied and saved.
+pub fn show_presets_dialog<F>(
+    registry: Rc<AppRegistry>,
+    config: Rc<RefCell<MimeAppsConfig>>,
+    on_applied: F,
+) where
+    F: Fn() + 'static,
+{
+    let on_applied: Rc<dyn Fn()> = Rc::new(on_applied);
+
+    let dialog = Window::builder()
+        .title("Presets")
+        .modal(true)
+        .default_width(520)
+        .build();
+    dialog.set_transient_for(active_window().as_ref());
+
+    let content = GtkBox::new(Orientation::Vertical, 12);
+    content.set_margin_start(18);
+    content.set_margin_end(18);
+    content.set_margin_top(18);
+    content.set_margin_bottom(18);
+
+    let presets = load_presets();
+    if presets.is_empty() {
+        let label = Label::new(Some(
+            "No presets installed. Add .toml files to ~/.config/xdg-chooser/presets.d \
+             or /usr/share/xdg-chooser/presets.d.",
+        ));
+        label.set_wrap(true);
+        label.add_css_class("dim-label");
+        content.append(&label);
+    } else {
+        let list = ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+
+        for preset in presets {
+            let changes = pending_changes(&preset, &registry, &config.borrow());
+            let (row, apply_btn) = create_row(&preset, changes.len());
+            list.append(&row);
+
+            let dialog = dialog.clone();
+            let config = Rc::clone(&config);
+            let on_applied = Rc::clone(&on_applied);
+            let assignments: HashMap<String, String> = preset
+                .assignments(&config.borrow().settings)
+                .into_iter()
+                .collect();
+            let changes = Rc::new(RefCell::new(Some(changes)));
+            apply_btn.connect_clicked(move |_| {
+                let Some(changes) = changes.borrow_mut().take() else {
+                    return;
+                };
+                dialog.close();
+
+                let config = Rc::clone(&config);
+                let on_applied = Rc::clone(&on_applied);
+                let assignments = assignments.clone();
+                let apply = move |mimes: Vec<String>| {
+                    let mut config_ref = config.borrow_mut();
+                    for mime in &mimes {
+                        if let Err(e) = config_ref.set_default(mime, &assignments[mime]) {
+                            tracing::error!("Failed to set default for {}: {}", mime, e);
+                            return;
+                        }
+                    }
+                    drop(config_ref);
+                    on_applied();
+
+                    // Overrides are only up to date once the save is done
+                    let config_clone = Rc::clone(&config);
+                    let on_applied = Rc::clone(&on_applied);
+                    save_in_background(&config, move || {
+                        offer_remove_overrides(config_clone, &mimes, move || on_applied());
+                    });
+                };
+
+                let heading = format!("Apply the {} preset to:", preset.name);
+                confirm_default_change(&heading, changes, apply);
+            });
+        }
+
+        let scrolled = ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .max_content_height(400)
+            .propagate_natural_height(true)
+            .child(&list)
+            .build();
+        content.append(&scrolled);
+    }
+
+    let close_btn = Button::with_label("Close");
+    close_btn.set_halign(gtk::Align::End);
+    let dialog_clone = dialog.clone();
+    close_btn.connect_clicked(move |_| dialog_clone.close());
+    content.append(&close_btn);
+
+    dialog.set_child(Some(&content));
+    dialog.present();
+}
+
+/// A row with the preset's name, description and source, and its Apply button
+fn create_row(preset: &Preset, change_count: usize) -> (ListBoxRow, Button) {
+    let hbox = GtkBox::new(Orientation::Horizontal, 12);
+    hbox.set_margin_start(12);
+    hbox.set_margin_end(12);
+    hbox.set_margin_top(8);
+    hbox.set_margin_bottom(8);
+
+    let text_box = GtkBox::new(Orientation::Vertical, 2);
+    text_box.set_hexpand(true);
+
+    let name_label = Label::new(Some(&preset.name));
+    name_label.set_halign(gtk::Align::Start);
+    name_label.add_css_class("heading");
+    text_box.append(&name_label);
+
+    if let Some(description) = &preset.description {
+        let desc_label = Label::new(Some(description));
+        desc_label.set_halign(gtk::Align::Start);
+        desc_label.set_wrap(true);
+        desc_label.add_css_class("dim-label");
+        text_box.append(&desc_label);
+    }
+
+    let source = preset.source.map_or("", |source| source.label());
+    let status = match change_count {
+        0 => "already applied".to_string(),
+        1 => "changes 1 type".to_string(),
+        n => format!("changes {} types", n),
+    };
+    let meta_label = Label::new(Some(&format!("{} preset, {}", source, status)));
+    meta_label.set_halign(gtk::Align::Start);
+    meta_label.add_css_class("dim-label");
+    meta_label.add_css_class("caption");
+    text_box.append(&meta_label);
+
+    hbox.append(&text_box);
+
+    let apply_btn = Button::with_label("Apply…");
+    apply_btn.set_valign(gtk::Align::Center);
+    apply_btn.set_sensitive(change_count > 0);
+    hbox.append(&apply_btn);
+
+    let row = ListBoxRow::new();
+    row.set_child(Some(&hbox));
+    (row, apply_btn)
+}
+
+/// The defaults a preset would change, with the apps before and after
+fn pending_changes(
+    preset: &Preset,
+    registry: &AppRegistry,
+    config: &MimeAppsConfig,
+) -> Vec<PendingChange> {
+    let app_name = |id: &str| {
+        registry
+            .get_app(id)
+            .map_or_else(|| format!("{} (not installed)", id), |app| app.name.clone())
+    };
+
+    preset
+        .assignments(&config.settings)
+        .into_iter()
+        .filter(|(mime, app_id)| config.get_default(mime) != Some(app_id.as_str()))
+        .map(|(mime, app_id)| PendingChange {
+            description: mime_description(&mime),
+            current: config.get_default(&mime).map(app_name),
+            proposed: Some(app_name(&app_id)),
+            mime,
+        })
+        .collect()
+}
@@ -0,0 +1,272 @@
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Duration;
+
+use gtk::prelude::*;
+use gtk::{
+    Box as GtkBox, Button, Label, ListBox, ListBoxRow, Orientation, ScrolledWindow, TextBuffer,
+    TextTag, TextView, Window,
+};
+
+use crate::config::diagnostics::{check_document, Diagnostic, Severity};
+use crate::config::document::{LineKind, MimeAppsDocument};
+use crate::ui::confirm_dialog::active_window;
+use crate::utils::atomic::write_atomic;
+
+/// Pause after the last keystroke before the text is checked again; looking
+/// up whether applications are installed takes a moment
+const VALIDATE_DELAY: Duration = Duration::from_millis(300);
+
+/// Show the mimeapps.list at `path` in a text editor that highlights its
+/// sections, keys and comments and lists the problems `lint` would report
+/// as you type. Saving is refused while there are errors; after a save,
+/// `on_saved` runs so the pages can pick up the edits.
+pub fn show_raw_editor<F>(path: PathBuf, on_saved: F)
+where
+    F: Fn() + 'static,
+{
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            tracing::error!("Failed to read {}: {}", path.display(), e);
+            return;
+        }
+    };
+
+    let dialog = Window::builder()
+        .title("Edit mimeapps.list")
+        .modal(true)
+        .default_width(720)
+        .default_height(640)
+        .build();
+    dialog.set_transient_for(active_window().as_ref());
+
+    let content_box = GtkBox::new(Orientation::Vertical, 12);
+    content_box.set_margin_start(18);
+    content_box.set_margin_end(18);
+    content_box.set_margin_top(18);
+    content_box.set_margin_bottom(18);
+
+    let path_label = Label::new(Some(&path.display().to_string()));
+    path_label.set_xalign(0.0);
+    path_label.set_selectable(true);
+    path_label.add_css_class("dim-label");
+    content_box.append(&path_label);
+
+    let buffer = TextBuffer::new(None);
+    create_tags(&buffer);
+    buffer.set_text(&content);
+
+    let view = TextView::builder()
+        .buffer(&buffer)
+        .monospace(true)
+        .top_margin(6)
+        .bottom_margin(6)
+        .left_margin(6)
+        .right_margin(6)
+        .build();
+    let scrolled = ScrolledWindow::builder().vexpand(true).child(&view).build();
+    scrolled.add_css_class("frame");
+    content_box.append(&scrolled);
+
+    let problems = ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    let problems_scrolled = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .propagate_natural_height(true)
+        .max_content_height(140)
+        .child(&problems)
+        .build();
+    content_box.append(&problems_scrolled);
+
+    // Line of each listed problem, for jumping to it
+    let problem_lines: Rc<RefCell<Vec<usize>>> = Rc::new(RefCell::new(Vec::new()));
+    {
+        let problem_lines = Rc::clone(&problem_lines);
+        let view = view.clone();
+        problems.connect_row_activated(move |_, row| {
+            if let Some(&line) = problem_lines.borrow().get(row.index() as usize) {
+                go_to_line(&view, line);
+            }
+        });
+    }
+
+    let buttons = GtkBox::new(Orientation::Horizontal, 6);
+    buttons.set_halign(gtk::Align::End);
+    let cancel_btn = Button::with_label("Cancel");
+    let save_btn = Button::with_label("Save");
+    save_btn.add_css_class("suggested-action");
+    buttons.append(&cancel_btn);
+    buttons.append(&save_btn);
+    content_box.append(&buttons);
+
+    let validate = {
+        let path = path.clone();
+        let view = view.clone();
+        let save_btn = save_btn.clone();
+        Rc::new(move || {
+            let text = buffer_text(&view.buffer());
+            let doc = MimeAppsDocument::parse(&text);
+            highlight(&view.buffer(), &doc);
+
+            let diagnostics = check_document(&doc, &path, true);
+            show_problems(&problems, &diagnostics);
+            problem_lines.replace(diagnostics.iter().map(|d| d.line).collect());
+            save_btn.set_sensitive(
+                !diagnostics
+                    .iter()
+                    .any(|diagnostic| diagnostic.severity == Severity::Error),
+            );
+        })
+    };
+    validate();
+
+    // Check again once typing pauses
+    let pending: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
+    buffer.connect_changed(move |_| {
+        if let Some(source) = pending.take() {
+            source.remove();
+        }
+        let validate = Rc::clone(&validate);
+        let pending_clone = Rc::clone(&pending);
+        let source = glib::timeout_add_local_once(VALIDATE_DELAY, move || {
+            pending_clone.replace(None);
+            validate();
+        });
+        pending.replace(Some(source));
+    });
+
+    let dialog_clone = dialog.clone();
+    cancel_btn.connect_clicked(move |_| dialog_clone.close());
+
+    let dialog_clone = dialog.clone();
+    save_btn.connect_clicked(move |_| {
+        let text = buffer_text(&view.buffer());
+        match write_atomic(&path, &text) {
+            Ok(()) => {
+                tracing::info!("Saved {}", path.display());
+                dialog_clone.close();
+                on_saved();
+            }
+            Err(e) => tracing::error!("Failed to save {}: {:#}", path.display(), e),
+        }
+    });
+
+    dialog.set_child(Some(&content_box));
+    dialog.present();
+}
+
+fn buffer_text(buffer: &TextBuffer) -> String {
+    buffer
+        .text(&buffer.start_iter(), &buffer.end_iter(), false)
+        .to_string()
+}
+
+/// Tags for the highlighting, in colors that read on light and dark themes
+fn create_tags(buffer: &TextBuffer) {
+    let table = buffer.tag_table();
+    table.add(
+        &TextTag::builder()
+            .name("section")
+            .weight(700)
+            .foreground("#3584e4")
+            .build(),
+    );
+    table.add(&TextTag::builder().name("key").weight(600).build());
+    table.add(
+        &TextTag::builder()
+            .name("comment")
+            .style(gtk::pango::Style::Italic)
+            .foreground("#8a8a8a")
+            .build(),
+    );
+    table.add(
+        &TextTag::builder()
+            .name("invalid")
+            .underline(gtk::pango::Underline::Error)
+            .build(),
+    );
+}
+
+/// Re-apply the highlighting tags, line by line
+fn highlight(buffer: &TextBuffer, doc: &MimeAppsDocument) {
+    buffer.remove_all_tags(&buffer.start_iter(), &buffer.end_iter());
+
+    for (index, kind) in doc.lines().enumerate() {
+        let Some(start) = buffer.iter_at_line(index as i32) else {
+            break;
+        };
+        let mut end = start;
+        if !end.ends_line() {
+            end.forward_to_line_end();
+        }
+        let line = buffer.text(&start, &end, false);
+
+        match kind {
+            LineKind::Blank if line.trim_start().starts_with('#') => {
+                buffer.apply_tag_by_name("comment", &start, &end);
+            }
+            LineKind::Blank => {}
+            LineKind::Section(_) => buffer.apply_tag_by_name("section", &start, &end),
+            LineKind::Entry { .. } => {
+                if let Some(equals) = line.find('=') {
+                    let mut key_end = start;
+                    key_end.forward_chars(line[..equals].chars().count() as i32);
+                    buffer.apply_tag_by_name("key", &start, &key_end);
+                }
+            }
+            LineKind::Invalid(_) => buffer.apply_tag_by_name("invalid", &start, &end),
+        }
+    }
+}
+
+/// List the problems found, replacing the previous ones
+fn show_problems(list: &ListBox, diagnostics: &[Diagnostic]) {
+    while let Some(child) = list.first_child() {
+        list.remove(&child);
+    }
+    list.set_visible(!diagnostics.is_empty());
+
+    for diagnostic in diagnostics {
+        let row_box = GtkBox::new(Orientation::Horizontal, 8);
+        row_box.set_margin_start(8);
+        row_box.set_margin_end(8);
+        row_box.set_margin_top(4);
+        row_box.set_margin_bottom(4);
+
+        let (icon, css_class) = match diagnostic.severity {
+            Severity::Error => ("dialog-error-symbolic", "error"),
+            Severity::Warning => ("dialog-warning-symbolic", "warning"),
+        };
+        let image = gtk::Image::from_icon_name(icon);
+        image.add_css_class(css_class);
+        row_box.append(&image);
+
+        let label = Label::new(Some(&format!(
+            "Line {}: {}",
+            diagnostic.line, diagnostic.message
+        )));
+        label.set_xalign(0.0);
+        label.set_wrap(true);
+        row_box.append(&label);
+
+        let row = ListBoxRow::new();
+        row.set_child(Some(&row_box));
+        row.set_activatable(true);
+        list.append(&row);
+    }
+}
+
+/// Move the cursor to a line (starting at 1) and scroll it into view
+fn go_to_line(view: &TextView, line: usize) {
+    let buffer = view.buffer();
+    if let Some(mut iter) = buffer.iter_at_line(line as i32 - 1) {
+        buffer.place_cursor(&iter);
+        view.scroll_to_iter(&mut iter, 0.1, false, 0.0, 0.0);
+        view.grab_focus();
+    }
+}
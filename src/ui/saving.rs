@@ -0,0 +1,146 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use anyhow::anyhow;
+use gtk::prelude::*;
+use gtk::{gio, glib};
+
+use crate::config::MimeAppsConfig;
+
+/// Notification ID, so a new failure replaces the previous one
+const FAILED_ID: &str = "save-failed";
+
+type OnSaved = Box<dyn FnOnce()>;
+
+/// Saves are written one at a time, so an older one can't land last
+#[derive(Default)]
+struct SaveQueue {
+    writing: bool,
+    /// Callbacks of the saves asked for while writing, which one more save
+    /// covers once the current one is done
+    waiting: Option<Vec<OnSaved>>,
+    /// Run once the last save is done, see [`after_saves`]
+    after: Vec<OnSaved>,
+}
+
+thread_local! {
+    static QUEUE: RefCell<SaveQueue> = RefCell::default();
+}
+
+/// Save the user's mimeapps.list on a worker thread, so a slow or network
+/// home directory doesn't freeze the window. The config already holds the
+/// changes, so the pages can be rebuilt right away; `on_saved` runs once
+/// they are on disk. Saves asked for while one is being written follow it,
+/// with whatever the config holds by then. Failures are logged and shown
+/// in a notification.
+pub fn save_in_background<F>(config: &Rc<RefCell<MimeAppsConfig>>, on_saved: F)
+where
+    F: FnOnce() + 'static,
+{
+    let on_saved: OnSaved = Box::new(on_saved);
+    let queued = QUEUE.with_borrow_mut(|queue| {
+        if queue.writing {
+            queue.waiting.get_or_insert_with(Vec::new).push(on_saved);
+            None
+        } else {
+            Some(on_saved)
+        }
+    });
+    if let Some(on_saved) = queued {
+        start_save(config, vec![on_saved]);
+    }
+}
+
+/// Whether a save is being written, or waiting for the one that is
+pub fn is_saving() -> bool {
+    QUEUE.with_borrow(|queue| queue.writing)
+}
+
+/// Run `callback` once every save asked for so far is done, right away if
+/// there are none. Failed saves count as done.
+pub fn after_saves<F>(callback: F)
+where
+    F: FnOnce() + 'static,
+{
+    if is_saving() {
+        QUEUE.with_borrow_mut(|queue| queue.after.push(Box::new(callback)));
+    } else {
+        callback();
+    }
+}
+
+fn start_save(config: &Rc<RefCell<MimeAppsConfig>>, callbacks: Vec<OnSaved>) {
+    let job = match config.borrow_mut().prepare_save() {
+        Ok(Some(job)) => job,
+        // Demo mode: nothing to write
+        Ok(None) => {
+            callbacks.into_iter().for_each(|callback| callback());
+            return;
+        }
+        Err(e) => {
+            report_failure(&e);
+            return;
+        }
+    };
+    QUEUE.with_borrow_mut(|queue| queue.writing = true);
+
+    // Keep running until the file is written, even if the window closes
+    let hold = gio::Application::default().map(|app| app.hold());
+    let config = Rc::clone(config);
+    glib::spawn_future_local(async move {
+        let result = gio::spawn_blocking(move || {
+            let result = job.run();
+            (job, result)
+        })
+        .await;
+
+        let saved = match result {
+            Ok((job, Ok(desktop_synced))) => {
+                config.borrow_mut().finish_save(job, desktop_synced);
+                tracing::debug!("Saved default applications");
+                true
+            }
+            Ok((_, Err(e))) => {
+                report_failure(&e);
+                false
+            }
+            Err(_) => {
+                report_failure(&anyhow!("Writing the file panicked"));
+                false
+            }
+        };
+
+        let waiting = QUEUE.with_borrow_mut(|queue| {
+            queue.writing = false;
+            queue.waiting.take()
+        });
+        if let Some(waiting) = waiting {
+            start_save(&config, waiting);
+        }
+        if saved {
+            callbacks.into_iter().for_each(|callback| callback());
+        }
+        // Unless the waiting saves are being written now
+        let after = QUEUE.with_borrow_mut(|queue| {
+            if queue.writing {
+                Vec::new()
+            } else {
+                std::mem::take(&mut queue.after)
+            }
+        });
+        after.into_iter().for_each(|callback| callback());
+        drop(hold);
+    });
+}
+
+/// Log a failed save and tell the user, as the change they just made only
+/// lives in the window now
+fn report_failure(error: &anyhow::Error) {
+    tracing::error!("Failed to save config: {:#}", error);
+
+    if let Some(app) = gio::Application::default() {
+        let notification = gio::Notification::new("Couldn't save default applications");
+        notification.set_body(Some(&format!("{:#}", error)));
+        app.send_notification(Some(FAILED_ID), &notification);
+    }
+}
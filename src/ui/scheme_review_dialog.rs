@@ -0,0 +1,122 @@
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Button, Image, Label, ListBox, Orientation, ScrolledWindow, Window};
+
+use crate::config::MimeAppsConfig;
+use crate::desktop::discovery::AppRegistry;
+use crate::desktop::scheme_review::{review_scheme_handlers, SchemeHandlerReview};
+use crate::ui::confirm_dialog::active_window;
+use crate::utils::icons::app_icon;
+
+/// Show the URL scheme handlers that are installed for the user alone,
+/// newly added or run a shell, as `review-schemes` lists them
+pub fn show_scheme_review_dialog(config: &MimeAppsConfig, registry: &AppRegistry) {
+    let reviews = review_scheme_handlers(registry, &config.effective_defaults());
+
+    let dialog = Window::builder()
+        .title("Review Scheme Handlers")
+        .modal(true)
+        .default_width(600)
+        .default_height(520)
+        .build();
+    dialog.set_transient_for(active_window().as_ref());
+
+    let content = GtkBox::new(Orientation::Vertical, 12);
+    content.set_margin_start(18);
+    content.set_margin_end(18);
+    content.set_margin_top(18);
+    content.set_margin_bottom(18);
+
+    let intro = Label::new(Some(
+        "Links on web pages and in messages are passed to these applications. \
+         Check that you know where each one came from.",
+    ));
+    intro.set_wrap(true);
+    intro.set_xalign(0.0);
+    intro.add_css_class("dim-label");
+    content.append(&intro);
+
+    if reviews.is_empty() {
+        let empty = Label::new(Some("No scheme handler stands out"));
+        empty.set_vexpand(true);
+        empty.add_css_class("dim-label");
+        content.append(&empty);
+    } else {
+        let list = ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        for review in &reviews {
+            let icon = registry
+                .get_app(&review.app_id)
+                .and_then(|app| app.icon.as_deref());
+            list.append(&create_review_row(review, icon));
+        }
+
+        let scrolled = ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vexpand(true)
+            .child(&list)
+            .build();
+        content.append(&scrolled);
+    }
+
+    let close_btn = Button::with_label("Close");
+    close_btn.set_halign(gtk::Align::End);
+    let dialog_clone = dialog.clone();
+    close_btn.connect_clicked(move |_| dialog_clone.close());
+    content.append(&close_btn);
+
+    dialog.set_child(Some(&content));
+    dialog.present();
+}
+
+/// A row with the app, its desktop file, its schemes and what stands out
+fn create_review_row(review: &SchemeHandlerReview, icon: Option<&str>) -> GtkBox {
+    let hbox = GtkBox::new(Orientation::Horizontal, 12);
+    hbox.set_margin_start(12);
+    hbox.set_margin_end(12);
+    hbox.set_margin_top(8);
+    hbox.set_margin_bottom(8);
+
+    let icon = app_icon(icon, 32);
+    icon.set_valign(gtk::Align::Start);
+    hbox.append(&icon);
+
+    let text_box = GtkBox::new(Orientation::Vertical, 2);
+    text_box.set_hexpand(true);
+
+    let name_label = Label::new(Some(&review.name));
+    name_label.set_halign(gtk::Align::Start);
+    name_label.add_css_class("heading");
+    text_box.append(&name_label);
+
+    let path_label = Label::new(Some(&review.path.display().to_string()));
+    path_label.set_halign(gtk::Align::Start);
+    path_label.set_selectable(true);
+    path_label.set_wrap(true);
+    path_label.add_css_class("dim-label");
+    text_box.append(&path_label);
+
+    let mut schemes = format!("Handles {}", review.schemes.join(", "));
+    if !review.default_for.is_empty() {
+        schemes.push_str(&format!("; default for {}", review.default_for.join(", ")));
+    }
+    let schemes_label = Label::new(Some(&schemes));
+    schemes_label.set_halign(gtk::Align::Start);
+    schemes_label.set_wrap(true);
+    text_box.append(&schemes_label);
+
+    for risk in &review.risks {
+        let risk_box = GtkBox::new(Orientation::Horizontal, 6);
+        let warning = Image::from_icon_name("dialog-warning-symbolic");
+        warning.add_css_class("warning");
+        risk_box.append(&warning);
+        let label = Label::new(Some(&risk.description()));
+        label.add_css_class("warning");
+        risk_box.append(&label);
+        text_box.append(&risk_box);
+    }
+
+    hbox.append(&text_box);
+    hbox
+}
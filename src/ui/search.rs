@@ -0,0 +1,115 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::Expander;
+
+use crate::desktop::entry::AppEntry;
+
+/// Entries matched per main loop iteration, so typing stays responsive
+/// however many applications a page lists
+const CHUNK_SIZE: usize = 64;
+
+/// Shows only the application entries of a page that match the search.
+/// Matching runs in idle chunks, and a newer query cancels the chunks of
+/// the previous one.
+#[derive(Clone, Default)]
+pub struct AppFilter {
+    /// Each row or tile with the lowercased text it is matched against
+    entries: Rc<RefCell<Vec<(gtk::Widget, String)>>>,
+    /// "Show all" expanders, opened while searching so no match is hidden
+    expanders: Rc<RefCell<Vec<Expander>>>,
+    /// Bumped for every query; chunks of older queries stop when they see it
+    generation: Rc<Cell<u64>>,
+}
+
+impl AppFilter {
+    pub fn add_entry(&self, widget: &impl IsA<gtk::Widget>, app: &AppEntry) {
+        self.entries
+            .borrow_mut()
+            .push((widget.clone().upcast(), search_text(app)));
+    }
+
+    pub fn add_expander(&self, expander: &Expander) {
+        self.expanders.borrow_mut().push(expander.clone());
+    }
+
+    /// Show the entries matching `query`, or all of them if it is empty
+    pub fn apply(&self, query: &str) {
+        let generation = self.generation.get() + 1;
+        self.generation.set(generation);
+
+        let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        if !terms.is_empty() {
+            for expander in self.expanders.borrow().iter() {
+                expander.set_expanded(true);
+            }
+        }
+
+        let filter = self.clone();
+        let mut next = 0;
+        glib::idle_add_local(move || {
+            if filter.generation.get() != generation {
+                return glib::ControlFlow::Break;
+            }
+
+            let entries = filter.entries.borrow();
+            let end = (next + CHUNK_SIZE).min(entries.len());
+            for (widget, text) in &entries[next..end] {
+                widget.set_visible(matches(text, &terms));
+            }
+            next = end;
+
+            if next == entries.len() {
+                glib::ControlFlow::Break
+            } else {
+                glib::ControlFlow::Continue
+            }
+        });
+    }
+}
+
+/// Name, generic name, comment and ID of an application, lowercased
+fn search_text(app: &AppEntry) -> String {
+    [
+        Some(app.name.as_str()),
+        app.generic_name.as_deref(),
+        app.comment.as_deref(),
+        Some(&*app.id),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join("\n")
+    .to_lowercase()
+}
+
+/// Whether the text contains every (lowercased) search term
+fn matches(text: &str, terms: &[String]) -> bool {
+    terms.iter().all(|term| text.contains(term.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_every_term() {
+        let app = AppEntry::parse(
+            "[Desktop Entry]\nType=Application\nName=Firefox\nGenericName=Web Browser\nExec=firefox %u\n",
+            std::path::Path::new("/usr/share/applications/firefox.desktop"),
+            &[],
+        )
+        .unwrap();
+        let text = search_text(&app);
+        let terms = |query: &str| -> Vec<String> {
+            query.split_whitespace().map(str::to_lowercase).collect()
+        };
+
+        assert!(matches(&text, &terms("fire")));
+        assert!(matches(&text, &terms("WEB firefox.desktop")));
+        assert!(!matches(&text, &terms("web chromium")));
+        assert!(matches(&text, &terms("")));
+    }
+}
@@ -1,39 +1,117 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
 use gtk::prelude::*;
-use gtk::{Box as GtkBox, Label, ListBox, ListBoxRow, Orientation};
+use gtk::{gdk, glib};
+use gtk::{Box as GtkBox, DragSource, DropTarget, Image, Label, ListBox, ListBoxRow, Orientation};
 
+use crate::config::settings::Settings;
+use crate::config::MimeAppsConfig;
 use crate::desktop::categories::AppCategory;
 use crate::utils::icons::category_icon;
 
-/// Navigation sidebar for selecting application categories
+/// Navigation sidebar for selecting application categories. Rows can be
+/// dragged, or moved with Alt+Up and Alt+Down, to reorder the categories.
+/// Typing while it has focus jumps to the first matching category.
 pub struct CategorySidebar {
     pub widget: ListBox,
-    categories: Vec<AppCategory>,
+    /// One per row of `widget`, in the same order
+    rows: Rc<RefCell<Vec<SidebarRow>>>,
+    on_reordered: Rc<ReorderedCallback>,
+    on_search: Rc<SearchCallback>,
+}
+
+type ReorderedCallback = RefCell<Option<Box<dyn Fn(&[AppCategory])>>>;
+type SearchCallback = RefCell<Option<Box<dyn Fn(&str)>>>;
+
+/// Pause after which the next key press starts a new type-ahead search
+const TYPE_AHEAD_TIMEOUT_US: i64 = 1_000_000;
+
+/// Text typed into the sidebar so far
+#[derive(Default)]
+struct TypeAhead {
+    text: String,
+    /// Monotonic time of the last key, in microseconds
+    last_key: i64,
+    /// Whether `text` matched a category
+    matched: bool,
+}
+
+struct SidebarRow {
+    category: AppCategory,
+    icon: Image,
+    status: Image,
 }
 
 impl CategorySidebar {
-    pub fn new() -> Self {
+    pub fn new(categories: Vec<AppCategory>) -> Self {
         let widget = ListBox::builder()
             .selection_mode(gtk::SelectionMode::Single)
             .css_classes(["navigation-sidebar"])
             .vexpand(true)
             .build();
 
-        let categories = AppCategory::all();
+        let sidebar = Self {
+            widget,
+            rows: Rc::new(RefCell::new(Vec::with_capacity(categories.len()))),
+            on_reordered: Rc::new(RefCell::new(None)),
+            on_search: Rc::new(RefCell::new(None)),
+        };
 
-        for category in &categories {
-            let row = Self::create_row(category);
-            widget.append(&row);
+        for category in categories {
+            sidebar.append_category(category);
         }
+        sidebar.connect_move_keys();
+        sidebar.connect_type_ahead();
 
         // Select first row by default
-        if let Some(first) = widget.row_at_index(0) {
-            widget.select_row(Some(&first));
+        if let Some(first) = sidebar.widget.row_at_index(0) {
+            sidebar.widget.select_row(Some(&first));
+        }
+
+        sidebar
+    }
+
+    fn append_category(&self, category: AppCategory) {
+        let (row, icon, status) = Self::create_row(&category);
+        self.connect_drag_and_drop(&row, &category);
+        self.widget.append(&row);
+        self.rows.borrow_mut().push(SidebarRow {
+            category,
+            icon,
+            status,
+        });
+    }
+
+    /// List other categories, after user-defined ones were added, changed
+    /// or deleted. The selected category stays selected (by ID, so an
+    /// edited one does too) if it is still listed; otherwise the first one
+    /// is.
+    pub fn set_categories(&self, categories: Vec<AppCategory>) {
+        let selected_id = self
+            .widget
+            .selected_row()
+            .and_then(|row| self.category_at(row.index() as usize))
+            .map(|category| category.id().to_string());
+
+        while let Some(child) = self.widget.first_child() {
+            self.widget.remove(&child);
+        }
+        self.rows.borrow_mut().clear();
+        let index = categories
+            .iter()
+            .position(|category| Some(category.id()) == selected_id.as_deref())
+            .unwrap_or(0);
+        for category in categories {
+            self.append_category(category);
         }
 
-        Self { widget, categories }
+        if let Some(row) = self.widget.row_at_index(index as i32) {
+            self.widget.select_row(Some(&row));
+        }
     }
 
-    fn create_row(category: &AppCategory) -> ListBoxRow {
+    fn create_row(category: &AppCategory) -> (ListBoxRow, Image, Image) {
         let hbox = GtkBox::new(Orientation::Horizontal, 12);
         hbox.set_margin_start(12);
         hbox.set_margin_end(12);
@@ -48,9 +126,249 @@ impl CategorySidebar {
         label.set_hexpand(true);
         hbox.append(&label);
 
+        let status = Image::new();
+        status.set_pixel_size(16);
+        hbox.append(&status);
+
         let row = ListBoxRow::new();
         row.set_child(Some(&hbox));
-        row
+        (row, icon, status)
+    }
+
+    /// Let the row be dragged by its category ID, and dropped on to move
+    /// the dragged category to its place
+    fn connect_drag_and_drop(&self, row: &ListBoxRow, category: &AppCategory) {
+        let drag = DragSource::new();
+        drag.set_actions(gdk::DragAction::MOVE);
+        drag.set_content(Some(&gdk::ContentProvider::for_value(
+            &category.id().to_value(),
+        )));
+        row.add_controller(drag);
+
+        let drop = DropTarget::new(String::static_type(), gdk::DragAction::MOVE);
+        let widget = self.widget.clone();
+        let rows = Rc::clone(&self.rows);
+        let on_reordered = Rc::clone(&self.on_reordered);
+        drop.connect_drop(move |target, value, _, _| {
+            let Ok(id) = value.get::<String>() else {
+                return false;
+            };
+            let Some(to) = target.widget().and_downcast::<ListBoxRow>() else {
+                return false;
+            };
+            let from = rows.borrow().iter().position(|r| r.category.id() == id);
+            let Some(from) = from else {
+                return false;
+            };
+            move_row(&widget, &rows, &on_reordered, from, to.index() as usize);
+            true
+        });
+        row.add_controller(drop);
+    }
+
+    /// Move the selected category up or down with Alt+Up and Alt+Down
+    fn connect_move_keys(&self) {
+        let key = gtk::EventControllerKey::new();
+        let widget = self.widget.clone();
+        let rows = Rc::clone(&self.rows);
+        let on_reordered = Rc::clone(&self.on_reordered);
+        key.connect_key_pressed(move |_, keyval, _, state| {
+            if !state.contains(gdk::ModifierType::ALT_MASK) {
+                return glib::Propagation::Proceed;
+            }
+            let Some(from) = widget.selected_row().map(|row| row.index() as usize) else {
+                return glib::Propagation::Proceed;
+            };
+            let to = match keyval {
+                gdk::Key::Up | gdk::Key::KP_Up => from.checked_sub(1),
+                gdk::Key::Down | gdk::Key::KP_Down => {
+                    Some(from + 1).filter(|&to| to < rows.borrow().len())
+                }
+                _ => return glib::Propagation::Proceed,
+            };
+            if let Some(to) = to {
+                move_row(&widget, &rows, &on_reordered, from, to);
+            }
+            glib::Propagation::Stop
+        });
+        self.widget.add_controller(key);
+    }
+
+    /// Select the first visible category matching what was typed. Enter
+    /// after text that matched nothing hands it to the search instead.
+    fn connect_type_ahead(&self) {
+        let key = gtk::EventControllerKey::new();
+        let widget = self.widget.clone();
+        let rows = Rc::clone(&self.rows);
+        let on_search = Rc::clone(&self.on_search);
+        let type_ahead = Rc::new(RefCell::new(TypeAhead::default()));
+        key.connect_key_pressed(move |_, keyval, _, state| {
+            let mut type_ahead = type_ahead.borrow_mut();
+            let now = glib::monotonic_time();
+            if now - type_ahead.last_key > TYPE_AHEAD_TIMEOUT_US {
+                type_ahead.text.clear();
+                type_ahead.matched = false;
+            }
+            type_ahead.last_key = now;
+
+            match keyval {
+                gdk::Key::Return | gdk::Key::KP_Enter => {
+                    let text = std::mem::take(&mut type_ahead.text);
+                    if text.is_empty() || type_ahead.matched {
+                        return glib::Propagation::Proceed;
+                    }
+                    drop(type_ahead);
+                    if let Some(callback) = on_search.borrow().as_ref() {
+                        callback(&text);
+                    }
+                    return glib::Propagation::Stop;
+                }
+                gdk::Key::Escape => {
+                    type_ahead.text.clear();
+                    return glib::Propagation::Proceed;
+                }
+                gdk::Key::BackSpace if !type_ahead.text.is_empty() => {
+                    type_ahead.text.pop();
+                }
+                _ => {
+                    let modifiers = gdk::ModifierType::CONTROL_MASK
+                        | gdk::ModifierType::ALT_MASK
+                        | gdk::ModifierType::SUPER_MASK;
+                    match keyval.to_unicode() {
+                        Some(c) if !c.is_control() && !state.intersects(modifiers) => {
+                            // A leading space is for activating the row
+                            if c == ' ' && type_ahead.text.is_empty() {
+                                return glib::Propagation::Proceed;
+                            }
+                            type_ahead.text.push(c);
+                        }
+                        _ => return glib::Propagation::Proceed,
+                    }
+                }
+            }
+
+            // Only categories still listed can be jumped to
+            let rows = rows.borrow();
+            let names: Vec<Option<&str>> = rows
+                .iter()
+                .enumerate()
+                .map(|(index, row)| {
+                    widget
+                        .row_at_index(index as i32)
+                        .is_some_and(|r| r.is_visible())
+                        .then(|| row.category.display_name())
+                })
+                .collect();
+            let found = find_match(&names, &type_ahead.text);
+            drop(rows);
+            type_ahead.matched = found.is_some();
+            drop(type_ahead);
+
+            if let Some(row) = found.and_then(|index| widget.row_at_index(index as i32)) {
+                widget.select_row(Some(&row));
+                row.grab_focus();
+            }
+            glib::Propagation::Stop
+        });
+        self.widget.add_controller(key);
+    }
+
+    /// Connect a callback for text typed into the sidebar that matched no
+    /// category, confirmed with Enter
+    pub fn connect_search_requested<F>(&self, callback: F)
+    where
+        F: Fn(&str) + 'static,
+    {
+        *self.on_search.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Connect a callback for when the user changed the order, given the
+    /// categories in their new order
+    pub fn connect_reordered<F>(&self, callback: F)
+    where
+        F: Fn(&[AppCategory]) + 'static,
+    {
+        *self.on_reordered.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Look the category icons up again, after the icon theme changed
+    pub fn refresh_icons(&self) {
+        for row in self.rows.borrow_mut().iter_mut() {
+            let Some(hbox) = row.icon.parent().and_downcast::<GtkBox>() else {
+                continue;
+            };
+            let fresh = category_icon(row.category.icon_name(), 24);
+            hbox.insert_child_after(&fresh, None::<&gtk::Widget>);
+            hbox.remove(&row.icon);
+            row.icon = fresh;
+        }
+    }
+
+    /// Annotate each category with how many of its primary MIME types have
+    /// a default set
+    pub fn update_status(&self, config: &MimeAppsConfig) {
+        for SidebarRow {
+            category, status, ..
+        } in self.rows.borrow().iter()
+        {
+            let mimes = config.settings.primary_mime_types(category);
+            let configured = mimes
+                .iter()
+                .filter(|mime| config.get_default(mime).is_some())
+                .count();
+
+            for class in ["success", "warning", "error"] {
+                status.remove_css_class(class);
+            }
+
+            let (icon, class, tooltip) = if configured == mimes.len() {
+                (
+                    "emblem-ok-symbolic",
+                    "success",
+                    "All types have a default".to_string(),
+                )
+            } else if configured > 0 {
+                (
+                    "dialog-warning-symbolic",
+                    "warning",
+                    format!("{} of {} types have a default", configured, mimes.len()),
+                )
+            } else {
+                (
+                    "dialog-error-symbolic",
+                    "error",
+                    "No default application set".to_string(),
+                )
+            };
+
+            status.set_icon_name(Some(icon));
+            status.add_css_class(class);
+            status.set_tooltip_text(Some(&tooltip));
+        }
+    }
+
+    /// Show only the categories the settings don't hide. If the selected
+    /// one is hidden, the first visible category is selected instead.
+    pub fn update_visibility(&self, settings: &Settings) {
+        for (index, row) in self.rows.borrow().iter().enumerate() {
+            if let Some(list_row) = self.widget.row_at_index(index as i32) {
+                list_row.set_visible(!settings.is_category_hidden(&row.category));
+            }
+        }
+
+        if self
+            .widget
+            .selected_row()
+            .is_some_and(|row| row.is_visible())
+        {
+            return;
+        }
+        let first_visible = (0..self.len() as i32)
+            .filter_map(|index| self.widget.row_at_index(index))
+            .find(|row| row.is_visible());
+        if let Some(row) = first_visible {
+            self.widget.select_row(Some(&row));
+        }
     }
 
     /// Connect a callback for when a category is selected
@@ -58,35 +376,135 @@ impl CategorySidebar {
     where
         F: Fn(AppCategory) + 'static,
     {
-        let categories = self.categories.clone();
+        let rows = Rc::clone(&self.rows);
         self.widget.connect_row_selected(move |_, row| {
             if let Some(row) = row {
                 let index = row.index() as usize;
-                if let Some(category) = categories.get(index) {
-                    callback(category.clone());
+                let category = rows.borrow().get(index).map(|r| r.category.clone());
+                if let Some(category) = category {
+                    callback(category);
                 }
             }
         });
     }
 
+    /// Select a category's row, which also switches to its page
+    pub fn select_category(&self, category: &AppCategory) {
+        let index = self
+            .rows
+            .borrow()
+            .iter()
+            .position(|r| r.category == *category);
+        let Some(index) = index else {
+            return;
+        };
+        if let Some(row) = self.widget.row_at_index(index as i32) {
+            self.widget.select_row(Some(&row));
+        }
+    }
+
+    /// The categories listed, in order
+    pub fn categories(&self) -> Vec<AppCategory> {
+        self.rows
+            .borrow()
+            .iter()
+            .map(|r| r.category.clone())
+            .collect()
+    }
+
     /// Get the category at the given index
-    pub fn category_at(&self, index: usize) -> Option<&AppCategory> {
-        self.categories.get(index)
+    pub fn category_at(&self, index: usize) -> Option<AppCategory> {
+        self.rows.borrow().get(index).map(|r| r.category.clone())
     }
 
     /// Get the number of categories
     pub fn len(&self) -> usize {
-        self.categories.len()
+        self.rows.borrow().len()
     }
 
     /// Check if sidebar is empty
     pub fn is_empty(&self) -> bool {
-        self.categories.is_empty()
+        self.rows.borrow().is_empty()
     }
 }
 
 impl Default for CategorySidebar {
     fn default() -> Self {
-        Self::new()
+        Self::new(AppCategory::all())
+    }
+}
+
+/// Index of the first name starting with the typed text, or else the first
+/// containing it, ignoring case. `None` names are skipped.
+fn find_match(names: &[Option<&str>], text: &str) -> Option<usize> {
+    let text = text.to_lowercase();
+    let lowered: Vec<Option<String>> = names
+        .iter()
+        .map(|name| name.map(str::to_lowercase))
+        .collect();
+    lowered
+        .iter()
+        .position(|name| name.as_ref().is_some_and(|n| n.starts_with(&text)))
+        .or_else(|| {
+            lowered
+                .iter()
+                .position(|name| name.as_ref().is_some_and(|n| n.contains(&text)))
+        })
+}
+
+/// Move the row at `from` to `to`, keeping it selected if it was, and
+/// report the new order
+fn move_row(
+    widget: &ListBox,
+    rows: &RefCell<Vec<SidebarRow>>,
+    on_reordered: &ReorderedCallback,
+    from: usize,
+    to: usize,
+) {
+    if from == to {
+        return;
+    }
+    let Some(list_row) = widget.row_at_index(from as i32) else {
+        return;
+    };
+    let was_selected = list_row.is_selected();
+
+    widget.remove(&list_row);
+    widget.insert(&list_row, to as i32);
+    {
+        let mut rows = rows.borrow_mut();
+        let row = rows.remove(from);
+        rows.insert(to, row);
+    }
+
+    if was_selected {
+        widget.select_row(Some(&list_row));
+        list_row.grab_focus();
+    }
+
+    let categories: Vec<AppCategory> = rows.borrow().iter().map(|r| r.category.clone()).collect();
+    if let Some(callback) = on_reordered.borrow().as_ref() {
+        callback(&categories);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_match() {
+        let names = [
+            Some("Web Browser"),
+            None,
+            Some("Email Client"),
+            Some("Text Editor"),
+            Some("Video Editor"),
+        ];
+
+        assert_eq!(find_match(&names, "te"), Some(3));
+        assert_eq!(find_match(&names, "EDIT"), Some(3));
+        assert_eq!(find_match(&names, "vid"), Some(4));
+        assert_eq!(find_match(&names, "file"), None);
     }
 }
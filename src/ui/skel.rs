@@ -0,0 +1,119 @@
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use gtk::AlertDialog;
+
+use crate::ui::confirm_dialog::active_window;
+
+/// Where new accounts get their mimeapps.list from
+const SKEL_MIMEAPPS: &str = "/etc/skel/.config/mimeapps.list";
+
+/// pkexec's exit status when the user dismisses the authentication dialog
+const PKEXEC_DISMISSED: i32 = 126;
+
+/// Ask for confirmation, then copy `source` (the user's mimeapps.list) into
+/// `/etc/skel/.config/` through pkexec, so polkit asks for an administrator
+/// password and newly created accounts start with the same defaults
+pub fn apply_to_skel(source: PathBuf) {
+    if !source.exists() {
+        let detail = format!(
+            "{} doesn't exist yet; set a default first",
+            source.display()
+        );
+        tracing::error!("{}", detail);
+        show_outcome("Nothing to Apply", &detail);
+        return;
+    }
+
+    let dialog = AlertDialog::builder()
+        .modal(true)
+        .message("Apply Defaults to New Accounts?")
+        .detail(format!(
+            "Your mimeapps.list will be copied to {}, replacing any that is \
+             there, for accounts created from now on. Existing accounts are \
+             not changed. Applications installed only for you won't be \
+             available to them.",
+            SKEL_MIMEAPPS
+        ))
+        .buttons(["Cancel", "Copy"])
+        .cancel_button(0)
+        .default_button(0)
+        .build();
+
+    dialog.choose(
+        active_window().as_ref(),
+        gio::Cancellable::NONE,
+        move |result| {
+            if result != Ok(1) {
+                return;
+            }
+            copy_with_pkexec(&source);
+        },
+    );
+}
+
+/// Run `pkexec install` in the background, then tell the user how it went
+fn copy_with_pkexec(source: &Path) {
+    let argv: [&OsStr; 7] = [
+        "pkexec".as_ref(),
+        "install".as_ref(),
+        "-D".as_ref(),
+        "-m".as_ref(),
+        "644".as_ref(),
+        source.as_os_str(),
+        SKEL_MIMEAPPS.as_ref(),
+    ];
+    let process = match gio::Subprocess::newv(&argv, gio::SubprocessFlags::NONE) {
+        Ok(process) => process,
+        Err(e) => {
+            report_failure(&format!("Failed to run pkexec: {}", e));
+            return;
+        }
+    };
+
+    process.wait_async(gio::Cancellable::NONE, {
+        let process = process.clone();
+        move |result| {
+            if let Err(e) = result {
+                report_failure(&format!("Failed to wait for pkexec: {}", e));
+                return;
+            }
+            if !process.has_exited() {
+                report_failure("pkexec was killed");
+                return;
+            }
+            match process.exit_status() {
+                0 => {
+                    tracing::info!("Copied the defaults to {}", SKEL_MIMEAPPS);
+                    show_outcome(
+                        "Defaults Applied to New Accounts",
+                        &format!("Copied your mimeapps.list to {}.", SKEL_MIMEAPPS),
+                    );
+                }
+                // The user chose not to authenticate, so nothing went wrong
+                PKEXEC_DISMISSED => tracing::info!("Authentication dismissed, nothing copied"),
+                status => report_failure(&format!(
+                    "Failed to copy the defaults to {} (exit status {})",
+                    SKEL_MIMEAPPS, status
+                )),
+            }
+        }
+    });
+}
+
+/// Log a failed copy and tell the user about it
+fn report_failure(detail: &str) {
+    tracing::error!("{}", detail);
+    show_outcome("Couldn't Apply Defaults to New Accounts", detail);
+}
+
+/// Show how applying the defaults went in a dialog with a Close button
+fn show_outcome(message: &str, detail: &str) {
+    AlertDialog::builder()
+        .modal(true)
+        .message(message)
+        .detail(detail)
+        .buttons(["Close"])
+        .build()
+        .show(active_window().as_ref());
+}
@@ -1,28 +1,83 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
 use gtk::gdk;
 
+/// Where an icon was found
+#[derive(Debug, Clone)]
+enum IconSource {
+    Theme,
+    File(PathBuf),
+}
+
+thread_local! {
+    /// Lookup results by (name, size), including misses, so rows showing
+    /// the same icons on other pages don't search the theme again
+    static ICON_CACHE: RefCell<HashMap<(String, i32), Option<IconSource>>> =
+        RefCell::new(HashMap::new());
+}
+
 /// Look up an icon from the current theme with fallback
 pub fn lookup_icon(icon_name: &str, size: i32) -> Option<gtk::Image> {
     let display = gdk::Display::default()?;
     let theme = gtk::IconTheme::for_display(&display);
 
+    let key = (icon_name.to_string(), size);
+    let source = match ICON_CACHE.with_borrow(|cache| cache.get(&key).cloned()) {
+        Some(source) => source,
+        None => {
+            let source = find_icon(&theme, icon_name, size);
+            ICON_CACHE.with_borrow_mut(|cache| cache.insert(key, source.clone()));
+            source
+        }
+    };
+
+    let image = match source? {
+        IconSource::Theme => gtk::Image::from_icon_name(icon_name),
+        IconSource::File(path) => gtk::Image::from_file(&path),
+    };
+    image.set_pixel_size(size);
+    Some(image)
+}
+
+/// Forget every lookup result, after the icon theme changed
+pub fn clear_icon_cache() {
+    ICON_CACHE.with_borrow_mut(HashMap::clear);
+}
+
+fn find_icon(theme: &gtk::IconTheme, icon_name: &str, size: i32) -> Option<IconSource> {
     // Check if the icon exists in the theme
     if theme.has_icon(icon_name) {
-        let image = gtk::Image::from_icon_name(icon_name);
-        image.set_pixel_size(size);
-        return Some(image);
+        return Some(IconSource::Theme);
     }
 
     // Try freedesktop-icons as fallback
-    if let Some(path) = freedesktop_icons::lookup(icon_name)
+    freedesktop_icons::lookup(icon_name)
         .with_size(size as u16)
         .find()
-    {
-        let image = gtk::Image::from_file(&path);
-        image.set_pixel_size(size);
-        return Some(image);
-    }
+        .map(IconSource::File)
+}
+
+/// An icon as a `data:` URI, for documents viewed away from this machine.
+/// Looked up without a display, in the icon themes on disk; `name` may
+/// also be an absolute path.
+pub fn icon_data_uri(name: &str) -> Option<String> {
+    let path = if name.starts_with('/') {
+        PathBuf::from(name)
+    } else {
+        freedesktop_icons::lookup(name).with_size(32).find()?
+    };
 
-    None
+    let mime = match path.extension()?.to_str()? {
+        "png" => "image/png",
+        "svg" => "image/svg+xml",
+        _ => return None,
+    };
+    let data = std::fs::read(&path).ok()?;
+    Some(format!("data:{};base64,{}", mime, STANDARD.encode(data)))
 }
 
 /// Create an image widget for an application, with fallback
@@ -57,3 +112,35 @@ pub fn category_icon(icon_name: &str, size: i32) -> gtk::Image {
         image
     })
 }
+
+/// Create an image widget for a MIME type, falling back from the specific
+/// icon (image-png) to the generic one for its media type (image-x-generic)
+pub fn mime_icon(mime: &str, size: i32) -> gtk::Image {
+    let mut names = vec![mime.replace('/', "-")];
+
+    // Types the icon naming spec covers under other names
+    if mime == "inode/directory" {
+        names.push("folder".to_string());
+    } else if mime.starts_with("x-scheme-handler/") {
+        names.push("text-html".to_string());
+    }
+
+    // Generic icon declared in the shared MIME database
+    if let Some(generic) = gio::content_type_get_generic_icon_name(mime) {
+        names.push(generic.to_string());
+    }
+
+    if let Some((media, _)) = mime.split_once('/') {
+        names.push(format!("{}-x-generic", media));
+    }
+
+    for name in &names {
+        if let Some(image) = lookup_icon(name, size) {
+            return image;
+        }
+    }
+
+    let image = gtk::Image::from_icon_name("text-x-generic");
+    image.set_pixel_size(size);
+    image
+}
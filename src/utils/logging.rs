@@ -0,0 +1,193 @@
+use std::collections::VecDeque;
+use std::fmt::{self, Write as _};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// Records kept for the log panel; older ones are dropped
+const MAX_RECORDS: usize = 1000;
+
+/// Rotated log files kept next to `--log-file`
+const MAX_LOG_FILES: usize = 7;
+
+/// A log message kept for the log panel
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    pub time: SystemTime,
+    pub level: Level,
+    pub message: String,
+}
+
+struct LogBuffer {
+    records: VecDeque<LogRecord>,
+    /// Number of records ever added, including dropped ones
+    total: u64,
+}
+
+static BUFFER: Mutex<LogBuffer> = Mutex::new(LogBuffer {
+    records: VecDeque::new(),
+    total: 0,
+});
+
+/// Set up logging to the in-app log panel, to stderr unless `stderr` is
+/// false, and optionally to a daily rotated file. `verbosity` is the number
+/// of `-v` flags.
+pub fn init(verbosity: u8, log_file: Option<&Path>, stderr: bool) {
+    let level = level_filter(verbosity);
+
+    // The panel also shows our debug messages (skipped desktop files and
+    // the like), but only warnings from dependencies
+    let panel_filter = Targets::new()
+        .with_target(env!("CARGO_CRATE_NAME"), Level::DEBUG)
+        .with_default(Level::WARN);
+
+    let (file_appender, file_error) = match log_file.map(file_appender).transpose() {
+        Ok(appender) => (appender, None),
+        Err(e) => (None, Some(e)),
+    };
+    let file_layer = file_appender.map(|appender| {
+        tracing_subscriber::fmt::layer()
+            .with_writer(appender)
+            .with_ansi(false)
+            .with_filter(level)
+    });
+
+    tracing_subscriber::registry()
+        .with(stderr.then(|| tracing_subscriber::fmt::layer().with_filter(level)))
+        .with(file_layer)
+        .with(PanelLayer.with_filter(panel_filter))
+        .init();
+
+    if let Some(e) = file_error {
+        tracing::warn!("Not writing a log file: {:#}", e);
+    }
+}
+
+/// Level for stderr and the log file: info by default, `-v` for debug
+/// and `-vv` for trace
+fn level_filter(verbosity: u8) -> LevelFilter {
+    match verbosity {
+        0 => LevelFilter::INFO,
+        1 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    }
+}
+
+/// Appender writing to `path` with the date added before the extension,
+/// starting a new file every day and removing the oldest ones
+fn file_appender(path: &Path) -> anyhow::Result<RollingFileAppender> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let prefix = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("xdg-chooser");
+
+    let mut builder = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(prefix)
+        .max_log_files(MAX_LOG_FILES);
+    if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+        builder = builder.filename_suffix(ext);
+    }
+
+    Ok(builder.build(dir)?)
+}
+
+/// Records added since `seen` records had been read, and the number to pass
+/// next time
+pub fn records_since(seen: u64) -> (Vec<LogRecord>, u64) {
+    let buffer = BUFFER.lock().unwrap_or_else(|e| e.into_inner());
+    let new = buffer
+        .total
+        .saturating_sub(seen)
+        .min(buffer.records.len() as u64) as usize;
+    let records = buffer
+        .records
+        .iter()
+        .skip(buffer.records.len() - new)
+        .cloned()
+        .collect();
+    (records, buffer.total)
+}
+
+fn push(record: LogRecord) {
+    let mut buffer = BUFFER.lock().unwrap_or_else(|e| e.into_inner());
+    if buffer.records.len() == MAX_RECORDS {
+        buffer.records.pop_front();
+    }
+    buffer.records.push_back(record);
+    buffer.total += 1;
+}
+
+/// Tracing layer feeding the log panel. Events can come from any thread,
+/// so they are buffered here and the panel polls for them.
+struct PanelLayer;
+
+impl<S: Subscriber> Layer<S> for PanelLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        push(LogRecord {
+            time: SystemTime::now(),
+            level: *event.metadata().level(),
+            message: visitor.message,
+        });
+    }
+}
+
+/// Formats an event's message followed by any other fields
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.message, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_filter() {
+        assert_eq!(level_filter(0), LevelFilter::INFO);
+        assert_eq!(level_filter(1), LevelFilter::DEBUG);
+        assert_eq!(level_filter(2), LevelFilter::TRACE);
+        assert_eq!(level_filter(5), LevelFilter::TRACE);
+    }
+
+    #[test]
+    fn test_records_since() {
+        let (_, start) = records_since(u64::MAX);
+        for i in 0..3 {
+            push(LogRecord {
+                time: SystemTime::now(),
+                level: Level::INFO,
+                message: format!("message {}", i),
+            });
+        }
+
+        let (records, seen) = records_since(start + 1);
+        let messages: Vec<_> = records.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(messages, vec!["message 1", "message 2"]);
+        assert_eq!(records_since(seen).0.len(), 0);
+    }
+}
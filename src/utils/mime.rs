@@ -0,0 +1,46 @@
+use anyhow::{Context, Result};
+use gio::prelude::*;
+
+/// Human-readable, localized description of a MIME type from the shared MIME
+/// database (e.g. "PNG image" for image/png).
+///
+/// URL scheme handlers have no database entry and are described from their
+/// scheme instead. Returns `None` when nothing better than the raw type is known.
+pub fn mime_description(mime: &str) -> Option<String> {
+    if let Some(scheme) = mime.strip_prefix("x-scheme-handler/") {
+        return Some(format!("{}: links", scheme));
+    }
+
+    let description = gio::content_type_get_description(mime).to_string();
+
+    // GIO falls back to the type itself (or "<type> type") for unknown types
+    if description.is_empty() || description.starts_with(mime) {
+        return None;
+    }
+
+    Some(description)
+}
+
+/// MIME type of a file: `x-scheme-handler/<scheme>` for non-local URIs,
+/// otherwise the content type GIO detects from the name and contents
+/// (`inode/directory` for folders)
+pub fn file_mime_type(file: &gio::File) -> Result<String> {
+    if !file.has_uri_scheme("file") {
+        let scheme = file.uri_scheme().context("URI without a scheme")?;
+        return Ok(format!("x-scheme-handler/{}", scheme.to_lowercase()));
+    }
+
+    let info = file
+        .query_info(
+            gio::FILE_ATTRIBUTE_STANDARD_CONTENT_TYPE,
+            gio::FileQueryInfoFlags::NONE,
+            gio::Cancellable::NONE,
+        )
+        .with_context(|| format!("Failed to read {}", file.parse_name()))?;
+    let mime = info
+        .content_type()
+        .and_then(|content_type| gio::content_type_get_mime_type(&content_type))
+        .context("Couldn't determine the file type")?;
+
+    Ok(mime.to_string())
+}
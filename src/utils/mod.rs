@@ -1,2 +1,6 @@
-pub mod exec;
+pub use xdg_chooser_core::utils::{atomic, exec};
+
 pub mod icons;
+pub mod logging;
+pub mod mime;
+pub mod watch;
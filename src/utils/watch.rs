@@ -0,0 +1,82 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use gio::prelude::*;
+
+/// Watches a set of files for changes, keeping the underlying monitors alive.
+/// Dropping the watcher stops watching.
+pub struct LayerWatcher {
+    _monitors: Vec<gio::FileMonitor>,
+}
+
+impl LayerWatcher {
+    /// Watch the given files, which may not exist yet, and call `on_change`
+    /// with the path of a file whenever it is written, created, deleted or
+    /// renamed into place. Parent directories are monitored so that atomic
+    /// saves (write to a temp file, then rename) are seen as well.
+    ///
+    /// Events are delivered on the default main context.
+    pub fn new<F>(paths: &[PathBuf], on_change: F) -> Self
+    where
+        F: Fn(&Path) + 'static,
+    {
+        let on_change = Rc::new(on_change);
+
+        // Group the watched files by directory
+        let mut by_dir: HashMap<PathBuf, HashSet<PathBuf>> = HashMap::new();
+        for path in paths {
+            if let Some(parent) = path.parent() {
+                by_dir
+                    .entry(parent.to_path_buf())
+                    .or_default()
+                    .insert(path.clone());
+            }
+        }
+
+        let mut monitors = Vec::new();
+
+        for (dir, files) in by_dir {
+            if !dir.is_dir() {
+                tracing::debug!("Not watching missing directory {}", dir.display());
+                continue;
+            }
+
+            let monitor = match gio::File::for_path(&dir)
+                .monitor_directory(gio::FileMonitorFlags::WATCH_MOVES, gio::Cancellable::NONE)
+            {
+                Ok(monitor) => monitor,
+                Err(e) => {
+                    tracing::warn!("Failed to watch {}: {}", dir.display(), e);
+                    continue;
+                }
+            };
+
+            let on_change = Rc::clone(&on_change);
+            monitor.connect_changed(move |_, file, other_file, event| {
+                if matches!(
+                    event,
+                    gio::FileMonitorEvent::AttributeChanged
+                        | gio::FileMonitorEvent::PreUnmount
+                        | gio::FileMonitorEvent::Unmounted
+                ) {
+                    return;
+                }
+
+                for changed in [Some(file), other_file].into_iter().flatten() {
+                    if let Some(path) = changed.path() {
+                        if files.contains(&path) {
+                            on_change(&path);
+                        }
+                    }
+                }
+            });
+
+            monitors.push(monitor);
+        }
+
+        Self {
+            _monitors: monitors,
+        }
+    }
+}
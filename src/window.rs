@@ -1,37 +1,72 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::rc::Rc;
 
 use gtk::prelude::*;
 use gtk::{
-    ApplicationWindow, HeaderBar, Label, MenuButton, Orientation, Paned, PopoverMenu, SearchEntry,
-    Stack, StackTransitionType,
+    ApplicationWindow, Box as GtkBox, HeaderBar, Image, Label, MenuButton, Orientation, Paned,
+    PopoverMenu, SearchEntry, Stack, StackTransitionType, ToggleButton,
 };
 
+use crate::app::StartupOptions;
+use crate::config::changes::diff_defaults;
+use crate::config::settings::{Settings, ViewMode};
 use crate::config::MimeAppsConfig;
 use crate::desktop::categories::AppCategory;
 use crate::desktop::discovery::AppRegistry;
-use crate::ui::category_page::CategoryPage;
+use crate::desktop::running::bus_names_async;
+use crate::ui::alternatives_dialog::show_alternatives_dialog;
+use crate::ui::any_type_dialog::show_any_type_dialog;
+use crate::ui::categories_dialog::show_categories_dialog;
+use crate::ui::category_page::{CategoryPage, MimeRows};
+use crate::ui::duplicates_dialog::show_duplicates_dialog;
+use crate::ui::export_report::export_report;
+use crate::ui::external_changes::{notify_external_changes, notify_pinned_restored};
+use crate::ui::layers_dialog::show_layers_dialog;
+use crate::ui::log_panel::LogPanel;
+use crate::ui::presets_dialog::show_presets_dialog;
+use crate::ui::raw_editor::show_raw_editor;
+use crate::ui::saving::{after_saves, is_saving, save_in_background};
+use crate::ui::scheme_review_dialog::show_scheme_review_dialog;
+use crate::ui::search::AppFilter;
 use crate::ui::sidebar::CategorySidebar;
+use crate::ui::skel::apply_to_skel;
+use crate::utils::icons::clear_icon_cache;
+use crate::utils::watch::LayerWatcher;
+
+/// Milliseconds of typing pause before the search runs
+const SEARCH_DELAY_MS: u32 = 200;
 
 /// Main application window
+#[derive(Clone)]
 pub struct MainWindow {
     window: ApplicationWindow,
     registry: Rc<AppRegistry>,
     config: Rc<RefCell<MimeAppsConfig>>,
     stack: Stack,
     sidebar: Rc<CategorySidebar>,
+    /// Monitors every mimeapps.list layer while the window is open
+    watcher: Rc<RefCell<Option<LayerWatcher>>>,
+    /// Whether a reload waits for the saves being written
+    reload_pending: Rc<Cell<bool>>,
+    search: SearchEntry,
+    /// Search filter of each category's current page
+    filters: Rc<RefCell<HashMap<AppCategory, AppFilter>>>,
+    /// Individual MIME type rows of each category's current page
+    mime_rows: Rc<RefCell<HashMap<AppCategory, MimeRows>>>,
+    /// Names owned on the session bus when the pages were last rebuilt,
+    /// for marking running apps
+    bus_names: Rc<RefCell<Vec<String>>>,
 }
 
 impl MainWindow {
-    pub fn new(app: &gtk::Application) -> Self {
+    pub fn new(app: &gtk::Application, options: &StartupOptions) -> Self {
         // Load data
-        let registry = Rc::new(AppRegistry::new());
-        let config = Rc::new(RefCell::new(
-            MimeAppsConfig::load().unwrap_or_else(|e| {
-                tracing::warn!("Failed to load config: {}, using defaults", e);
-                MimeAppsConfig::default()
-            }),
-        ));
+        let (config, registry) = options.load_data();
+        let config = Rc::new(RefCell::new(config));
+        let registry = Rc::new(registry);
+
+        let locked = config.borrow().settings.locked;
 
         // Create window
         let window = ApplicationWindow::builder()
@@ -43,6 +78,15 @@ impl MainWindow {
 
         // Create header bar
         let header = Self::create_header_bar();
+        let search = Self::create_search_entry(&window);
+        header.pack_start(&search);
+        let view_toggle = Self::create_view_toggle();
+        header.pack_end(&view_toggle);
+        let log_toggle = Self::create_log_toggle();
+        header.pack_end(&log_toggle);
+        if locked {
+            header.pack_start(&Self::create_locked_indicator());
+        }
         window.set_titlebar(Some(&header));
 
         // Main layout
@@ -52,7 +96,11 @@ impl MainWindow {
         paned.set_shrink_end_child(false);
 
         // Sidebar
-        let sidebar = Rc::new(CategorySidebar::new());
+        let categories = {
+            let settings = &config.borrow().settings;
+            settings.ordered_categories(registry.visible_categories(settings))
+        };
+        let sidebar = Rc::new(CategorySidebar::new(categories));
 
         // Wrap sidebar in a scrolled window
         let sidebar_scroll = gtk::ScrolledWindow::builder()
@@ -70,8 +118,18 @@ impl MainWindow {
 
         // We'll create pages lazily
         paned.set_end_child(Some(&stack));
+        paned.set_vexpand(true);
 
-        window.set_child(Some(&paned));
+        // Log panel below everything else, hidden until toggled
+        let log_panel = LogPanel::new();
+        log_toggle
+            .bind_property("active", &log_panel.widget, "reveal-child")
+            .build();
+
+        let layout = GtkBox::new(Orientation::Vertical, 0);
+        layout.append(&paned);
+        layout.append(&log_panel.widget);
+        window.set_child(Some(&layout));
 
         let main_window = Self {
             window,
@@ -79,13 +137,37 @@ impl MainWindow {
             config,
             stack,
             sidebar,
+            watcher: Rc::new(RefCell::new(None)),
+            reload_pending: Rc::new(Cell::new(false)),
+            search,
+            filters: Rc::new(RefCell::new(HashMap::new())),
+            mime_rows: Rc::new(RefCell::new(HashMap::new())),
+            bus_names: Rc::new(RefCell::new(Vec::new())),
         };
 
         // Create initial pages
         main_window.create_category_pages();
+        main_window
+            .sidebar
+            .update_status(&main_window.config.borrow());
+        main_window.refresh_running_apps();
 
         // Connect sidebar selection
         main_window.connect_sidebar();
+        main_window
+            .sidebar
+            .update_visibility(&main_window.config.borrow().settings);
+
+        // Switch between list and grid layouts
+        main_window.connect_view_toggle(&view_toggle);
+
+        main_window.connect_search();
+
+        main_window.setup_actions();
+
+        main_window.watch_external_changes();
+
+        main_window.watch_icon_theme();
 
         main_window
     }
@@ -98,13 +180,6 @@ impl MainWindow {
         title.add_css_class("title");
         header.set_title_widget(Some(&title));
 
-        // Search entry (for future implementation)
-        let search = SearchEntry::new();
-        search.set_placeholder_text(Some("Search applications..."));
-        search.set_width_chars(25);
-        search.set_visible(false); // Hide for now, can enable later
-        // header.pack_start(&search);
-
         // Menu button
         let menu_btn = Self::create_menu_button();
         header.pack_end(&menu_btn);
@@ -112,6 +187,264 @@ impl MainWindow {
         header
     }
 
+    /// Search entry filtering the applications of every page. Typing
+    /// anywhere in the window starts a search, except in the sidebar, which
+    /// jumps to categories as you type.
+    fn create_search_entry(window: &ApplicationWindow) -> SearchEntry {
+        let search = SearchEntry::new();
+        search.set_placeholder_text(Some("Search applications..."));
+        search.set_width_chars(25);
+        search.set_key_capture_widget(Some(window));
+        // Wait for a pause in typing before filtering
+        search.set_search_delay(SEARCH_DELAY_MS);
+        search
+    }
+
+    fn connect_search(&self) {
+        let filters = Rc::clone(&self.filters);
+        self.search.connect_search_changed(move |search| {
+            let query = search.text();
+            for filter in filters.borrow().values() {
+                filter.apply(&query);
+            }
+        });
+    }
+
+    fn create_locked_indicator() -> Image {
+        let icon = Image::from_icon_name("changes-prevent-symbolic");
+        icon.set_tooltip_text(Some(
+            "Read-only: default applications can't be changed here",
+        ));
+        icon
+    }
+
+    fn create_view_toggle() -> ToggleButton {
+        let toggle = ToggleButton::new();
+        toggle.set_icon_name("view-grid-symbolic");
+        toggle.set_tooltip_text(Some("Show applications as a grid"));
+        toggle
+    }
+
+    fn create_log_toggle() -> ToggleButton {
+        let toggle = ToggleButton::new();
+        toggle.set_icon_name("utilities-terminal-symbolic");
+        toggle.set_tooltip_text(Some("Show log"));
+        toggle
+    }
+
+    fn connect_view_toggle(&self, toggle: &ToggleButton) {
+        toggle.set_active(self.config.borrow().settings.view_mode == ViewMode::Grid);
+
+        let main_window = self.clone();
+        toggle.connect_toggled(move |toggle| {
+            let mode = if toggle.is_active() {
+                ViewMode::Grid
+            } else {
+                ViewMode::List
+            };
+            let mut config = main_window.config.borrow_mut();
+            if config.settings.view_mode == mode {
+                return;
+            }
+            config.settings.view_mode = mode;
+            if let Err(e) = config.save_settings() {
+                tracing::error!("Failed to save settings: {:#}", e);
+            }
+            drop(config);
+            main_window.rebuild_pages();
+        });
+    }
+
+    fn setup_actions(&self) {
+        // Offer the presets shipped in presets.d directories
+        let presets_action = gio::SimpleAction::new("presets", None);
+        let main_window = self.clone();
+        presets_action.connect_activate(move |_, _| {
+            let on_applied = {
+                let main_window = main_window.clone();
+                move || main_window.rebuild_pages()
+            };
+            show_presets_dialog(
+                Rc::clone(&main_window.registry),
+                Rc::clone(&main_window.config),
+                on_applied,
+            );
+        });
+        presets_action.set_enabled(!self.config.borrow().settings.locked);
+        self.window.add_action(&presets_action);
+
+        // Save a Markdown or JSON report of the current defaults
+        let export_action = gio::SimpleAction::new("export-report", None);
+        let main_window = self.clone();
+        export_action.connect_activate(move |_, _| {
+            export_report(&main_window.config.borrow(), &main_window.registry);
+        });
+        self.window.add_action(&export_action);
+
+        // Choose which categories the sidebar lists
+        let categories_action = gio::SimpleAction::new("categories", None);
+        let main_window = self.clone();
+        categories_action.connect_activate(move |_, _| {
+            let on_changed = {
+                let main_window = main_window.clone();
+                move || {
+                    main_window
+                        .sidebar
+                        .update_visibility(&main_window.config.borrow().settings)
+                }
+            };
+            let on_edited = {
+                let main_window = main_window.clone();
+                move || main_window.reload_categories()
+            };
+            show_categories_dialog(
+                Rc::clone(&main_window.config),
+                Rc::clone(&main_window.registry),
+                &main_window.sidebar.categories(),
+                on_changed,
+                on_edited,
+            );
+        });
+        self.window.add_action(&categories_action);
+
+        // Debug view of how the mimeapps.list files are merged
+        let layers_action = gio::SimpleAction::new("layers", None);
+        layers_action.connect_activate(|_, _| show_layers_dialog());
+        layers_action.set_enabled(!self.config.borrow().is_in_memory());
+        self.window.add_action(&layers_action);
+
+        // Set the default of a type no category lists
+        let any_type_action = gio::SimpleAction::new("any-type", None);
+        let main_window = self.clone();
+        any_type_action.connect_activate(move |_, _| {
+            let on_changed = {
+                let main_window = main_window.clone();
+                move || main_window.rebuild_pages()
+            };
+            show_any_type_dialog(
+                Rc::clone(&main_window.registry),
+                Rc::clone(&main_window.config),
+                on_changed,
+            );
+        });
+        self.window.add_action(&any_type_action);
+
+        // Installed apps that aren't the default for anything
+        let alternatives_action = gio::SimpleAction::new("alternatives", None);
+        let main_window = self.clone();
+        alternatives_action.connect_activate(move |_, _| {
+            let on_changed = {
+                let main_window = main_window.clone();
+                move || main_window.rebuild_pages()
+            };
+            show_alternatives_dialog(
+                Rc::clone(&main_window.registry),
+                Rc::clone(&main_window.config),
+                on_changed,
+            );
+        });
+        self.window.add_action(&alternatives_action);
+
+        // Choose between native and Flatpak copies of the same app
+        let duplicates_action = gio::SimpleAction::new("duplicates", None);
+        let main_window = self.clone();
+        duplicates_action.connect_activate(move |_, _| {
+            let on_changed = {
+                let main_window = main_window.clone();
+                move || main_window.rebuild_pages()
+            };
+            show_duplicates_dialog(
+                Rc::clone(&main_window.registry),
+                Rc::clone(&main_window.config),
+                on_changed,
+            );
+        });
+        self.window.add_action(&duplicates_action);
+
+        // Point out scheme handlers that links could be used to exploit
+        let scheme_review_action = gio::SimpleAction::new("review-schemes", None);
+        let main_window = self.clone();
+        scheme_review_action.connect_activate(move |_, _| {
+            show_scheme_review_dialog(&main_window.config.borrow(), &main_window.registry);
+        });
+        self.window.add_action(&scheme_review_action);
+
+        // Edit the user's mimeapps.list as text
+        let raw_editor_action = gio::SimpleAction::new("edit-raw", None);
+        let main_window = self.clone();
+        raw_editor_action.connect_activate(move |_, _| {
+            let on_saved = {
+                let main_window = main_window.clone();
+                move || main_window.reload_after_edit()
+            };
+            show_raw_editor(main_window.config.borrow().path().clone(), on_saved);
+        });
+        raw_editor_action.set_enabled({
+            let config = self.config.borrow();
+            !config.is_in_memory() && !config.settings.locked
+        });
+        self.window.add_action(&raw_editor_action);
+
+        // Admin action: give new accounts the current defaults
+        let skel_action = gio::SimpleAction::new("apply-to-skel", None);
+        let main_window = self.clone();
+        skel_action.connect_activate(move |_, _| {
+            apply_to_skel(main_window.config.borrow().path().clone());
+        });
+        skel_action.set_enabled(!self.config.borrow().is_in_memory());
+        self.window.add_action(&skel_action);
+
+        // Preferences shown as checkboxes in the menu
+        self.add_settings_toggle(
+            "advanced",
+            |settings| settings.advanced,
+            |settings, enabled| settings.advanced = enabled,
+        );
+        self.add_settings_toggle(
+            "clean-environment",
+            |settings| settings.launch.clean_environment,
+            |settings, enabled| settings.launch.clean_environment = enabled,
+        );
+        let native_action = self.add_settings_toggle(
+            "prefer-native",
+            |settings| settings.prefer_native_apps,
+            |settings, enabled| settings.prefer_native_apps = enabled,
+        );
+        // Which apps are shown up front depends on the ranking
+        let main_window = self.clone();
+        native_action.connect_state_notify(move |_| main_window.rebuild_pages());
+    }
+
+    /// Add a boolean window action that reads and saves a setting, and
+    /// return it for listening to state changes
+    fn add_settings_toggle(
+        &self,
+        name: &str,
+        get: fn(&Settings) -> bool,
+        set: fn(&mut Settings, bool),
+    ) -> gio::SimpleAction {
+        let initial = get(&self.config.borrow().settings);
+        let action = gio::SimpleAction::new_stateful(name, None, &initial.to_variant());
+        let config = Rc::clone(&self.config);
+        action.connect_change_state(move |action, value| {
+            let Some(enabled) = value.and_then(|v| v.get::<bool>()) else {
+                return;
+            };
+
+            {
+                let mut config = config.borrow_mut();
+                set(&mut config.settings, enabled);
+                if let Err(e) = config.save_settings() {
+                    tracing::error!("Failed to save settings: {:#}", e);
+                }
+            }
+            // Last, so state listeners see the new setting
+            action.set_state(&enabled.to_variant());
+        });
+        self.window.add_action(&action);
+        action
+    }
+
     fn create_menu_button() -> MenuButton {
         let menu_btn = MenuButton::new();
         menu_btn.set_icon_name("open-menu-symbolic");
@@ -119,6 +452,22 @@ impl MainWindow {
 
         // Create menu
         let menu = gio::Menu::new();
+        menu.append(Some("Presets…"), Some("win.presets"));
+        menu.append(Some("Export Report…"), Some("win.export-report"));
+        menu.append(Some("Configuration Layers…"), Some("win.layers"));
+        menu.append(Some("Edit Raw File…"), Some("win.edit-raw"));
+        menu.append(Some("Review Scheme Handlers…"), Some("win.review-schemes"));
+        menu.append(Some("Alternatives…"), Some("win.alternatives"));
+        menu.append(Some("Duplicate Applications…"), Some("win.duplicates"));
+        menu.append(Some("Categories…"), Some("win.categories"));
+        menu.append(Some("Set Default for Any Type…"), Some("win.any-type"));
+        menu.append(Some("Apply to New Accounts…"), Some("win.apply-to-skel"));
+        menu.append(Some("Prefer Desktop's Own Apps"), Some("win.prefer-native"));
+        menu.append(Some("Advanced Mode"), Some("win.advanced"));
+        menu.append(
+            Some("Clean Test Environment"),
+            Some("win.clean-environment"),
+        );
         menu.append(Some("About"), Some("win.about"));
         menu.append(Some("Quit"), Some("app.quit"));
 
@@ -129,57 +478,275 @@ impl MainWindow {
     }
 
     fn create_category_pages(&self) {
-        for category in AppCategory::all() {
+        for category in self.sidebar.categories() {
             self.create_page_for_category(&category);
         }
     }
 
     fn create_page_for_category(&self, category: &AppCategory) {
-        let registry = Rc::clone(&self.registry);
-        let config = Rc::clone(&self.config);
-        let stack = self.stack.clone();
+        // Rebuild the page and refresh the sidebar when defaults change
+        let main_window = self.clone();
         let category_clone = category.clone();
-
-        // Create a callback that rebuilds the page when defaults change
-        let on_default_changed = move || {
-            // Rebuild the page
-            if let Some(child) = stack.child_by_name(category_clone.display_name()) {
-                stack.remove(&child);
-            }
-
-            let page = CategoryPage::new(
-                category_clone.clone(),
-                Rc::clone(&registry),
-                Rc::clone(&config),
-                || {}, // No recursive rebuilding
-            );
-            stack.add_named(&page.widget, Some(category_clone.display_name()));
-        };
+        let on_default_changed = move || main_window.refresh_page(&category_clone);
 
         let page = CategoryPage::new(
             category.clone(),
             Rc::clone(&self.registry),
             Rc::clone(&self.config),
+            &self.bus_names.borrow(),
             on_default_changed,
         );
 
+        let query = self.search.text();
+        if !query.is_empty() {
+            page.filter().apply(&query);
+        }
+        self.filters
+            .borrow_mut()
+            .insert(category.clone(), page.filter().clone());
+        self.mime_rows
+            .borrow_mut()
+            .insert(category.clone(), page.mime_rows().clone());
+
         self.stack
             .add_named(&page.widget, Some(category.display_name()));
     }
 
+    /// Rebuild a single category page, keeping it visible if it was
+    fn refresh_page(&self, category: &AppCategory) {
+        self.sidebar.update_status(&self.config.borrow());
+
+        let name = category.display_name();
+        let was_visible = self.stack.visible_child_name().as_deref() == Some(name);
+        if let Some(child) = self.stack.child_by_name(name) {
+            self.stack.remove(&child);
+        }
+
+        self.create_page_for_category(category);
+        if was_visible {
+            self.stack.set_visible_child_name(name);
+        }
+    }
+
     fn connect_sidebar(&self) {
         let stack = self.stack.clone();
         self.sidebar.connect_category_selected(move |category| {
             stack.set_visible_child_name(category.display_name());
         });
+
+        // Text typed into the sidebar that named no category is a search
+        let search = self.search.clone();
+        self.sidebar.connect_search_requested(move |text| {
+            search.set_text(text);
+            search.grab_focus();
+            search.set_position(-1);
+        });
+
+        // Remember the order categories were dragged into
+        let config = Rc::clone(&self.config);
+        self.sidebar.connect_reordered(move |categories| {
+            let mut config = config.borrow_mut();
+            config.settings.set_category_order(categories);
+            if let Err(e) = config.save_settings() {
+                tracing::error!("Failed to save settings: {:#}", e);
+            }
+        });
+    }
+
+    /// The toplevel window
+    pub fn window(&self) -> &ApplicationWindow {
+        &self.window
+    }
+
+    /// Switch to a category's page
+    pub fn show_category(&self, category: &AppCategory) {
+        self.sidebar.select_category(category);
+    }
+
+    /// Point at the row of the first of `mimes` in a category page's
+    /// individual MIME type settings
+    pub fn reveal_mime_type(&self, category: &AppCategory, mimes: &[String]) {
+        if let Some(rows) = self.mime_rows.borrow().get(category) {
+            rows.reveal(mimes);
+        }
+    }
+
+    /// Watch every mimeapps.list layer for changes made by other programs
+    fn watch_external_changes(&self) {
+        if self.config.borrow().is_in_memory() {
+            return;
+        }
+        let paths = match MimeAppsConfig::layer_paths() {
+            Ok(paths) => paths,
+            Err(e) => {
+                tracing::warn!("Not watching for external changes: {:#}", e);
+                return;
+            }
+        };
+
+        let main_window = self.clone();
+        let watcher = LayerWatcher::new(&paths, move |path| {
+            tracing::debug!("{} changed on disk", path.display());
+            main_window.reload_config();
+        });
+        self.watcher.replace(Some(watcher));
+    }
+
+    /// Redo icon lookups when the icon theme changes (the user picked another
+    /// theme, or icons were installed), so old and new icons aren't mixed:
+    /// icons found as files rather than in the theme don't update by
+    /// themselves.
+    fn watch_icon_theme(&self) {
+        let theme = gtk::IconTheme::for_display(&WidgetExt::display(&self.window));
+
+        let main_window = self.clone();
+        let handler = theme.connect_changed(move |_| {
+            tracing::debug!("Icon theme changed, refreshing icons");
+            clear_icon_cache();
+            main_window.sidebar.refresh_icons();
+            main_window.rebuild_pages();
+        });
+
+        // The theme outlives the window
+        let handler = RefCell::new(Some(handler));
+        self.window.connect_destroy(move |_| {
+            if let Some(handler) = handler.take() {
+                theme.disconnect(handler);
+            }
+        });
+    }
+
+    /// Reload the configuration after a file changed on disk. Our own saves
+    /// come back here too but leave the defaults as they were; anything
+    /// else is shown in the pages and reported in a notification.
+    fn reload_config(&self) {
+        // Replacing the config now would drop the edits still waiting to be
+        // written, so reload once, after the last save
+        if is_saving() {
+            if !self.reload_pending.replace(true) {
+                let main_window = self.clone();
+                after_saves(move || {
+                    main_window.reload_pending.set(false);
+                    main_window.reload_config();
+                });
+            }
+            return;
+        }
+
+        let mut fresh = match MimeAppsConfig::load() {
+            Ok(config) => config,
+            Err(e) => {
+                tracing::warn!("Failed to reload config: {:#}", e);
+                return;
+            }
+        };
+
+        fresh.settings.locked = self.config.borrow().settings.locked;
+
+        // Guard pinned defaults before looking at what changed, so restored
+        // ones only show up as restored
+        let restored = fresh.restore_pinned().unwrap_or_else(|e| {
+            tracing::error!("Failed to restore pinned defaults: {:#}", e);
+            Vec::new()
+        });
+
+        let changes = diff_defaults(
+            &self.config.borrow().effective_defaults(),
+            &fresh.effective_defaults(),
+        );
+        let locked = fresh.settings.locked;
+        self.config.replace(fresh);
+        if !restored.is_empty() {
+            save_in_background(&self.config, || {});
+        }
+
+        if let Some(app) = self.window.application() {
+            if !restored.is_empty() {
+                notify_pinned_restored(app.upcast_ref(), &restored, &self.registry);
+            }
+        }
+        for change in &restored {
+            tracing::info!(
+                "Restored pinned default for {}: {} -> {}",
+                change.mime,
+                change.old.as_deref().unwrap_or("(none)"),
+                change.new.as_deref().unwrap_or("(none)")
+            );
+        }
+
+        if changes.is_empty() {
+            return;
+        }
+        for change in &changes {
+            tracing::info!(
+                "{} changed externally: {} -> {}",
+                change.mime,
+                change.old.as_deref().unwrap_or("(none)"),
+                change.new.as_deref().unwrap_or("(none)")
+            );
+        }
+
+        self.rebuild_pages();
+        if let Some(app) = self.window.application() {
+            notify_external_changes(app.upcast_ref(), &changes, &self.registry, !locked);
+        }
+    }
+
+    /// Load the edits made in the raw file editor right away, rather than
+    /// reporting them as external changes once the file watcher notices
+    fn reload_after_edit(&self) {
+        let mut fresh = match MimeAppsConfig::load() {
+            Ok(config) => config,
+            Err(e) => {
+                tracing::warn!("Failed to reload config: {:#}", e);
+                return;
+            }
+        };
+        fresh.settings.locked = self.config.borrow().settings.locked;
+        self.config.replace(fresh);
+        self.rebuild_pages();
+    }
+
+    /// Restore defaults from before an external change. An empty app ID
+    /// removes the default.
+    pub fn revert_defaults(&self, previous: &[(String, String)]) {
+        let mut config = self.config.borrow_mut();
+        for (mime, app_id) in previous {
+            if app_id.is_empty() {
+                config.remove_default(mime);
+            } else if let Err(e) = config.set_default(mime, app_id) {
+                tracing::error!("Failed to restore default for {}: {}", mime, e);
+                return;
+            }
+        }
+        drop(config);
+        save_in_background(&self.config, || {});
+        self.rebuild_pages();
     }
 
-    pub fn present(&self) {
-        self.window.present();
+    /// List the categories again after user-defined ones were added,
+    /// edited or deleted, and return them
+    fn reload_categories(&self) -> Vec<AppCategory> {
+        let categories = {
+            let settings = &self.config.borrow().settings;
+            settings.ordered_categories(self.registry.visible_categories(settings))
+        };
+        self.sidebar.set_categories(categories.clone());
+        self.sidebar
+            .update_visibility(&self.config.borrow().settings);
+        self.rebuild_pages();
+        categories
     }
 
     /// Rebuild all category pages (e.g., after settings change)
     pub fn rebuild_pages(&self) {
+        self.recreate_pages();
+        self.refresh_running_apps();
+    }
+
+    /// Replace every page, marking the apps that were running at the last
+    /// rebuild
+    fn recreate_pages(&self) {
         // Remove all children
         while let Some(child) = self.stack.first_child() {
             self.stack.remove(&child);
@@ -187,6 +754,7 @@ impl MainWindow {
 
         // Recreate pages
         self.create_category_pages();
+        self.sidebar.update_status(&self.config.borrow());
 
         // Re-select current category
         if let Some(row) = self.sidebar.widget.selected_row() {
@@ -196,4 +764,21 @@ impl MainWindow {
             }
         }
     }
+
+    /// Ask the session bus which apps are running, once per rebuild and
+    /// without waiting for it, and recreate the pages if that changed
+    fn refresh_running_apps(&self) {
+        let main_window = self.clone();
+        bus_names_async(move |names| {
+            let names = names.unwrap_or_else(|e| {
+                tracing::debug!("Not marking running apps: {:#}", e);
+                Vec::new()
+            });
+            if *main_window.bus_names.borrow() == names {
+                return;
+            }
+            *main_window.bus_names.borrow_mut() = names;
+            main_window.recreate_pages();
+        });
+    }
 }